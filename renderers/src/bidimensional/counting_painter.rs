@@ -0,0 +1,192 @@
+use std::ops::Range;
+
+use candy_shared_types::{BlendMode, CornerRadii, Damage, Rect};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer,
+    primitives::{CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText},
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+///How many calls of each kind were made on a painter
+pub struct PainterCalls {
+    pub squares: usize,
+    pub circles: usize,
+    ///Texts drawn, whole or sliced
+    pub texts: usize,
+    pub images: usize,
+    ///Sprite sheets drawn, no matter how many sprites each one has got
+    pub sprites: usize,
+    pub paths: usize,
+    pub backgrounds: usize,
+    ///Pictures replayed, each one counting as a single call no matter how many were recorded on it
+    pub pictures: usize,
+    ///Saves, with or without a layer
+    pub saves: usize,
+    ///Saves that started a new layer
+    pub layers: usize,
+    pub clips: usize,
+    ///Transforms applied, such as translations, scales and rotations
+    pub transforms: usize,
+}
+
+impl PainterCalls {
+    ///Retrieves how many calls actually drew something
+    pub fn draws(&self) -> usize {
+        self.squares
+            + self.circles
+            + self.texts
+            + self.images
+            + self.sprites
+            + self.paths
+            + self.backgrounds
+            + self.pictures
+    }
+
+    ///Retrieves how many calls were made, including the ones that only change the clip and transform
+    pub fn total(&self) -> usize {
+        self.draws() + self.saves + self.clips + self.transforms
+    }
+}
+
+#[derive(Debug)]
+///A painter that forwards every call made on it to another one, counting them by kind. Used to find out what a frame costs
+pub struct CountingPainter<'a> {
+    inner: &'a mut dyn BiDimensionalPainter,
+    calls: PainterCalls,
+}
+
+impl<'a> CountingPainter<'a> {
+    ///Creates a new painter that draws everything on `inner`
+    pub fn new(inner: &'a mut dyn BiDimensionalPainter) -> Self {
+        Self {
+            inner,
+            calls: PainterCalls::default(),
+        }
+    }
+
+    #[inline]
+    ///Retrieves how many calls were made until now
+    pub fn calls(&self) -> &PainterCalls {
+        &self.calls
+    }
+
+    #[inline]
+    ///Starts counting from zero again
+    pub fn reset(&mut self) {
+        self.calls = PainterCalls::default();
+    }
+}
+
+impl BiDimensionalRenderer for CountingPainter<'_> {
+    fn resize(&mut self, window: &Window, width: u32, height: u32) {
+        self.inner.resize(window, width, height);
+    }
+
+    fn flush(&mut self) {
+        self.inner.flush();
+    }
+
+    fn begin_frame(&mut self, damage: &Damage) -> Damage {
+        self.inner.begin_frame(damage)
+    }
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for CountingPainter<'_> {
+    fn square(&mut self, square_info: &CandySquare) {
+        self.calls.squares += 1;
+        self.inner.square(square_info);
+    }
+
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        self.calls.circles += 1;
+        self.inner.circle(position, color, radius);
+    }
+
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        self.calls.texts += 1;
+        self.inner.text_sliced(info, range);
+    }
+
+    fn text(&mut self, info: &CandyText) {
+        self.calls.texts += 1;
+        self.inner.text(info);
+    }
+
+    fn render_image(&mut self, info: &CandyImage) {
+        self.calls.images += 1;
+        self.inner.render_image(info);
+    }
+
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        self.calls.sprites += 1;
+        self.inner.sprites(sheet);
+    }
+
+    fn path(&mut self, path: &CandyPath) {
+        self.calls.paths += 1;
+        self.inner.path(path);
+    }
+
+    fn background(&mut self, color: &Vector4<f32>) {
+        self.calls.backgrounds += 1;
+        self.inner.background(color);
+    }
+
+    fn save(&mut self) {
+        self.calls.saves += 1;
+        self.inner.save();
+    }
+
+    fn restore(&mut self) {
+        self.inner.restore();
+    }
+
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, bounds: Option<&Rect>) {
+        self.calls.saves += 1;
+        self.calls.layers += 1;
+        self.inner.save_layer(alpha, blend_mode, bounds);
+    }
+
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>) {
+        self.calls.clips += 1;
+        self.inner.clip_rounded_rect(rect, radius);
+    }
+
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        self.calls.clips += 1;
+        self.inner.clip_rounded_rect_radii(rect, radii);
+    }
+
+    fn clip_rects(&mut self, rects: &[Rect]) {
+        self.calls.clips += 1;
+        self.inner.clip_rects(rects);
+    }
+
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        self.calls.transforms += 1;
+        self.inner.concat(matrix);
+    }
+
+    fn begin_picture(&mut self) -> bool {
+        self.inner.begin_picture()
+    }
+
+    fn end_picture(&mut self) -> Option<CandyPicture> {
+        self.inner.end_picture()
+    }
+
+    fn picture(&mut self, picture: &CandyPicture) -> bool {
+        let drawn = self.inner.picture(picture);
+        if drawn {
+            self.calls.pictures += 1;
+        }
+        drawn
+    }
+}
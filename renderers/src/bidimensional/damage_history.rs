@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use candy_shared_types::Damage;
+
+///Most frames kept. Buffers older than this are repainted whole, which is what happens when their age is unknown anyway
+const MAX_AGE: usize = 4;
+
+#[derive(Debug, Default)]
+///The damage of the last frames presented, so a buffer that still holds an older frame, as happens on swapchains, can be brought up
+///to date by repainting only what changed since it
+pub(crate) struct DamageHistory {
+    ///The damage of each frame presented, from the last one to the oldest
+    frames: VecDeque<Damage>,
+}
+
+impl DamageHistory {
+    ///Retrieves what must be repainted on a buffer holding the frame presented `age` frames ago so it shows the current one, whose
+    ///`damage` is the given one. An age of 0 means the contents of the buffer are unknown, so everything is
+    pub(crate) fn repaint(&self, damage: &Damage, age: u32) -> Damage {
+        let age = age as usize;
+        if age == 0 || age > self.frames.len() + 1 {
+            return Damage::full();
+        }
+        let mut out = damage.clone();
+        for frame in self.frames.iter().take(age - 1) {
+            out.merge(frame);
+        }
+        out
+    }
+
+    ///Records the `damage` of the frame just presented
+    pub(crate) fn push(&mut self, damage: Damage) {
+        self.frames.push_front(damage);
+        self.frames.truncate(MAX_AGE);
+    }
+
+    #[inline]
+    ///Forgets every frame, such as when the buffers are recreated
+    pub(crate) fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candy_shared_types::Rect;
+
+    use super::*;
+
+    fn damage(x: f32) -> Damage {
+        let mut out = Damage::new();
+        out.add(Rect::new(x, 0.0, 10.0, 10.0));
+        out
+    }
+
+    #[test]
+    fn repaint_adds_the_frames_since_the_age() {
+        let mut history = DamageHistory::default();
+        history.push(damage(100.0));
+        history.push(damage(50.0));
+        let current = damage(0.0);
+
+        assert_eq!(history.repaint(&current, 1), current);
+        assert_eq!(history.repaint(&current, 2).rects().len(), 2);
+        let mut expected = damage(0.0);
+        expected.add(Rect::new(50.0, 0.0, 10.0, 10.0));
+        expected.add(Rect::new(100.0, 0.0, 10.0, 10.0));
+        assert_eq!(history.repaint(&current, 3), expected);
+    }
+
+    #[test]
+    fn unknown_or_too_old_ages_repaint_everything() {
+        let mut history = DamageHistory::default();
+        history.push(damage(50.0));
+        let current = damage(0.0);
+        assert!(history.repaint(&current, 0).is_full());
+        assert!(history.repaint(&current, 3).is_full());
+        history.clear();
+        assert!(history.repaint(&current, 2).is_full());
+    }
+
+    #[test]
+    fn only_the_last_frames_are_kept() {
+        let mut history = DamageHistory::default();
+        for index in 0..MAX_AGE + 2 {
+            history.push(damage(index as f32 * 20.0));
+        }
+        let current = Damage::new();
+        assert!(!history.repaint(&current, MAX_AGE as u32 + 1).is_full());
+        assert!(history.repaint(&current, MAX_AGE as u32 + 2).is_full());
+    }
+}
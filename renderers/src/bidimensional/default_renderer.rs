@@ -1,24 +1,28 @@
 use std::ops::Range;
 
-use candy_shared_types::vec4f32_to_color;
 #[cfg(feature = "opengl")]
 use glutin::{
     config::Config,
     context::{NotCurrentContext, PossiblyCurrentContext},
     surface::{Surface, WindowSurface},
 };
-use nalgebra::Vector4;
+use candy_shared_types::{BlendMode, CornerRadii, Damage, Rect};
+use nalgebra::{Matrix3, Vector2, Vector4};
 #[cfg(feature = "opengl")]
 use raw_window_handle::RawWindowHandle;
 #[cfg(feature = "opengl")]
 use skia_safe::gpu::gl::FramebufferInfo;
-use skia_safe::{Canvas, Paint, Point, RRect, Rect, SamplingOptions, canvas::SrcRectConstraint};
+use skia_safe::Canvas;
 #[cfg(feature = "opengl")]
 use winit::window::Window;
 
 use crate::{
     BiDimensionalPainter, BiDimensionalRenderer, BiDimensionalRendererConstructor,
-    primitives::{CandyImage, CandySquare, CandyText},
+    bidimensional::{
+        damage_history::DamageHistory,
+        skia::{self, PictureRecorders},
+    },
+    primitives::{CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText},
 };
 
 pub fn create_surface(
@@ -71,6 +75,27 @@ pub fn create_context(handle: Option<RawWindowHandle>, config: &Config) -> NotCu
     }
 }
 
+#[cfg(feature = "opengl")]
+///Swaps the buffers of `surface` telling the compositor that only `rects`, with their origin at the bottom left corner, changed. Surfaces
+///that can't tell it just swap the buffers
+fn swap_buffers_with_damage(
+    surface: &Surface<WindowSurface>,
+    context: &PossiblyCurrentContext,
+    rects: &[glutin::surface::Rect],
+) -> glutin::error::Result<()> {
+    use glutin::surface::GlSurface;
+
+    #[cfg(all(
+        any(windows, unix),
+        not(any(target_os = "macos", target_os = "ios")),
+        not(target_family = "wasm")
+    ))]
+    if let (Surface::Egl(surface), PossiblyCurrentContext::Egl(context)) = (surface, context) {
+        return surface.swap_buffers_with_damage(context, rects);
+    }
+    surface.swap_buffers(context)
+}
+
 #[derive(Debug)]
 ///Internal environemtn for rendering stuff on the 2D default renderer
 struct Renderer2DEnvironment {
@@ -87,17 +112,23 @@ struct Renderer2DEnvironment {
     samples: usize,
     #[cfg(feature = "opengl")]
     stencil_size: usize,
+    ///The damage of the last frames presented, as the back buffer may hold an older one
+    history: DamageHistory,
+    ///The damage of the frame being drawn, presented when flushed. `None` if the frame didn't start with `begin_frame`, so everything is
+    damage: Option<Damage>,
 }
 
 #[derive(Debug)]
 ///The default renderer that is be used when drawing 2D
 pub struct Candy2DefaultRenderer {
     environment: Renderer2DEnvironment,
+    pictures: PictureRecorders,
 }
 impl BiDimensionalRendererConstructor for Candy2DefaultRenderer {
     fn new(window: &Window, config: &Config) -> Self {
         Self {
             environment: Self::create_environment(window, config),
+            pictures: PictureRecorders::default(),
         }
     }
 }
@@ -171,16 +202,20 @@ impl Candy2DefaultRenderer {
             fb_info: fb,
             samples,
             stencil_size,
+            history: DamageHistory::default(),
+            damage: None,
         }
     }
-    ///Retrieves the canvas of this renderer
+    ///Retrieves the canvas drawn on, which is the one of the picture being recorded if there's any
     fn canvas(&mut self) -> &Canvas {
-        self.environment.surface.canvas()
+        match self.pictures.canvas() {
+            Some(canvas) => canvas,
+            None => self.environment.surface.canvas(),
+        }
     }
 }
 
 impl BiDimensionalRenderer for Candy2DefaultRenderer {
-    #[cfg(feature = "opengl")]
     fn resize(&mut self, window: &Window, width: u32, height: u32) {
         use std::num::NonZero;
 
@@ -198,18 +233,54 @@ impl BiDimensionalRenderer for Candy2DefaultRenderer {
             NonZero::new(width.max(1)).unwrap(),
             NonZero::new(height.max(1)).unwrap(),
         );
+        self.environment.history.clear();
     }
 
-    #[inline]
     #[cfg(feature = "opengl")]
+    ///Submits everything drawn and swaps the buffers, telling the compositor which regions changed when the surface allows it
     fn flush(&mut self) {
         use glutin::surface::GlSurface;
 
-        self.environment.gr_context.flush_and_submit();
-        self.environment
-            .gl_surface
-            .swap_buffers(&self.environment.gl_context)
-            .unwrap();
+        let environment = &mut self.environment;
+        environment.gr_context.flush_and_submit();
+        let damage = environment.damage.take().unwrap_or_else(Damage::full);
+        if damage.is_full() {
+            environment
+                .gl_surface
+                .swap_buffers(&environment.gl_context)
+                .unwrap();
+        } else {
+            let (width, height) = (
+                environment.surface.width() as f32,
+                environment.surface.height() as f32,
+            );
+            //the rects of the damage start at the top left corner, while the ones of the compositor start at the bottom left one
+            let rects: Vec<glutin::surface::Rect> = damage
+                .rects_within(width, height)
+                .iter()
+                .map(|rect| {
+                    glutin::surface::Rect::new(
+                        rect.x as i32,
+                        (height - rect.bottom()) as i32,
+                        rect.width as i32,
+                        rect.height as i32,
+                    )
+                })
+                .collect();
+            swap_buffers_with_damage(&environment.gl_surface, &environment.gl_context, &rects)
+                .unwrap();
+        }
+        environment.history.push(damage);
+    }
+
+    #[cfg(feature = "opengl")]
+    ///The back buffer holds the frame presented as many frames ago as its age, so what changed since then is repainted as well
+    fn begin_frame(&mut self, damage: &Damage) -> Damage {
+        use glutin::surface::GlSurface;
+
+        let age = self.environment.gl_surface.buffer_age();
+        self.environment.damage = Some(damage.clone());
+        self.environment.history.repaint(damage, age)
     }
     fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
         self
@@ -217,128 +288,95 @@ impl BiDimensionalRenderer for Candy2DefaultRenderer {
 }
 
 impl BiDimensionalPainter for Candy2DefaultRenderer {
+    #[inline]
     fn square(&mut self, square_info: &CandySquare) {
-        let rule = &square_info.rule;
-
-        let radius = rule.border_radius;
-        let rect = {
-            let position = square_info.position();
-            let size = square_info.size();
-            Rect::new(
-                position.x,
-                position.y,
-                position.x + size.x,
-                position.y + size.y,
-            )
-        };
-
-        self.canvas()
-            .draw_round_rect(rect, radius.x, radius.y, &rule.inner);
-        let border_color = rule.border_color;
-
-        if border_color.w == 0.0 || rule.border_width == 0.0 {
-            return;
-        }
-        let mut paint = Paint::new(vec4f32_to_color(&border_color), None);
-        paint
-            .set_style(skia_safe::PaintStyle::Stroke)
-            .set_stroke_width(rule.border_width);
-
-        self.canvas()
-            .draw_round_rect(&rect, radius.x, radius.y, &paint);
+        skia::square(self.canvas(), square_info);
     }
 
     #[inline]
     fn circle(&mut self, position: &nalgebra::Vector2<f32>, color: &Vector4<f32>, radius: f32) {
-        let paint = Paint::new(vec4f32_to_color(color), None);
-        self.canvas()
-            .draw_circle(Point::new(position.x, position.y), radius, &paint);
+        skia::circle(self.canvas(), position, color, radius);
     }
 
+    #[inline]
     fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
-        let rule = &info.rule;
-
-        let bounds = info.bounds();
-        let canvas = self.canvas();
-        canvas.save();
-        canvas.clip_rect(
-            Rect {
-                left: bounds.x - info.font().size(),
-                top: bounds.y - info.font().size(),
-                right: bounds.x + bounds.width,
-                bottom: bounds.y + bounds.height,
-            },
-            None,
-            Some(true),
-        );
-        canvas.draw_str(
-            &info.content()[range],
-            Point::new(info.position().x, info.position().y),
-            &info.font(),
-            &rule.inner,
-        );
-        canvas.restore();
+        skia::text_sliced(self.canvas(), info, range);
     }
 
+    #[inline]
     fn text(&mut self, info: &CandyText) {
-        let rule = &info.rule;
-        let canvas = self.canvas();
-        canvas.save();
-        let bounds = info.bounds();
-        canvas.clip_rect(
-            Rect {
-                left: bounds.x - info.font().size(),
-                top: bounds.y - info.font().size(),
-                right: bounds.x + bounds.width,
-                bottom: bounds.y + bounds.height,
-            },
-            None,
-            Some(true),
-        );
-        canvas.draw_str(
-            info.content(),
-            Point::new(info.position().x, info.position().y),
-            &info.font(),
-            &rule.inner,
-        );
-        canvas.restore();
+        skia::text(self.canvas(), info);
     }
+
+    #[inline]
     fn render_image(&mut self, image: &CandyImage) {
-        let rule = &image.rule;
-        let w = image.real_width();
-        let h = image.real_height();
-        let position = image.position();
-        let rect = Rect::new(
-            position.x,
-            position.y,
-            position.x + w as f32,
-            position.y + h as f32,
-        );
+        skia::image(self.canvas(), image);
+    }
+
+    #[inline]
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        skia::sprites(self.canvas(), sheet);
+    }
 
-        let canvas = self.canvas();
+    #[inline]
+    fn path(&mut self, path: &CandyPath) {
+        skia::path(self.canvas(), path);
+    }
 
-        canvas.save();
+    #[inline]
+    fn save(&mut self) {
+        self.canvas().save();
+    }
 
-        canvas.clip_rrect(
-            &RRect::new_rect_xy(&rect, rule.border_radius.x, rule.border_radius.y),
-            None,
-            true,
-        );
+    #[inline]
+    fn restore(&mut self) {
+        self.canvas().restore();
+    }
 
-        canvas.draw_image_rect_with_sampling_options(
-            image.image_handler(),
-            Some((
-                &Rect::new(0.0, 0.0, w as f32, h as f32),
-                SrcRectConstraint::Fast,
-            )),
-            rect,
-            SamplingOptions::default(),
-            &rule.inner,
-        );
+    #[inline]
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, bounds: Option<&Rect>) {
+        skia::save_layer(self.canvas(), alpha, blend_mode, bounds);
+    }
+
+    #[inline]
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>) {
+        skia::clip_rounded_rect(self.canvas(), rect, radius);
+    }
+
+    #[inline]
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        skia::clip_rounded_rect_radii(self.canvas(), rect, radii);
+    }
+
+    #[inline]
+    fn clip_rects(&mut self, rects: &[Rect]) {
+        skia::clip_rects(self.canvas(), rects);
+    }
 
-        canvas.restore();
+    #[inline]
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        skia::concat(self.canvas(), matrix);
     }
+
+    #[inline]
     fn background(&mut self, color: &Vector4<f32>) {
-        self.canvas().clear(*vec4f32_to_color(color));
+        skia::background(self.canvas(), color);
+    }
+
+    #[inline]
+    fn begin_picture(&mut self) -> bool {
+        self.pictures.begin();
+        true
+    }
+
+    #[inline]
+    fn end_picture(&mut self) -> Option<CandyPicture> {
+        self.pictures.end()
+    }
+
+    #[inline]
+    fn picture(&mut self, picture: &CandyPicture) -> bool {
+        skia::picture(self.canvas(), picture);
+        true
     }
 }
@@ -1,21 +1,58 @@
 use std::ops::Range;
 
-use nalgebra::{Vector2, Vector4};
+use candy_shared_types::{BlendMode, CornerRadii, Damage, Rect};
+use nalgebra::{Matrix3, Vector2, Vector4};
 use winit::window::Window;
+mod counting_painter;
+#[cfg(any(feature = "opengl", feature = "tiny-skia", feature = "vello"))]
+pub(crate) mod damage_history;
+#[cfg(feature = "opengl")]
 mod default_renderer;
+mod pdf_renderer;
+#[cfg(any(feature = "tiny-skia", feature = "vello"))]
+pub(crate) mod presenter;
+mod raster_renderer;
+mod recording_painter;
+pub(crate) mod skia;
+mod svg_painter;
+#[cfg(feature = "tiny-skia")]
+mod tiny_skia_renderer;
+#[cfg(feature = "vello")]
+mod vello_renderer;
+pub use counting_painter::*;
+#[cfg(feature = "opengl")]
 pub use default_renderer::*;
+pub use pdf_renderer::*;
+pub use raster_renderer::*;
+pub use recording_painter::*;
+pub use svg_painter::*;
+#[cfg(feature = "tiny-skia")]
+pub use tiny_skia_renderer::*;
+#[cfg(feature = "vello")]
+pub use vello_renderer::*;
 
-use crate::primitives::{CandyImage, CandySquare, CandyText};
+use crate::primitives::{
+    CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText,
+};
 ///Trait used to control a 2D painter
 
 pub trait BiDimensionalRenderer {
     ///When this renderer is requested to resize with the given `width` and `height`
-    #[cfg(feature = "opengl")]
     fn resize(&mut self, window: &Window, width: u32, height: u32);
 
     ///Finishes every command made supposing everything is ready to be drawn on the next frame
     fn flush(&mut self);
 
+    #[inline]
+    ///Tells this renderer the `damage` of the frame about to be drawn, the regions that changed since the last one. Returns the regions
+    ///that must be repainted for the frame to be right, which are more than the damage when the buffer drawn on holds an older frame,
+    ///and everything when what it holds is unknown. When flushed, only the damage is presented if the surface allows it.
+    ///By default everything is repainted
+    fn begin_frame(&mut self, damage: &Damage) -> Damage {
+        let _ = damage;
+        Damage::full()
+    }
+
     fn painter(&mut self) -> &mut dyn BiDimensionalPainter;
 }
 
@@ -23,6 +60,23 @@ pub trait BiDimensionalRenderer {
 pub trait BiDimensionalRendererConstructor {
     #[cfg(feature = "opengl")]
     fn new(window: &Window, config: &glutin::config::Config) -> Self;
+
+    #[inline]
+    ///Creates a renderer that draws on the CPU and presents to `window` on its own, without opengl. `None` if this renderer can't
+    ///present to a window by itself, which is the case by default
+    fn from_window(window: &Window) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        let _ = window;
+        None
+    }
+}
+
+///Trait used to create 2D renderers that don't need any window to draw on, such as the ones that draw on the CPU
+pub trait HeadlessRendererConstructor {
+    ///Creates a new renderer whose surface has the given `width` and `height` in pixels
+    fn new_headless(width: u32, height: u32) -> Self;
 }
 
 ///A 2D painter used to draw 2D stuff on the screen
@@ -40,5 +94,92 @@ pub trait BiDimensionalPainter: BiDimensionalRenderer + std::fmt::Debug {
     ///Method uses to draw the given `img` at the given `position`
     fn render_image(&mut self, info: &CandyImage);
 
+    ///Method used to draw every sprite of the given `sheet`, in a single batch when the underlying renderer can
+    fn sprites(&mut self, sheet: &CandySpriteSheet);
+
+    ///Method used to draw the given `path`, filling it with the color of its rule and stroking it with its border
+    fn path(&mut self, path: &CandyPath);
+
     fn background(&mut self, rule: &Vector4<f32>);
+
+    ///Saves the current clip and transform, so they can be brought back with `restore`
+    fn save(&mut self);
+
+    ///Brings back the clip and transform of the last `save`. Every clip and transform made after it is discarded
+    fn restore(&mut self);
+
+    ///Saves the current clip and transform, like `save`, and starts a new layer everything is drawn on until `restore`. When restored,
+    ///the layer is composited as a whole with the given `alpha` and `blend_mode`, so overlapping elements on it don't show through each other.
+    ///`bounds`, if known, is the area that will be drawn on the layer, used to keep it small
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, bounds: Option<&Rect>);
+
+    ///Restricts everything drawn from now on to the given `rect`, rounded by `radius`. The rect is affected by the current transform
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>);
+
+    ///Multiplies the current transform by the given `matrix`. Everything drawn from now on is transformed by it
+    fn concat(&mut self, matrix: &Matrix3<f32>);
+
+    #[inline]
+    ///Starts recording everything drawn from now on into a picture, instead of drawing it, until `end_picture`. Pictures can be started
+    ///while recording another one. Returns whether this painter can record pictures, by default it can't, so everything is drawn as usual
+    fn begin_picture(&mut self) -> bool {
+        false
+    }
+
+    #[inline]
+    ///Finishes the picture started by the last `begin_picture` and retrieves it, without drawing it. `None` if no picture was being recorded
+    fn end_picture(&mut self) -> Option<CandyPicture> {
+        None
+    }
+
+    #[inline]
+    ///Draws the recorded `picture` with the current clip and transform. Returns whether it was drawn, which by default it isn't, as
+    ///only painters that can record pictures can draw them
+    fn picture(&mut self, picture: &CandyPicture) -> bool {
+        let _ = picture;
+        false
+    }
+
+    #[inline]
+    ///Restricts everything drawn from now on to the given `rect`, with each corner rounded by its radius on `radii`. The rect is affected
+    ///by the current transform. By default every corner is rounded by the top left radius
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        self.clip_rounded_rect(rect, &radii.top_left);
+    }
+
+    #[inline]
+    ///Restricts everything drawn from now on to the given `rect`. The rect is affected by the current transform
+    fn clip_rect(&mut self, rect: &Rect) {
+        self.clip_rounded_rect(rect, &Vector2::zeros());
+    }
+
+    #[inline]
+    ///Restricts everything drawn from now on to the area covered by the given `rects`, such as the regions of a frame that changed. The
+    ///rects are affected by the current transform. By default it's restricted to their bounds, so it's a single rect
+    fn clip_rects(&mut self, rects: &[Rect]) {
+        let bounds = rects
+            .iter()
+            .cloned()
+            .reduce(|out, rect| out.union(&rect))
+            .unwrap_or_default();
+        self.clip_rect(&bounds);
+    }
+
+    #[inline]
+    ///Moves everything drawn from now on by `offset`
+    fn translate(&mut self, offset: &Vector2<f32>) {
+        self.concat(&Matrix3::new_translation(offset));
+    }
+
+    #[inline]
+    ///Scales everything drawn from now on by `scale`, relative to the origin of the current transform
+    fn scale(&mut self, scale: &Vector2<f32>) {
+        self.concat(&Matrix3::new_nonuniform_scaling(scale));
+    }
+
+    #[inline]
+    ///Rotates everything drawn from now on by `degrees` clockwise, around the origin of the current transform
+    fn rotate(&mut self, degrees: f32) {
+        self.concat(&Matrix3::new_rotation(degrees.to_radians()));
+    }
 }
@@ -0,0 +1,297 @@
+use std::{io::Write, ops::Range, path::Path};
+
+use candy_shared_types::{BlendMode, CornerRadii};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::{Canvas, Picture, PictureRecorder, Rect};
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer,
+    bidimensional::skia::{self, PictureRecorders},
+    primitives::{CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+///Configuration of the pages of a PDF document. Every value is in points, where 1pt == 1/72 inch
+pub struct PdfPageConfig {
+    ///The size of each page
+    pub size: Vector2<f32>,
+    ///The space between the borders of the page and its content, understood as Vec4(LEFT,TOP,RIGHT,BOTTOM)
+    pub margins: Vector4<f32>,
+}
+
+impl Default for PdfPageConfig {
+    fn default() -> Self {
+        Self::a4()
+    }
+}
+
+impl PdfPageConfig {
+    ///Creates a new configuration with pages of the given `size` and without any margin
+    pub fn new(size: Vector2<f32>) -> Self {
+        Self {
+            size,
+            margins: Vector4::zeros(),
+        }
+    }
+
+    #[inline]
+    ///An A4 page, 210mm x 297mm
+    pub fn a4() -> Self {
+        Self::new(Vector2::new(595.0, 842.0))
+    }
+
+    #[inline]
+    ///A US letter page, 8.5in x 11in
+    pub fn letter() -> Self {
+        Self::new(Vector2::new(612.0, 792.0))
+    }
+
+    #[inline]
+    ///Sets the margins of the pages to be the given `margins`, understood as Vec4(LEFT,TOP,RIGHT,BOTTOM)
+    pub fn with_margins(mut self, margins: Vector4<f32>) -> Self {
+        self.margins = margins;
+        self
+    }
+
+    ///Retrieves the size avaible for the content of a page, which is its size without the margins
+    pub fn content_size(&self) -> Vector2<f32> {
+        Vector2::new(
+            (self.size.x - self.margins.x - self.margins.z).max(0.0),
+            (self.size.y - self.margins.y - self.margins.w).max(0.0),
+        )
+    }
+}
+
+#[derive(Debug)]
+///A 2D renderer that generates a multi page PDF document. Every page is recorded while drawing and the document is only
+///generated when it's written, which consumes the renderer
+pub struct PdfRenderer {
+    config: PdfPageConfig,
+    pages: Vec<Picture>,
+    recorder: PictureRecorder,
+    ///Pictures being recorded on top of the page
+    pictures: PictureRecorders,
+}
+
+impl PdfRenderer {
+    ///Creates a new PDF renderer whose pages follow the given `config`. The first page is already started
+    pub fn new(config: PdfPageConfig) -> Self {
+        let mut out = Self {
+            config,
+            pages: Vec::new(),
+            recorder: PictureRecorder::new(),
+            pictures: PictureRecorders::default(),
+        };
+        out.start_recording(Vector2::zeros());
+        out
+    }
+
+    #[inline]
+    ///Retrieves the configuration of the pages of this document
+    pub fn config(&self) -> &PdfPageConfig {
+        &self.config
+    }
+
+    #[inline]
+    ///Retrieves the amount of pages this document has, including the one being drawn
+    pub fn pages(&self) -> usize {
+        self.pages.len() + 1
+    }
+
+    ///Starts recording a new page whose content area shows what is located at `offset`
+    fn start_recording(&mut self, offset: Vector2<f32>) {
+        let size = self.config.size;
+        let content = self.config.content_size();
+        let margins = self.config.margins;
+        let canvas = self
+            .recorder
+            .begin_recording(Rect::new(0.0, 0.0, size.x, size.y), None);
+        canvas.clip_rect(
+            Rect::new(
+                margins.x,
+                margins.y,
+                margins.x + content.x,
+                margins.y + content.y,
+            ),
+            None,
+            Some(true),
+        );
+        canvas.translate((margins.x - offset.x, margins.y - offset.y));
+    }
+
+    ///Finishes the page being drawn and stores it on the document
+    fn finish_page(&mut self) {
+        if let Some(page) = self.recorder.finish_recording_as_picture(None) {
+            self.pages.push(page);
+        }
+    }
+
+    #[inline]
+    ///Finishes the current page and starts a new one. Everything drawn from now on goes to the new page
+    pub fn begin_page(&mut self) {
+        self.begin_page_at(Vector2::zeros());
+    }
+
+    ///Finishes the current page and starts a new one whose content area shows what is drawn at `offset`. Used to split content
+    ///taller than a page into many of them. For example, with a content height of 700pt, the second page would start at (0, 700)
+    pub fn begin_page_at(&mut self, offset: Vector2<f32>) {
+        self.finish_page();
+        self.start_recording(offset);
+    }
+
+    ///Retrieves the canvas drawn on, which is the one of the picture being recorded if there's any, otherwise the one of the page
+    fn canvas(&mut self) -> &Canvas {
+        match self.pictures.canvas() {
+            Some(canvas) => canvas,
+            None => self
+                .recorder
+                .recording_canvas()
+                .expect("A PDF page is always being recorded"),
+        }
+    }
+
+    ///Finishes the page being drawn, generates the PDF document with every page and writes it on `writer`
+    pub fn write_to<W: Write>(mut self, writer: &mut W) -> std::io::Result<()> {
+        self.finish_page();
+        //The document needs the writer exclusively and doesn't report io errors, so it's written into memory first
+        let mut buffer = Vec::new();
+        {
+            let mut document = skia_safe::pdf::new_document(&mut buffer, None);
+            let size = self.config.size;
+            for page in &self.pages {
+                let mut on_page = document.begin_page((size.x, size.y), None);
+                on_page.canvas().draw_picture(page, None, None);
+                document = on_page.end_page();
+            }
+            document.close();
+        }
+        writer.write_all(&buffer)
+    }
+
+    ///Finishes the page being drawn and returns the bytes of the PDF document with every page
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut out = Vec::new();
+        //Writing into a vector never fails
+        let _ = self.write_to(&mut out);
+        out
+    }
+
+    ///Finishes the page being drawn and saves the PDF document with every page at the given `path`
+    pub fn save<P: AsRef<Path>>(self, path: P) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        self.write_to(&mut file)
+    }
+}
+
+impl BiDimensionalRenderer for PdfRenderer {
+    ///The pages of a PDF have a fixed size, so resizing doesn't change anything
+    fn resize(&mut self, _: &Window, _: u32, _: u32) {}
+
+    fn flush(&mut self) {}
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for PdfRenderer {
+    #[inline]
+    fn square(&mut self, square_info: &CandySquare) {
+        skia::square(self.canvas(), square_info);
+    }
+
+    #[inline]
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        skia::circle(self.canvas(), position, color, radius);
+    }
+
+    #[inline]
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        skia::text_sliced(self.canvas(), info, range);
+    }
+
+    #[inline]
+    fn text(&mut self, info: &CandyText) {
+        skia::text(self.canvas(), info);
+    }
+
+    #[inline]
+    fn render_image(&mut self, image: &CandyImage) {
+        skia::image(self.canvas(), image);
+    }
+
+    #[inline]
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        skia::sprites(self.canvas(), sheet);
+    }
+
+    #[inline]
+    fn path(&mut self, path: &CandyPath) {
+        skia::path(self.canvas(), path);
+    }
+
+    #[inline]
+    fn save(&mut self) {
+        self.canvas().save();
+    }
+
+    #[inline]
+    fn restore(&mut self) {
+        self.canvas().restore();
+    }
+
+    #[inline]
+    fn save_layer(
+        &mut self,
+        alpha: f32,
+        blend_mode: BlendMode,
+        bounds: Option<&candy_shared_types::Rect>,
+    ) {
+        skia::save_layer(self.canvas(), alpha, blend_mode, bounds);
+    }
+
+    #[inline]
+    fn clip_rounded_rect(&mut self, rect: &candy_shared_types::Rect, radius: &Vector2<f32>) {
+        skia::clip_rounded_rect(self.canvas(), rect, radius);
+    }
+
+    #[inline]
+    fn clip_rounded_rect_radii(&mut self, rect: &candy_shared_types::Rect, radii: &CornerRadii) {
+        skia::clip_rounded_rect_radii(self.canvas(), rect, radii);
+    }
+
+    #[inline]
+    fn clip_rects(&mut self, rects: &[candy_shared_types::Rect]) {
+        skia::clip_rects(self.canvas(), rects);
+    }
+
+    #[inline]
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        skia::concat(self.canvas(), matrix);
+    }
+
+    ///Fills the content area of the current page with the given `color`. Clearing isn't supported on PDFs, so the color is painted
+    ///over everything instead
+    fn background(&mut self, color: &Vector4<f32>) {
+        let paint = skia_safe::Paint::new(candy_shared_types::vec4f32_to_color(color), None);
+        self.canvas().draw_paint(&paint);
+    }
+
+    #[inline]
+    fn begin_picture(&mut self) -> bool {
+        self.pictures.begin();
+        true
+    }
+
+    #[inline]
+    fn end_picture(&mut self) -> Option<CandyPicture> {
+        self.pictures.end()
+    }
+
+    #[inline]
+    fn picture(&mut self, picture: &CandyPicture) -> bool {
+        skia::picture(self.canvas(), picture);
+        true
+    }
+}
@@ -0,0 +1,112 @@
+use std::num::NonZeroU32;
+
+use candy_shared_types::Damage;
+
+use raw_window_handle::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, RawDisplayHandle,
+    RawWindowHandle, WindowHandle,
+};
+use winit::window::Window;
+
+use crate::bidimensional::damage_history::DamageHistory;
+
+#[derive(Debug, Clone, Copy)]
+///Raw handles of a window. Used because the presenting surface needs to own something that can give the handles of the window
+struct RawHandles {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+impl HasWindowHandle for RawHandles {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        //The window is owned by the root component, which lives as long as the renderer
+        Ok(unsafe { WindowHandle::borrow_raw(self.window) })
+    }
+}
+
+impl HasDisplayHandle for RawHandles {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        Ok(unsafe { DisplayHandle::borrow_raw(self.display) })
+    }
+}
+
+///A CPU swapchain that copies the pixels drawn by the renderer into the window
+pub(crate) struct Presenter {
+    _context: softbuffer::Context<RawHandles>,
+    surface: softbuffer::Surface<RawHandles, RawHandles>,
+    width: u32,
+    height: u32,
+    ///The damage of the last frames presented, as the buffers of the window may hold older ones
+    history: DamageHistory,
+}
+
+impl Presenter {
+    pub(crate) fn new(window: &Window) -> Option<Self> {
+        let handles = RawHandles {
+            window: window.window_handle().ok()?.as_raw(),
+            display: window.display_handle().ok()?.as_raw(),
+        };
+        let context = softbuffer::Context::new(handles).ok()?;
+        let surface = softbuffer::Surface::new(&context, handles).ok()?;
+        Some(Self {
+            _context: context,
+            surface,
+            width: 0,
+            height: 0,
+            history: DamageHistory::default(),
+        })
+    }
+
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        (self.width, self.height) = (width.max(1), height.max(1));
+        let _ = self.surface.resize(
+            NonZeroU32::new(self.width).unwrap(),
+            NonZeroU32::new(self.height).unwrap(),
+        );
+        self.history.clear();
+    }
+
+    ///Copies the given premultiplied RGBA `pixels`, with the width the window was resized to, into the window and presents them. Only
+    ///what is needed to bring the buffer up to date with the `damage` of this frame is copied, and only the damage is presented when
+    ///the platform allows it
+    pub(crate) fn present(&mut self, pixels: &[u8], damage: &Damage) {
+        let Ok(mut buffer) = self.surface.buffer_mut() else {
+            return;
+        };
+        let (width, height) = (self.width as f32, self.height as f32);
+        let repaint = self.history.repaint(damage, buffer.age() as u32);
+        for rect in repaint.rects_within(width, height) {
+            let (left, right) = (rect.x as usize, rect.right() as usize);
+            for y in rect.y as usize..rect.bottom() as usize {
+                let row = y * self.width as usize;
+                let (Some(out), Some(source)) = (
+                    buffer.get_mut(row + left..row + right),
+                    pixels.get((row + left) * 4..(row + right) * 4),
+                ) else {
+                    continue;
+                };
+                for (out, pixel) in out.iter_mut().zip(source.chunks_exact(4)) {
+                    *out = (pixel[0] as u32) << 16 | (pixel[1] as u32) << 8 | pixel[2] as u32;
+                }
+            }
+        }
+        let _ = if damage.is_full() {
+            buffer.present()
+        } else {
+            let rects: Vec<softbuffer::Rect> = damage
+                .rects_within(width, height)
+                .iter()
+                .filter_map(|rect| {
+                    Some(softbuffer::Rect {
+                        x: rect.x as u32,
+                        y: rect.y as u32,
+                        width: NonZeroU32::new(rect.width as u32)?,
+                        height: NonZeroU32::new(rect.height as u32)?,
+                    })
+                })
+                .collect();
+            buffer.present_with_damage(&rects)
+        };
+        self.history.push(damage.clone());
+    }
+}
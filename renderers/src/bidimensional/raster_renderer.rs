@@ -0,0 +1,217 @@
+use std::ops::Range;
+
+use candy_shared_types::{BlendMode, CornerRadii, Damage, Rect};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::{AlphaType, Canvas, ColorType, ISize, ImageInfo, Surface};
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer, BiDimensionalRendererConstructor,
+    HeadlessRendererConstructor,
+    bidimensional::skia::{self, PictureRecorders},
+    primitives::{CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText},
+};
+
+///Creates a raster surface on the cpu with the given `width` and `height`. Both are clamped to at least 1px, since skia can't
+///allocate empty surfaces
+fn create_raster_surface(width: u32, height: u32) -> Surface {
+    let info = ImageInfo::new(
+        ISize::new(width.max(1) as i32, height.max(1) as i32),
+        ColorType::RGBA8888,
+        AlphaType::Premul,
+        None,
+    );
+    skia_safe::surfaces::raster(&info, None, None).expect("Could not allocate the raster surface")
+}
+
+#[derive(Debug)]
+///A 2D renderer that draws everything on the CPU into a pixel buffer owned by itself. It doesn't need any window nor GPU to
+///be created, so it can be used for offscreen rendering, tests and environments where no GL context exists
+pub struct Candy2DRasterRenderer {
+    surface: Surface,
+    ///Whether the surface was just created, so nothing drawn before can be kept on the next frame
+    fresh: bool,
+    pictures: PictureRecorders,
+}
+
+impl Candy2DRasterRenderer {
+    ///Creates a new raster renderer whose surface has the given `width` and `height` in pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            surface: create_raster_surface(width, height),
+            fresh: true,
+            pictures: PictureRecorders::default(),
+        }
+    }
+
+    ///Retrieves the canvas drawn on, which is the one of the picture being recorded if there's any
+    fn canvas(&mut self) -> &Canvas {
+        match self.pictures.canvas() {
+            Some(canvas) => canvas,
+            None => self.surface.canvas(),
+        }
+    }
+
+    #[inline]
+    ///Retrieves the size in pixels of the surface of this renderer
+    pub fn size(&self) -> Vector2<u32> {
+        Vector2::new(self.surface.width() as u32, self.surface.height() as u32)
+    }
+
+    ///Recreates the surface of this renderer with the given `width` and `height`. Everything drawn until now is discarded
+    pub fn resize_surface(&mut self, width: u32, height: u32) {
+        self.surface = create_raster_surface(width, height);
+        self.fresh = true;
+    }
+
+    #[inline]
+    ///Retrieves the inner skia surface this renderer draws on
+    pub fn surface_mut(&mut self) -> &mut Surface {
+        &mut self.surface
+    }
+
+    ///Reads the pixels drawn until now as non premultiplied RGBA8888, row by row from the top left corner
+    pub fn read_pixels(&mut self) -> Vec<u8> {
+        let size = self.size();
+        let info = ImageInfo::new(
+            ISize::new(size.x as i32, size.y as i32),
+            ColorType::RGBA8888,
+            AlphaType::Unpremul,
+            None,
+        );
+        let row_bytes = info.min_row_bytes();
+        let mut out = vec![0; row_bytes * size.y as usize];
+        self.surface.read_pixels(&info, &mut out, row_bytes, (0, 0));
+        out
+    }
+}
+
+impl HeadlessRendererConstructor for Candy2DRasterRenderer {
+    #[inline]
+    fn new_headless(width: u32, height: u32) -> Self {
+        Self::new(width, height)
+    }
+}
+
+impl BiDimensionalRendererConstructor for Candy2DRasterRenderer {
+    #[cfg(feature = "opengl")]
+    fn new(window: &Window, _: &glutin::config::Config) -> Self {
+        let size = window.inner_size();
+        Self::new(size.width, size.height)
+    }
+}
+
+impl BiDimensionalRenderer for Candy2DRasterRenderer {
+    fn resize(&mut self, _: &Window, width: u32, height: u32) {
+        self.resize_surface(width, height);
+    }
+
+    ///As everything is drawn directly into the pixel buffer, there is nothing to be submitted
+    fn flush(&mut self) {}
+
+    ///The surface keeps the last frame, so only the damage is repainted, unless it was just created
+    fn begin_frame(&mut self, damage: &Damage) -> Damage {
+        if std::mem::take(&mut self.fresh) {
+            return Damage::full();
+        }
+        damage.clone()
+    }
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for Candy2DRasterRenderer {
+    #[inline]
+    fn square(&mut self, square_info: &CandySquare) {
+        skia::square(self.canvas(), square_info);
+    }
+
+    #[inline]
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        skia::circle(self.canvas(), position, color, radius);
+    }
+
+    #[inline]
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        skia::text_sliced(self.canvas(), info, range);
+    }
+
+    #[inline]
+    fn text(&mut self, info: &CandyText) {
+        skia::text(self.canvas(), info);
+    }
+
+    #[inline]
+    fn render_image(&mut self, image: &CandyImage) {
+        skia::image(self.canvas(), image);
+    }
+
+    #[inline]
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        skia::sprites(self.canvas(), sheet);
+    }
+
+    #[inline]
+    fn path(&mut self, path: &CandyPath) {
+        skia::path(self.canvas(), path);
+    }
+
+    #[inline]
+    fn save(&mut self) {
+        self.canvas().save();
+    }
+
+    #[inline]
+    fn restore(&mut self) {
+        self.canvas().restore();
+    }
+
+    #[inline]
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, bounds: Option<&Rect>) {
+        skia::save_layer(self.canvas(), alpha, blend_mode, bounds);
+    }
+
+    #[inline]
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>) {
+        skia::clip_rounded_rect(self.canvas(), rect, radius);
+    }
+
+    #[inline]
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        skia::clip_rounded_rect_radii(self.canvas(), rect, radii);
+    }
+
+    #[inline]
+    fn clip_rects(&mut self, rects: &[Rect]) {
+        skia::clip_rects(self.canvas(), rects);
+    }
+
+    #[inline]
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        skia::concat(self.canvas(), matrix);
+    }
+
+    #[inline]
+    fn background(&mut self, color: &Vector4<f32>) {
+        skia::background(self.canvas(), color);
+    }
+
+    #[inline]
+    fn begin_picture(&mut self) -> bool {
+        self.pictures.begin();
+        true
+    }
+
+    #[inline]
+    fn end_picture(&mut self) -> Option<CandyPicture> {
+        self.pictures.end()
+    }
+
+    #[inline]
+    fn picture(&mut self, picture: &CandyPicture) -> bool {
+        skia::picture(self.canvas(), picture);
+        true
+    }
+}
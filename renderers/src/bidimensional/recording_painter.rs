@@ -0,0 +1,623 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use candy_shared_types::RendererImage;
+use candy_shared_types::{BlendMode, Borders, ColorFilter, CornerRadii, Effects, Fill, Rect};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::{FontMgr, FontStyle};
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer,
+    primitives::{
+        CandyFont, CandyImage, CandyPath, CandySpriteSheet, CandySquare, CandyText, DrawRule,
+        FillRule, ImageFit, ImageSampling, NineSlice, ParagraphStyle, PathSegment, Sprite,
+        StrokeStyle, TextureAtlas,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about a square that was requested to be drawn
+pub struct SquareCommand {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub color: Vector4<f32>,
+    pub border_color: Vector4<f32>,
+    pub border_radius: Vector2<f32>,
+    pub border_width: f32,
+    ///The radius of each corner. The same as `border_radius` on every corner unless they were set independently
+    pub corner_radii: CornerRadii,
+    ///The border of each side. The same as the uniform border on every side unless they were set independently
+    pub borders: Borders,
+    pub blend_mode: BlendMode,
+    pub color_filter: Option<ColorFilter>,
+    pub effects: Effects,
+    ///The fill, when it's not the solid `color`, such as a gradient or a shader. When deserialized this is lost, and `color` is used
+    ///instead
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub fill: Option<Fill>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about a text that was requested to be drawn
+pub struct TextCommand {
+    ///The whole content of the text, even if only a slice of it was drawn
+    pub content: String,
+    ///The range of bytes of `content` that was drawn. None if all of it was
+    pub range: Option<Range<usize>>,
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub color: Vector4<f32>,
+    pub font_family: String,
+    pub font_size: f32,
+    pub blend_mode: BlendMode,
+    pub color_filter: Option<ColorFilter>,
+    pub effects: Effects,
+    ///How the text was laid out as a paragraph. None if it was a single line
+    pub paragraph: Option<ParagraphStyle>,
+    ///The fill, when it's not the solid `color`, such as a gradient or a shader. When deserialized this is lost, and `color` is used
+    ///instead
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub fill: Option<Fill>,
+    ///The font used to draw. When deserialized, it's searched again by `font_family`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub font: Option<CandyFont>,
+}
+
+impl PartialEq for TextCommand {
+    fn eq(&self, other: &Self) -> bool {
+        self.content == other.content
+            && self.range == other.range
+            && self.position == other.position
+            && self.size == other.size
+            && self.color == other.color
+            && self.font_family == other.font_family
+            && self.font_size == other.font_size
+            && self.blend_mode == other.blend_mode
+            && self.color_filter == other.color_filter
+            && self.effects == other.effects
+            && self.paragraph == other.paragraph
+            && self.fill == other.fill
+    }
+}
+
+impl TextCommand {
+    #[inline]
+    ///Retrieves the part of the content that was actually drawn
+    pub fn drawn_content(&self) -> &str {
+        match self.range {
+            Some(ref range) => &self.content[range.clone()],
+            None => &self.content,
+        }
+    }
+
+    ///Retrieves the font this text was drawn with. If it's got lost, for example when deserialized, searches it by the family name
+    pub fn font(&self) -> Option<CandyFont> {
+        if let Some(ref font) = self.font {
+            return Some(font.clone());
+        }
+        let typeface = FontMgr::new().match_family_style(&self.font_family, FontStyle::default())?;
+        Some(CandyFont::new(typeface, self.font_size))
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about an image that was requested to be drawn
+pub struct ImageCommand {
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    ///The actual size of the image, not the one it's drawn with
+    pub real_size: Vector2<i32>,
+    pub color: Vector4<f32>,
+    pub border_radius: Vector2<f32>,
+    pub blend_mode: BlendMode,
+    pub color_filter: Option<ColorFilter>,
+    pub effects: Effects,
+    pub fit: ImageFit,
+    pub alignment: Vector2<f32>,
+    ///The position and size of the part of the image that was drawn, in its pixels
+    pub source: (Vector2<f32>, Vector2<f32>),
+    pub nine_slice: Option<NineSlice>,
+    pub sampling: ImageSampling,
+    ///The image that was drawn. When deserialized this is lost, and the command can't be replayed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub image: Option<RendererImage>,
+}
+
+impl PartialEq for ImageCommand {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.size == other.size
+            && self.real_size == other.real_size
+            && self.color == other.color
+            && self.border_radius == other.border_radius
+            && self.blend_mode == other.blend_mode
+            && self.color_filter == other.color_filter
+            && self.effects == other.effects
+            && self.fit == other.fit
+            && self.alignment == other.alignment
+            && self.source == other.source
+            && self.nine_slice == other.nine_slice
+            && self.sampling == other.sampling
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about a sprite sheet that was requested to be drawn
+pub struct SpritesCommand {
+    pub position: Vector2<f32>,
+    pub sprites: Vec<Sprite>,
+    pub sampling: ImageSampling,
+    pub color: Vector4<f32>,
+    pub blend_mode: BlendMode,
+    ///The atlas the sprites were drawn from. When deserialized this is lost, and the command can't be replayed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub atlas: Option<Arc<TextureAtlas>>,
+}
+
+impl PartialEq for SpritesCommand {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.sprites == other.sprites
+            && self.sampling == other.sampling
+            && self.color == other.color
+            && self.blend_mode == other.blend_mode
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about a path that was requested to be drawn
+pub struct PathCommand {
+    pub segments: Vec<PathSegment>,
+    pub position: Vector2<f32>,
+    pub size: Vector2<f32>,
+    pub fill_rule: FillRule,
+    pub stroke: StrokeStyle,
+    pub color: Vector4<f32>,
+    pub border_color: Vector4<f32>,
+    pub border_width: f32,
+    pub blend_mode: BlendMode,
+    pub color_filter: Option<ColorFilter>,
+    pub effects: Effects,
+    ///The fill, when it's not the solid `color`, such as a gradient or a shader. When deserialized this is lost, and `color` is used
+    ///instead
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub fill: Option<Fill>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A single call made on a painter
+pub enum DrawCommand {
+    Square(SquareCommand),
+    Circle {
+        position: Vector2<f32>,
+        color: Vector4<f32>,
+        radius: f32,
+    },
+    Text(TextCommand),
+    Image(ImageCommand),
+    Sprites(SpritesCommand),
+    Path(PathCommand),
+    Background(Vector4<f32>),
+    Save,
+    SaveLayer {
+        alpha: f32,
+        blend_mode: BlendMode,
+        bounds: Option<(Vector2<f32>, Vector2<f32>)>,
+    },
+    Restore,
+    Clip {
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        radius: Vector2<f32>,
+    },
+    ///A clip whose corners are rounded by different radii
+    ClipCorners {
+        position: Vector2<f32>,
+        size: Vector2<f32>,
+        radii: CornerRadii,
+    },
+    Concat(Matrix3<f32>),
+}
+
+impl DrawCommand {
+    ///Executes this command on the given `painter`
+    pub fn replay(&self, painter: &mut dyn BiDimensionalPainter) {
+        match self {
+            Self::Square(info) => {
+                let mut square = CandySquare::new(info.position, info.size);
+                let rule = square.rule_mut();
+                rule.set_color(&info.color);
+                rule.border_color = info.border_color;
+                rule.border_radius = info.border_radius;
+                rule.border_width = info.border_width;
+                rule.set_corner_radii(info.corner_radii);
+                rule.set_borders(info.borders);
+                rule.set_blend_mode(info.blend_mode);
+                rule.set_color_filter(info.color_filter.clone());
+                rule.set_effects(info.effects.clone());
+                if let Some(ref fill) = info.fill {
+                    rule.set_fill(fill.clone());
+                }
+                painter.square(&square);
+            }
+            Self::Circle {
+                position,
+                color,
+                radius,
+            } => painter.circle(position, color, *radius),
+            Self::Text(info) => {
+                let Some(font) = info.font() else {
+                    return;
+                };
+                let mut text = CandyText::new(&info.content, info.position, font);
+                *text.size_mut() = info.size;
+                text.rule.set_color(&info.color);
+                text.rule.set_blend_mode(info.blend_mode);
+                text.rule.set_color_filter(info.color_filter.clone());
+                text.rule.set_effects(info.effects.clone());
+                if let Some(ref fill) = info.fill {
+                    text.rule.set_fill(fill.clone());
+                }
+                text.set_paragraph(info.paragraph.clone());
+                match info.range {
+                    Some(ref range) => painter.text_sliced(&text, range.clone()),
+                    None => painter.text(&text),
+                }
+            }
+            Self::Image(info) => {
+                let Some(ref image) = info.image else {
+                    return;
+                };
+                let mut image = CandyImage::new(
+                    image.clone(),
+                    CandySquare::new(info.position, info.size),
+                );
+                let rule = image.rule_mut();
+                rule.set_color(&info.color);
+                rule.border_radius = info.border_radius;
+                rule.set_blend_mode(info.blend_mode);
+                rule.set_color_filter(info.color_filter.clone());
+                rule.set_effects(info.effects.clone());
+                image
+                    .set_fit(info.fit)
+                    .set_alignment(info.alignment)
+                    .set_source_rect(Some(Rect::new(
+                        info.source.0.x,
+                        info.source.0.y,
+                        info.source.1.x,
+                        info.source.1.y,
+                    )))
+                    .set_nine_slice(info.nine_slice)
+                    .set_sampling(info.sampling);
+                painter.render_image(&image);
+            }
+            Self::Sprites(info) => {
+                let Some(ref atlas) = info.atlas else {
+                    return;
+                };
+                let mut sheet = CandySpriteSheet::new(atlas.clone(), info.position);
+                *sheet.sprites_mut() = info.sprites.clone();
+                sheet.set_sampling(info.sampling);
+                sheet.rule.set_color(&info.color);
+                sheet.rule.set_blend_mode(info.blend_mode);
+                painter.sprites(&sheet);
+            }
+            Self::Path(info) => {
+                let mut path = CandyPath::from_segments(info.position, info.segments.clone());
+                *path.size_mut() = info.size;
+                path.fill_rule = info.fill_rule;
+                path.stroke = info.stroke.clone();
+                path.rule.set_color(&info.color);
+                path.rule.border_color = info.border_color;
+                path.rule.border_width = info.border_width;
+                path.rule.set_blend_mode(info.blend_mode);
+                path.rule.set_color_filter(info.color_filter.clone());
+                path.rule.set_effects(info.effects.clone());
+                if let Some(ref fill) = info.fill {
+                    path.rule.set_fill(fill.clone());
+                }
+                painter.path(&path);
+            }
+            Self::Background(color) => painter.background(color),
+            Self::Save => painter.save(),
+            Self::SaveLayer {
+                alpha,
+                blend_mode,
+                bounds,
+            } => {
+                let bounds = bounds
+                    .map(|(position, size)| Rect::new(position.x, position.y, size.x, size.y));
+                painter.save_layer(*alpha, *blend_mode, bounds.as_ref());
+            }
+            Self::Restore => painter.restore(),
+            Self::Clip {
+                position,
+                size,
+                radius,
+            } => painter.clip_rounded_rect(
+                &Rect::new(position.x, position.y, size.x, size.y),
+                radius,
+            ),
+            Self::ClipCorners {
+                position,
+                size,
+                radii,
+            } => painter.clip_rounded_rect_radii(
+                &Rect::new(position.x, position.y, size.x, size.y),
+                radii,
+            ),
+            Self::Concat(matrix) => painter.concat(matrix),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A list of draw commands in the order they were made. Can be inspected or replayed on any painter
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Appends the given `command` at the end of this list
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    #[inline]
+    ///Retrieves all the commands of this list
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    #[inline]
+    ///Iterates over the commands of this list in the order they were made
+    pub fn iter(&self) -> std::slice::Iter<'_, DrawCommand> {
+        self.commands.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    #[inline]
+    ///Removes all the commands of this list
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    ///Executes every command of this list, in order, on the given `painter`
+    pub fn replay(&self, painter: &mut dyn BiDimensionalPainter) {
+        for command in &self.commands {
+            command.replay(painter);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DrawCommand;
+    type IntoIter = std::slice::Iter<'a, DrawCommand>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+///Retrieves the fill of `rule` to be recorded. `None` if it's a solid color, which is recorded as the color of the command
+fn recorded_fill(rule: &DrawRule) -> Option<Fill> {
+    match rule.fill() {
+        Fill::Solid(_) => None,
+        fill => Some(fill.clone()),
+    }
+}
+
+#[derive(Debug, Default)]
+///A painter that doesn't draw anything, instead it records every call made on it into a `DisplayList`
+pub struct RecordingPainter {
+    list: DisplayList,
+}
+
+impl RecordingPainter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Retrieves the commands recorded until now
+    pub fn display_list(&self) -> &DisplayList {
+        &self.list
+    }
+
+    #[inline]
+    ///Retrieves the commands recorded until now and starts a new, empty, recording
+    pub fn take_display_list(&mut self) -> DisplayList {
+        std::mem::take(&mut self.list)
+    }
+
+    #[inline]
+    ///Discards every command recorded until now
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    fn record_text(&mut self, info: &CandyText, range: Option<Range<usize>>) {
+        let font = info.font();
+        self.list.push(DrawCommand::Text(TextCommand {
+            content: info.content().to_string(),
+            range,
+            position: *info.position(),
+            size: *info.size(),
+            color: info.rule.get_color(),
+            font_family: font.typeface().family_name(),
+            font_size: font.size(),
+            font: Some(font.clone()),
+            blend_mode: info.rule.blend_mode(),
+            color_filter: info.rule.color_filter().cloned(),
+            effects: info.rule.effects().clone(),
+            paragraph: info.paragraph().cloned(),
+            fill: recorded_fill(&info.rule),
+        }));
+    }
+}
+
+impl BiDimensionalRenderer for RecordingPainter {
+    fn resize(&mut self, _: &Window, _: u32, _: u32) {}
+
+    fn flush(&mut self) {}
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for RecordingPainter {
+    fn square(&mut self, square_info: &CandySquare) {
+        let rule = square_info.rule();
+        self.list.push(DrawCommand::Square(SquareCommand {
+            position: *square_info.position(),
+            size: *square_info.size(),
+            color: rule.get_color(),
+            border_color: rule.border_color,
+            border_radius: rule.border_radius,
+            border_width: rule.border_width,
+            corner_radii: rule.corner_radii(),
+            borders: rule.borders(),
+            blend_mode: rule.blend_mode(),
+            color_filter: rule.color_filter().cloned(),
+            effects: rule.effects().clone(),
+            fill: recorded_fill(rule),
+        }));
+    }
+
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        self.list.push(DrawCommand::Circle {
+            position: *position,
+            color: *color,
+            radius,
+        });
+    }
+
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        self.record_text(info, Some(range));
+    }
+
+    fn text(&mut self, info: &CandyText) {
+        self.record_text(info, None);
+    }
+
+    fn render_image(&mut self, image: &CandyImage) {
+        self.list.push(DrawCommand::Image(ImageCommand {
+            position: *image.position(),
+            size: *image.size(),
+            real_size: Vector2::new(image.real_width(), image.real_height()),
+            color: image.rule().get_color(),
+            border_radius: image.rule().border_radius,
+            blend_mode: image.rule().blend_mode(),
+            color_filter: image.rule().color_filter().cloned(),
+            effects: image.rule().effects().clone(),
+            fit: image.fit(),
+            alignment: *image.alignment(),
+            source: {
+                let source = image.source_rect();
+                (
+                    Vector2::new(source.x, source.y),
+                    Vector2::new(source.width, source.height),
+                )
+            },
+            nine_slice: image.nine_slice(),
+            sampling: image.sampling(),
+            image: Some(image.image_handler().clone()),
+        }));
+    }
+
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        self.list.push(DrawCommand::Sprites(SpritesCommand {
+            position: *sheet.position(),
+            sprites: sheet.sprites().to_vec(),
+            sampling: sheet.sampling(),
+            color: sheet.rule.get_color(),
+            blend_mode: sheet.rule.blend_mode(),
+            atlas: Some(sheet.atlas().clone()),
+        }));
+    }
+
+    fn path(&mut self, path: &CandyPath) {
+        let rule = &path.rule;
+        self.list.push(DrawCommand::Path(PathCommand {
+            segments: path.segments().to_vec(),
+            position: *path.position(),
+            size: *path.size(),
+            fill_rule: path.fill_rule,
+            stroke: path.stroke.clone(),
+            color: rule.get_color(),
+            border_color: rule.border_color,
+            border_width: rule.border_width,
+            blend_mode: rule.blend_mode(),
+            color_filter: rule.color_filter().cloned(),
+            effects: rule.effects().clone(),
+            fill: recorded_fill(rule),
+        }));
+    }
+
+    fn background(&mut self, color: &Vector4<f32>) {
+        self.list.push(DrawCommand::Background(*color));
+    }
+
+    fn save(&mut self) {
+        self.list.push(DrawCommand::Save);
+    }
+
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, bounds: Option<&Rect>) {
+        self.list.push(DrawCommand::SaveLayer {
+            alpha,
+            blend_mode,
+            bounds: bounds.map(|rect| {
+                (
+                    Vector2::new(rect.x, rect.y),
+                    Vector2::new(rect.width, rect.height),
+                )
+            }),
+        });
+    }
+
+    fn restore(&mut self) {
+        self.list.push(DrawCommand::Restore);
+    }
+
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>) {
+        self.list.push(DrawCommand::Clip {
+            position: Vector2::new(rect.x, rect.y),
+            size: Vector2::new(rect.width, rect.height),
+            radius: *radius,
+        });
+    }
+
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        self.list.push(DrawCommand::ClipCorners {
+            position: Vector2::new(rect.x, rect.y),
+            size: Vector2::new(rect.width, rect.height),
+            radii: *radii,
+        });
+    }
+
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        self.list.push(DrawCommand::Concat(*matrix));
+    }
+}
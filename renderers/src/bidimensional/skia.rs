@@ -0,0 +1,545 @@
+use std::ops::Range;
+
+use candy_shared_types::{BlendMode, BorderSide, BorderStyle, CornerRadii, vec4f32_to_color};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::{
+    Canvas, CubicResampler, FilterMode, Image, Matrix, MipmapMode, Paint, PathDirection,
+    PathEffect, PathFillType, PictureRecorder, Point, RRect, RSXform, Rect, SamplingOptions,
+    canvas::{SaveLayerRec, SrcRectConstraint},
+    image_filters::{self, CropRect},
+    paint::{Cap, Join},
+    path::ArcSize,
+};
+
+use crate::primitives::{
+    CandyImage, CandyPath, CandyPicture, CandySpriteSheet, CandySquare, CandyText, DrawRule,
+    FillRule, ImageSampling, PathSegment, StrokeCap, StrokeJoin,
+};
+
+//Drawing routines shared by every painter that is backed by a skia `Canvas`, no matter where the canvas is drawing to(a window, a cpu buffer, etc)
+
+///Converts the given `rect` with the given corner `radii` into a skia rounded rect. Radii that don't fit are scaled down by skia
+pub(crate) fn to_skia_rrect(rect: &Rect, radii: &CornerRadii) -> RRect {
+    let radii = radii
+        .as_array()
+        .map(|radius| Point::new(radius.x, radius.y));
+    RRect::new_rect_radii(rect, &radii)
+}
+
+///Strokes the given `rrect` with the `paint` of a border `side`. Double borders are stroked twice, a third of their width outside and
+///inside of it
+fn stroke_border(canvas: &Canvas, rrect: &RRect, paint: &Paint, side: &BorderSide) {
+    if side.style != BorderStyle::Double {
+        canvas.draw_rrect(rrect, paint);
+        return;
+    }
+    let offset = side.width / 3.0;
+    canvas.draw_rrect(rrect.with_outset((offset, offset)), paint);
+    canvas.draw_rrect(rrect.with_inset((offset, offset)), paint);
+}
+
+///Draws the borders of the given `rule` around `rrect`, whose bounds are `rect`. When the sides are different, each one is drawn
+///clipped to its trapezoid, which goes from the outer corners of the border to the inner ones, like CSS does
+fn borders(canvas: &Canvas, rule: &DrawRule, rect: &Rect, rrect: &RRect) {
+    let borders = rule.borders();
+    if rule.has_uniform_borders() {
+        if borders.top.is_visible() {
+            stroke_border(canvas, rrect, &rule.side_paint(&borders.top), &borders.top);
+        }
+        return;
+    }
+
+    //every border is centered on its side
+    let (t, r, b, l) = (
+        borders.top.width * 0.5,
+        borders.right.width * 0.5,
+        borders.bottom.width * 0.5,
+        borders.left.width * 0.5,
+    );
+    let outer = Rect::new(rect.left - l, rect.top - t, rect.right + r, rect.bottom + b);
+    let inner = Rect::new(rect.left + l, rect.top + t, rect.right - r, rect.bottom - b);
+    let corners = |rect: &Rect| {
+        [
+            Point::new(rect.left, rect.top),
+            Point::new(rect.right, rect.top),
+            Point::new(rect.right, rect.bottom),
+            Point::new(rect.left, rect.bottom),
+        ]
+    };
+    let (outer, inner) = (corners(&outer), corners(&inner));
+    for (idx, side) in borders.as_array().iter().enumerate() {
+        if !side.is_visible() {
+            continue;
+        }
+        let next = (idx + 1) % 4;
+        let trapezoid = skia_safe::Path::polygon(
+            &[outer[idx], outer[next], inner[next], inner[idx]],
+            true,
+            None,
+            None,
+        );
+        canvas.save();
+        canvas.clip_path(&trapezoid, None, Some(true));
+        stroke_border(canvas, rrect, &rule.side_paint(side), side);
+        canvas.restore();
+    }
+}
+
+///Starts a layer that applies the shadows, glow and blur of `rule` to everything drawn until `restore`, for something at `bounds`.
+///Returns whether it was started, as there's no layer when there's no such effect
+fn begin_effects(canvas: &Canvas, rule: &DrawRule, bounds: &Rect) -> bool {
+    let Some(filter) = rule.image_filter_for(bounds) else {
+        return false;
+    };
+    let mut paint = Paint::default();
+    paint.set_image_filter(filter);
+    canvas.save_layer(&SaveLayerRec::default().paint(&paint));
+    true
+}
+
+///Blurs what was drawn below `rrect`, within it, if `rule` has got a backdrop blur
+fn backdrop_blur(canvas: &Canvas, rule: &DrawRule, rrect: &RRect) {
+    let Some(blur) = rule.effects().backdrop_blur else {
+        return;
+    };
+    let Some(filter) = image_filters::blur(
+        (blur.x, blur.y),
+        skia_safe::TileMode::Clamp,
+        None,
+        CropRect::NO_CROP_RECT,
+    ) else {
+        return;
+    };
+    canvas.save();
+    canvas.clip_rrect(rrect, None, true);
+    //an empty layer whose backdrop is blurred, so what's below it is drawn blurred when it's restored
+    canvas.save_layer(&SaveLayerRec::default().backdrop(&filter));
+    canvas.restore();
+    canvas.restore();
+}
+
+///Draws the inner shadows of `rule` within `rrect`. Each one is the blurred area around the rect offset by the shadow, clipped by it
+fn inner_shadows(canvas: &Canvas, rule: &DrawRule, rrect: &RRect) {
+    for shadow in &rule.effects().inner_shadows {
+        let spread = shadow.blur.x.abs().max(shadow.blur.y.abs()) * 3.0
+            + shadow.offset.x.abs().max(shadow.offset.y.abs());
+        let mut around = skia_safe::Path::new();
+        around
+            .add_rect(rrect.rect().with_outset((spread, spread)), None)
+            .add_rrect(rrect.with_offset((shadow.offset.x, shadow.offset.y)), None)
+            .set_fill_type(PathFillType::EvenOdd);
+        let mut paint = Paint::new(vec4f32_to_color(&shadow.color), None);
+        paint
+            .set_anti_alias(true)
+            .set_image_filter(image_filters::blur(
+                (shadow.blur.x, shadow.blur.y),
+                None,
+                None,
+                CropRect::NO_CROP_RECT,
+            ));
+        canvas.save();
+        canvas.clip_rrect(rrect, None, true);
+        canvas.draw_path(&around, &paint);
+        canvas.restore();
+    }
+}
+
+///Draws the given `square_info` on the `canvas`, including its borders and effects if it has got some
+pub(crate) fn square(canvas: &Canvas, square_info: &CandySquare) {
+    let rule = square_info.rule();
+
+    let rect = {
+        let position = square_info.position();
+        let size = square_info.size();
+        Rect::new(
+            position.x,
+            position.y,
+            position.x + size.x,
+            position.y + size.y,
+        )
+    };
+    let rrect = to_skia_rrect(&rect, &rule.corner_radii());
+
+    backdrop_blur(canvas, rule, &rrect);
+    let layered = begin_effects(canvas, rule, &rect);
+    canvas.draw_rrect(rrect, &rule.paint_for(&rect));
+    inner_shadows(canvas, rule, &rrect);
+    borders(canvas, rule, &rect, &rrect);
+    if layered {
+        canvas.restore();
+    }
+}
+
+#[inline]
+///Draws a circle centered at `position` with the given `radius` and filled with `color`
+pub(crate) fn circle(canvas: &Canvas, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+    let paint = Paint::new(vec4f32_to_color(color), None);
+    canvas.draw_circle(Point::new(position.x, position.y), radius, &paint);
+}
+
+///Draws every piece of `info` given on `runs` with where its baseline starts, applying its effects once and clipping them by the bounds
+///of the text
+fn text_runs(canvas: &Canvas, info: &CandyText, runs: &[(&str, Vector2<f32>)]) {
+    let rule = &info.rule;
+
+    let bounds = info.bounds();
+    let rect = Rect::from_xywh(bounds.x, bounds.y, bounds.width, bounds.height);
+    let layered = begin_effects(canvas, rule, &rect);
+    canvas.save();
+    let clip = info.clip_bounds();
+    canvas.clip_rect(
+        Rect::from_xywh(clip.x, clip.y, clip.width, clip.height),
+        None,
+        Some(true),
+    );
+    let paint = rule.paint_for(&rect);
+    for (content, origin) in runs {
+        canvas.draw_str(content, Point::new(origin.x, origin.y), info.font(), &paint);
+    }
+    canvas.restore();
+    if layered {
+        canvas.restore();
+    }
+}
+
+#[inline]
+///Draws the bytes of the content of `info` within the given `range` as a single line, clipped by the bounds of the text
+pub(crate) fn text_sliced(canvas: &Canvas, info: &CandyText, range: Range<usize>) {
+    text_runs(canvas, info, &[(&info.content()[range], *info.position())]);
+}
+
+#[inline]
+///Draws the whole content of `info`, laid out as a paragraph if it's got a style for it, clipped by the bounds of the text
+pub(crate) fn text(canvas: &Canvas, info: &CandyText) {
+    text_runs(canvas, info, &info.runs());
+}
+
+///Draws the given `image` at its position, clipped by the border radius of its rule
+pub(crate) fn image(canvas: &Canvas, image: &CandyImage) {
+    let Some(mut handler) = image.to_skia_image() else {
+        return;
+    };
+    let rule = image.rule();
+    let bounds = image.bounds();
+    let rect = Rect::from_xywh(bounds.x, bounds.y, bounds.width, bounds.height);
+    let sampling = sampling_options(&mut handler, image.sampling());
+
+    let layered = begin_effects(canvas, rule, &rect);
+    canvas.save();
+
+    canvas.clip_rrect(to_skia_rrect(&rect, &rule.corner_radii()), None, true);
+
+    for (from, to) in image.patches() {
+        canvas.draw_image_rect_with_sampling_options(
+            &handler,
+            Some((
+                &Rect::from_xywh(from.x, from.y, from.width, from.height),
+                SrcRectConstraint::Strict,
+            )),
+            Rect::from_xywh(to.x, to.y, to.width, to.height),
+            sampling,
+            &rule.inner,
+        );
+    }
+
+    canvas.restore();
+    if layered {
+        canvas.restore();
+    }
+}
+
+///Converts the given `sampling` into skia sampling options. Mipmaps are built on `image` when they're requested
+pub(crate) fn sampling_options(image: &mut Image, sampling: ImageSampling) -> SamplingOptions {
+    match sampling {
+        ImageSampling::Nearest => SamplingOptions::new(FilterMode::Nearest, MipmapMode::None),
+        ImageSampling::Linear => SamplingOptions::new(FilterMode::Linear, MipmapMode::None),
+        ImageSampling::Mipmap => {
+            if let Some(mipmapped) = image.with_default_mipmaps() {
+                *image = mipmapped;
+            }
+            SamplingOptions::new(FilterMode::Linear, MipmapMode::Linear)
+        }
+        ImageSampling::Cubic => CubicResampler::mitchell().into(),
+    }
+}
+
+///Draws every sprite of `sheet` with a single `draw_atlas`. Tints are multiplied with the sprites
+pub(crate) fn sprites(canvas: &Canvas, sheet: &CandySpriteSheet) {
+    let Some(mut atlas) = sheet.atlas().to_skia_image() else {
+        return;
+    };
+    let sampling = sampling_options(&mut atlas, sheet.sampling());
+    let mut transforms = Vec::with_capacity(sheet.sprites().len());
+    let mut regions = Vec::with_capacity(sheet.sprites().len());
+    let mut colors = Vec::with_capacity(sheet.sprites().len());
+    for (sprite, region) in sheet.visible() {
+        let (scos, ssin, translation) = sprite.transform(region, sheet.position());
+        transforms.push(RSXform::new(scos, ssin, (translation.x, translation.y)));
+        regions.push(Rect::from_xywh(
+            region.x,
+            region.y,
+            region.width,
+            region.height,
+        ));
+        let tint = sprite.tint.unwrap_or(Vector4::new(1.0, 1.0, 1.0, 1.0));
+        colors.push(vec4f32_to_color(&tint).to_color());
+    }
+    //without tints every color is white, so they aren't even given
+    let tinted = sheet.sprites().iter().any(|sprite| sprite.tint.is_some());
+    canvas.draw_atlas(
+        &atlas,
+        &transforms,
+        &regions,
+        tinted.then_some(colors.as_slice()),
+        skia_safe::BlendMode::Modulate,
+        sampling,
+        None,
+        &sheet.rule.inner,
+    );
+}
+
+///Converts the segments of `path` into a skia path
+pub(crate) fn to_skia_path(path: &CandyPath) -> skia_safe::Path {
+    let mut out = skia_safe::Path::new();
+    for segment in path.segments() {
+        match *segment {
+            PathSegment::MoveTo(to) => out.move_to((to.x, to.y)),
+            PathSegment::LineTo(to) => out.line_to((to.x, to.y)),
+            PathSegment::QuadTo { control, to } => {
+                out.quad_to((control.x, control.y), (to.x, to.y))
+            }
+            PathSegment::CubicTo {
+                control1,
+                control2,
+                to,
+            } => out.cubic_to(
+                (control1.x, control1.y),
+                (control2.x, control2.y),
+                (to.x, to.y),
+            ),
+            PathSegment::ArcTo {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => out.arc_to_rotated(
+                (radius.x, radius.y),
+                rotation,
+                if large_arc {
+                    ArcSize::Large
+                } else {
+                    ArcSize::Small
+                },
+                if sweep {
+                    PathDirection::CW
+                } else {
+                    PathDirection::CCW
+                },
+                (to.x, to.y),
+            ),
+            PathSegment::Close => out.close(),
+        };
+    }
+    out.set_fill_type(match path.fill_rule {
+        FillRule::NonZero => PathFillType::Winding,
+        FillRule::EvenOdd => PathFillType::EvenOdd,
+    });
+    out
+}
+
+///Draws the given `path` at its position, filled with the paint of its rule and stroked with its border if it has got some
+pub(crate) fn path(canvas: &Canvas, path: &CandyPath) {
+    let rule = &path.rule;
+    let skpath = to_skia_path(path);
+    let position = path.position();
+
+    canvas.save();
+    canvas.translate((position.x, position.y));
+    let bounds = skpath.compute_tight_bounds();
+    let layered = begin_effects(canvas, rule, &bounds);
+    //gradients follow the shape of the path, not the area it was given
+    canvas.draw_path(&skpath, &rule.paint_for(&bounds));
+
+    let border_color = rule.border_color;
+    if border_color.w != 0.0 && rule.border_width != 0.0 {
+        let stroke = &path.stroke;
+        let mut paint = rule.border_paint();
+        paint
+            .set_stroke_miter(stroke.miter_limit)
+            .set_stroke_cap(match stroke.cap {
+                StrokeCap::Butt => Cap::Butt,
+                StrokeCap::Round => Cap::Round,
+                StrokeCap::Square => Cap::Square,
+            })
+            .set_stroke_join(match stroke.join {
+                StrokeJoin::Miter => Join::Miter,
+                StrokeJoin::Round => Join::Round,
+                StrokeJoin::Bevel => Join::Bevel,
+            });
+        if stroke.is_dashed() {
+            paint.set_path_effect(PathEffect::dash(&stroke.dash_pattern(), stroke.dash_offset));
+        }
+        canvas.draw_path(&skpath, &paint);
+    }
+
+    if layered {
+        canvas.restore();
+    }
+    canvas.restore();
+}
+
+///Converts the given `matrix` into a skia matrix
+pub(crate) fn to_skia_matrix(matrix: &Matrix3<f32>) -> Matrix {
+    Matrix::new_all(
+        matrix[(0, 0)],
+        matrix[(0, 1)],
+        matrix[(0, 2)],
+        matrix[(1, 0)],
+        matrix[(1, 1)],
+        matrix[(1, 2)],
+        matrix[(2, 0)],
+        matrix[(2, 1)],
+        matrix[(2, 2)],
+    )
+}
+
+///Restricts what is drawn on the `canvas` to the given `rect` rounded by `radius`
+pub(crate) fn clip_rounded_rect(
+    canvas: &Canvas,
+    rect: &candy_shared_types::Rect,
+    radius: &Vector2<f32>,
+) {
+    let rect = Rect::from_xywh(rect.x, rect.y, rect.width, rect.height);
+    if radius.x == 0.0 && radius.y == 0.0 {
+        canvas.clip_rect(rect, None, Some(true));
+    } else {
+        canvas.clip_rrect(RRect::new_rect_xy(rect, radius.x, radius.y), None, true);
+    }
+}
+
+///Restricts what is drawn on the `canvas` to the given `rect` with each corner rounded by its radius on `radii`
+pub(crate) fn clip_rounded_rect_radii(
+    canvas: &Canvas,
+    rect: &candy_shared_types::Rect,
+    radii: &CornerRadii,
+) {
+    let rect = Rect::from_xywh(rect.x, rect.y, rect.width, rect.height);
+    canvas.clip_rrect(to_skia_rrect(&rect, radii), None, true);
+}
+
+///Restricts what is drawn on the `canvas` to the area covered by the given `rects`
+pub(crate) fn clip_rects(canvas: &Canvas, rects: &[candy_shared_types::Rect]) {
+    let mut path = skia_safe::Path::new();
+    for rect in rects {
+        path.add_rect(Rect::from_xywh(rect.x, rect.y, rect.width, rect.height), None);
+    }
+    //the rects of a damage are rounded out to whole pixels, so there's no edge to antialias
+    canvas.clip_path(&path, None, Some(false));
+}
+
+#[inline]
+///Multiplies the current transform of the `canvas` by `matrix`
+pub(crate) fn concat(canvas: &Canvas, matrix: &Matrix3<f32>) {
+    canvas.concat(&to_skia_matrix(matrix));
+}
+
+///Converts the given `mode` into its skia counterpart
+pub(crate) fn to_skia_blend_mode(mode: BlendMode) -> skia_safe::BlendMode {
+    use skia_safe::BlendMode as Sk;
+    match mode {
+        BlendMode::SrcOver => Sk::SrcOver,
+        BlendMode::Dst => Sk::Dst,
+        BlendMode::Src => Sk::Src,
+        BlendMode::Clear => Sk::Clear,
+        BlendMode::Plus => Sk::Plus,
+        BlendMode::Multiply => Sk::Multiply,
+        BlendMode::Screen => Sk::Screen,
+        BlendMode::Overlay => Sk::Overlay,
+        BlendMode::Darken => Sk::Darken,
+        BlendMode::Lighten => Sk::Lighten,
+        BlendMode::ColorDodge => Sk::ColorDodge,
+        BlendMode::ColorBurn => Sk::ColorBurn,
+        BlendMode::HardLight => Sk::HardLight,
+        BlendMode::SoftLight => Sk::SoftLight,
+        BlendMode::Difference => Sk::Difference,
+        BlendMode::Exclusion => Sk::Exclusion,
+        BlendMode::Hue => Sk::Hue,
+        BlendMode::Saturation => Sk::Saturation,
+        BlendMode::Color => Sk::Color,
+        BlendMode::Luminosity => Sk::Luminosity,
+    }
+}
+
+///Starts a new layer on the `canvas` that is composited with `alpha` and `blend_mode` when restored
+pub(crate) fn save_layer(
+    canvas: &Canvas,
+    alpha: f32,
+    blend_mode: BlendMode,
+    bounds: Option<&candy_shared_types::Rect>,
+) {
+    let mut paint = Paint::default();
+    paint
+        .set_alpha_f(alpha.clamp(0.0, 1.0))
+        .set_blend_mode(to_skia_blend_mode(blend_mode));
+    let bounds = bounds.map(|rect| Rect::from_xywh(rect.x, rect.y, rect.width, rect.height));
+    let mut rec = SaveLayerRec::default().paint(&paint);
+    if let Some(ref bounds) = bounds {
+        rec = rec.bounds(bounds);
+    }
+    canvas.save_layer(&rec);
+}
+
+#[inline]
+///Clears the whole `canvas` with the given `color`
+pub(crate) fn background(canvas: &Canvas, color: &Vector4<f32>) {
+    canvas.clear(*vec4f32_to_color(color));
+}
+
+///How far from the origin a picture can be drawn on. What is recorded doesn't know where it'll be drawn, such as the content of a
+///scrollable, so it's only limited by something no window reaches
+const PICTURE_EXTENT: f32 = 1.0e7;
+
+#[derive(Debug, Default)]
+///The pictures being recorded by a painter, from the outermost to the one drawn on. A picture started while another one is being
+///recorded is nested inside of it
+pub(crate) struct PictureRecorders {
+    recorders: Vec<PictureRecorder>,
+}
+
+impl PictureRecorders {
+    ///Starts recording a new picture. Everything drawn from now on goes to it, until `end`
+    pub(crate) fn begin(&mut self) {
+        let mut recorder = PictureRecorder::new();
+        recorder.begin_recording(
+            Rect::new(
+                -PICTURE_EXTENT,
+                -PICTURE_EXTENT,
+                PICTURE_EXTENT,
+                PICTURE_EXTENT,
+            ),
+            None,
+        );
+        self.recorders.push(recorder);
+    }
+
+    ///Finishes the picture being recorded. `None` if there wasn't any
+    pub(crate) fn end(&mut self) -> Option<CandyPicture> {
+        self.recorders
+            .pop()?
+            .finish_recording_as_picture(None)
+            .map(CandyPicture::new)
+    }
+
+    #[inline]
+    ///Retrieves the canvas of the picture being recorded, if there's any
+    pub(crate) fn canvas(&mut self) -> Option<&Canvas> {
+        self.recorders.last_mut()?.recording_canvas()
+    }
+}
+
+#[inline]
+///Draws the recorded `picture` on the `canvas`
+pub(crate) fn picture(canvas: &Canvas, picture: &CandyPicture) {
+    canvas.draw_picture(picture.skia_picture(), None, None);
+}
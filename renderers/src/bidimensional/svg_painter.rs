@@ -0,0 +1,886 @@
+use std::{fmt::Write, ops::Range, path::Path};
+
+use base64::Engine;
+use candy_shared_types::{
+    BlendMode, BorderSide, BorderStyle, ColorFilter, CornerRadii, Effects, Fill, GradientStop,
+    Rect, ShadowEffect, TileMode,
+};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::EncodedImageFormat;
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer,
+    bidimensional::skia,
+    primitives::{
+        CandyImage, CandyPath, CandySpriteSheet, CandySquare, CandyText, DrawRule, FillRule,
+        ImageSampling, PathSegment, StrokeCap, StrokeJoin, skia_image_of,
+    },
+};
+
+///Converts the rgb channels of the given `color` into a css `rgb(r, g, b)` color
+fn svg_color(color: &Vector4<f32>) -> String {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "rgb({},{},{})",
+        channel(color.x),
+        channel(color.y),
+        channel(color.z)
+    )
+}
+
+///Converts the segments of the given `path` into the content of the `d` attribute of a svg path
+fn svg_path_data(path: &CandyPath) -> String {
+    let mut out = String::new();
+    for segment in path.segments() {
+        let _ = match *segment {
+            PathSegment::MoveTo(to) => write!(out, "M{} {} ", to.x, to.y),
+            PathSegment::LineTo(to) => write!(out, "L{} {} ", to.x, to.y),
+            PathSegment::QuadTo { control, to } => {
+                write!(out, "Q{} {} {} {} ", control.x, control.y, to.x, to.y)
+            }
+            PathSegment::CubicTo {
+                control1,
+                control2,
+                to,
+            } => write!(
+                out,
+                "C{} {} {} {} {} {} ",
+                control1.x, control1.y, control2.x, control2.y, to.x, to.y
+            ),
+            PathSegment::ArcTo {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => write!(
+                out,
+                "A{} {} {} {} {} {} {} ",
+                radius.x, radius.y, rotation, large_arc as u8, sweep as u8, to.x, to.y
+            ),
+            PathSegment::Close => write!(out, "Z "),
+        };
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+///Creates the start of the element of a rect located at `position` with the given `size` and corner `radii`. It's a `rect` when every
+///corner is the same, otherwise a `path` with elliptical arcs on its corners
+fn svg_rounded_rect(position: &Vector2<f32>, size: &Vector2<f32>, radii: &CornerRadii) -> String {
+    let (x, y, w, h) = (position.x, position.y, size.x.max(0.0), size.y.max(0.0));
+    if radii.is_uniform() {
+        return format!(
+            "<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" rx=\"{}\" ry=\"{}\"",
+            radii.top_left.x, radii.top_left.y
+        );
+    }
+    let [tl, tr, br, bl] = radii.fitted(&Vector2::new(w, h)).as_array();
+    format!(
+        "<path d=\"M{} {y} H{} A{} {} 0 0 1 {} {} V{} A{} {} 0 0 1 {} {} H{} A{} {} 0 0 1 {x} {} V{} A{} {} 0 0 1 {} {y} Z\"",
+        x + tl.x,
+        x + w - tr.x,
+        tr.x,
+        tr.y,
+        x + w,
+        y + tr.y,
+        y + h - br.y,
+        br.x,
+        br.y,
+        x + w - br.x,
+        y + h,
+        x + bl.x,
+        bl.x,
+        bl.y,
+        y + h - bl.y,
+        y + tl.y,
+        tl.x,
+        tl.y,
+        x + tl.x,
+    )
+}
+
+///Escapes the given `content` so it can be safely written inside a xml node
+fn escape_xml(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for ch in content.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[derive(Debug)]
+///A painter that, instead of drawing on some surface, writes everything into a SVG document
+pub struct SvgPainter {
+    size: Vector2<f32>,
+    body: String,
+    clip_count: usize,
+    filter_count: usize,
+    image_count: usize,
+    ///Amount of gradients and patterns defined
+    paint_count: usize,
+    ///Amount of groups opened by clips and transforms that weren't closed yet
+    open_groups: usize,
+    ///Amount of groups that were open on every save
+    saves: Vec<usize>,
+}
+
+impl SvgPainter {
+    ///Creates a new SvgPainter whose document has the given `width` and `height`
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            size: Vector2::new(width, height),
+            body: String::new(),
+            clip_count: 0,
+            filter_count: 0,
+            image_count: 0,
+            paint_count: 0,
+            open_groups: 0,
+            saves: Vec::new(),
+        }
+    }
+
+    ///Retrieves the size of the document this painter is writing
+    pub fn size(&self) -> &Vector2<f32> {
+        &self.size
+    }
+
+    ///Discards everything drawn until now, keeping the size of the document
+    pub fn clear(&mut self) {
+        self.body.clear();
+        self.clip_count = 0;
+        self.filter_count = 0;
+        self.image_count = 0;
+        self.paint_count = 0;
+        self.open_groups = 0;
+        self.saves.clear();
+    }
+
+    ///Generates the whole SVG document with everything drawn until now. Groups of clips and transforms not restored yet are closed
+    pub fn to_svg(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}{}</svg>\n",
+            self.body,
+            "</g>\n".repeat(self.open_groups),
+            w = self.size.x,
+            h = self.size.y,
+        )
+    }
+
+    ///Writes the SVG document with everything drawn until now at the given `path`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_svg())
+    }
+
+    ///Defines a new clip path with the given rounded rect and returns its id
+    fn define_clip(&mut self, rect: &Rect, radius: &Vector2<f32>) -> String {
+        let id = format!("candy-clip-{}", self.clip_count);
+        self.clip_count += 1;
+        let _ = writeln!(
+            self.body,
+            "<clipPath id=\"{id}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" ry=\"{}\"/></clipPath>",
+            rect.x,
+            rect.y,
+            rect.width.max(0.0),
+            rect.height.max(0.0),
+            radius.x,
+            radius.y
+        );
+        id
+    }
+
+    ///Defines `image` encoded as png, so it can be referenced by the returned id as many times as needed. `None` if it couldn't be
+    ///encoded
+    fn define_image(
+        &mut self,
+        image: &skia_safe::Image,
+        sampling: ImageSampling,
+    ) -> Option<String> {
+        let data = image.encode(None, EncodedImageFormat::PNG, None)?;
+        let id = format!("candy-image-{}", self.image_count);
+        self.image_count += 1;
+        let _ = writeln!(
+            self.body,
+            "<defs><image id=\"{id}\" width=\"{}\" height=\"{}\"{} href=\"data:image/png;base64,{}\"/></defs>",
+            image.width(),
+            image.height(),
+            match sampling {
+                ImageSampling::Nearest => " image-rendering=\"pixelated\"",
+                _ => "",
+            },
+            base64::engine::general_purpose::STANDARD.encode(data.as_bytes())
+        );
+        Some(id)
+    }
+
+    ///Creates the attributes that fill an element with the fill of `rule`, whose gradients and patterns are placed on `bounds`, defining
+    ///them first. SVG has got no sweep gradients nor runtime shaders, so they're filled with their representative color, and patterns are
+    ///always repeated
+    fn fill_attributes(&mut self, rule: &DrawRule, bounds: &Rect) -> String {
+        let stops = |stops: &[GradientStop]| {
+            stops
+                .iter()
+                .map(|stop| {
+                    format!(
+                        "<stop offset=\"{}\" stop-color=\"{}\" stop-opacity=\"{}\"/>",
+                        stop.offset,
+                        svg_color(&stop.color),
+                        stop.color.w
+                    )
+                })
+                .collect::<String>()
+        };
+        let spread = |mode: &TileMode| match mode {
+            TileMode::Clamp | TileMode::Decal => "pad",
+            TileMode::Repeat => "repeat",
+            TileMode::Mirror => "reflect",
+        };
+        //gradients are placed on a 1x1 space, which is mapped to the bounds
+        let unit = format!(
+            "gradientUnits=\"userSpaceOnUse\" gradientTransform=\"matrix({} 0 0 {} {} {})\"",
+            bounds.width, bounds.height, bounds.x, bounds.y
+        );
+        let solid = || {
+            let color = rule.get_color();
+            format!(
+                " fill=\"{}\" fill-opacity=\"{}\"",
+                svg_color(&color),
+                color.w
+            )
+        };
+        let id = format!("candy-paint-{}", self.paint_count);
+        let definition = match rule.fill() {
+            Fill::LinearGradient {
+                start,
+                end,
+                stops: colors,
+                tile_mode,
+            } => format!(
+                "<defs><linearGradient id=\"{id}\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" spreadMethod=\"{}\" {unit}>{}</linearGradient></defs>",
+                start.x,
+                start.y,
+                end.x,
+                end.y,
+                spread(tile_mode),
+                stops(colors)
+            ),
+            Fill::RadialGradient {
+                center,
+                radius,
+                stops: colors,
+                tile_mode,
+            } => format!(
+                "<defs><radialGradient id=\"{id}\" cx=\"{}\" cy=\"{}\" r=\"{radius}\" spreadMethod=\"{}\" {unit}>{}</radialGradient></defs>",
+                center.x,
+                center.y,
+                spread(tile_mode),
+                stops(colors)
+            ),
+            Fill::Pattern {
+                image,
+                offset,
+                scale,
+                ..
+            } => {
+                let Some(image_id) = skia_image_of(image)
+                    .and_then(|image| self.define_image(&image, ImageSampling::Linear))
+                else {
+                    return solid();
+                };
+                format!(
+                    "<defs><pattern id=\"{id}\" patternUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"><use href=\"#{image_id}\" transform=\"scale({} {})\"/></pattern></defs>",
+                    bounds.x + offset.x,
+                    bounds.y + offset.y,
+                    image.width() as f32 * scale.x,
+                    image.height() as f32 * scale.y,
+                    scale.x,
+                    scale.y
+                )
+            }
+            _ => return solid(),
+        };
+        self.paint_count += 1;
+        let _ = writeln!(self.body, "{definition}");
+        format!(" fill=\"url(#{id})\"")
+    }
+
+    ///Defines a new clip path with the rect at `position` with the given `size` and corner `radii` and returns its id
+    fn define_rounded_clip(
+        &mut self,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+    ) -> String {
+        let id = format!("candy-clip-{}", self.clip_count);
+        self.clip_count += 1;
+        let _ = writeln!(
+            self.body,
+            "<clipPath id=\"{id}\">{}/></clipPath>",
+            svg_rounded_rect(position, size, radii)
+        );
+        id
+    }
+
+    ///Defines a new clip path with the given polygon and returns its id
+    fn define_polygon_clip(&mut self, points: &[(f32, f32)]) -> String {
+        let id = format!("candy-clip-{}", self.clip_count);
+        self.clip_count += 1;
+        let points = points
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            self.body,
+            "<clipPath id=\"{id}\"><polygon points=\"{points}\"/></clipPath>"
+        );
+        id
+    }
+
+    ///Writes the border `side` around the rect at `position` with the given `size` and `radii`, clipped by `clip` if given.
+    ///Double borders are written twice, a third of their width outside and inside of it
+    fn write_border(
+        &mut self,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+        side: &BorderSide,
+        clip: Option<&str>,
+        attributes: &str,
+    ) {
+        let mut extra = String::new();
+        let dash = side.style.dash_pattern(side.width);
+        if !dash.is_empty() {
+            let dash = dash
+                .iter()
+                .map(|len| len.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+            let _ = write!(extra, " stroke-dasharray=\"{dash}\"");
+        }
+        if side.style == BorderStyle::Dotted {
+            extra.push_str(" stroke-linecap=\"round\"");
+        }
+        if let Some(clip) = clip {
+            let _ = write!(extra, " clip-path=\"url(#{clip})\"");
+        }
+        let (width, offsets): (f32, &[f32]) = match side.style {
+            BorderStyle::Double => (side.width / 3.0, &[side.width / 3.0, -side.width / 3.0]),
+            _ => (side.width, &[0.0]),
+        };
+        for offset in offsets {
+            let _ = writeln!(
+                self.body,
+                "{} fill=\"none\" stroke=\"{}\" stroke-opacity=\"{}\" stroke-width=\"{width}\"{extra}{attributes}/>",
+                svg_rounded_rect(
+                    &position.add_scalar(-offset),
+                    &size.add_scalar(offset * 2.0),
+                    &radii.outset(*offset)
+                ),
+                svg_color(&side.color),
+                side.color.w,
+            );
+        }
+    }
+
+    ///Defines a new filter with the color matrix of the given `filter` and returns its id
+    fn define_filter(&mut self, filter: &ColorFilter) -> String {
+        let id = format!("candy-filter-{}", self.filter_count);
+        self.filter_count += 1;
+        let values = filter
+            .matrix()
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            self.body,
+            "<filter id=\"{id}\" color-interpolation-filters=\"sRGB\"><feColorMatrix type=\"matrix\" values=\"{values}\"/></filter>"
+        );
+        id
+    }
+
+    ///Defines a new filter that draws the shadows, glow and blur of `effects` for something at `bounds`, and returns its id. `None` if
+    ///there's no such effect. The backdrop blur can't be written, as SVG has got no way to read what is below an element
+    fn define_effects_filter(&mut self, effects: &Effects, bounds: &Rect) -> Option<String> {
+        if !effects.needs_layer() {
+            return None;
+        }
+        let id = format!("candy-filter-{}", self.filter_count);
+        self.filter_count += 1;
+        let area = effects.bounds(bounds);
+        let mut out = format!(
+            "<filter id=\"{id}\" filterUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" color-interpolation-filters=\"sRGB\">",
+            area.x, area.y, area.width, area.height
+        );
+        let glow = effects.glow.map(|glow| ShadowEffect {
+            color: glow.color,
+            offset: Vector2::zeros(),
+            blur: Vector2::new(glow.radius, glow.radius),
+        });
+        let mut merged = Vec::new();
+        for (idx, shadow) in glow.iter().chain(effects.shadows.iter()).enumerate() {
+            let _ = write!(
+                out,
+                "<feGaussianBlur in=\"SourceAlpha\" stdDeviation=\"{} {}\"/><feOffset dx=\"{}\" dy=\"{}\" result=\"alpha{idx}\"/><feFlood flood-color=\"{}\" flood-opacity=\"{}\"/><feComposite operator=\"in\" in2=\"alpha{idx}\" result=\"shadow{idx}\"/>",
+                shadow.blur.x,
+                shadow.blur.y,
+                shadow.offset.x,
+                shadow.offset.y,
+                svg_color(&shadow.color),
+                shadow.color.w
+            );
+            merged.push(format!("shadow{idx}"));
+        }
+        match effects.blur {
+            Some(blur) => {
+                let _ = write!(
+                    out,
+                    "<feGaussianBlur in=\"SourceGraphic\" stdDeviation=\"{} {}\" result=\"source\"/>",
+                    blur.x, blur.y
+                );
+                merged.push("source".to_string());
+            }
+            None => merged.push("SourceGraphic".to_string()),
+        }
+        out.push_str("<feMerge>");
+        for node in merged {
+            let _ = write!(out, "<feMergeNode in=\"{node}\"/>");
+        }
+        out.push_str("</feMerge></filter>");
+        let _ = writeln!(self.body, "{out}");
+        Some(id)
+    }
+
+    ///Defines a new filter that draws the given inner `shadows` above an element at `bounds`, within it, and returns its id. `None` if
+    ///there's no shadow
+    fn define_inner_shadows_filter(
+        &mut self,
+        shadows: &[ShadowEffect],
+        bounds: &Rect,
+    ) -> Option<String> {
+        if shadows.is_empty() {
+            return None;
+        }
+        let id = format!("candy-filter-{}", self.filter_count);
+        self.filter_count += 1;
+        let mut out = format!(
+            "<filter id=\"{id}\" filterUnits=\"userSpaceOnUse\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" color-interpolation-filters=\"sRGB\">",
+            bounds.x, bounds.y, bounds.width, bounds.height
+        );
+        //what's outside of the element is what casts the shadows inside of it
+        out.push_str("<feComponentTransfer in=\"SourceAlpha\" result=\"outside\"><feFuncA type=\"table\" tableValues=\"1 0\"/></feComponentTransfer>");
+        let mut merged = vec!["SourceGraphic".to_string()];
+        for (idx, shadow) in shadows.iter().enumerate() {
+            let _ = write!(
+                out,
+                "<feGaussianBlur in=\"outside\" stdDeviation=\"{} {}\"/><feOffset dx=\"{}\" dy=\"{}\" result=\"alpha{idx}\"/><feFlood flood-color=\"{}\" flood-opacity=\"{}\"/><feComposite operator=\"in\" in2=\"alpha{idx}\"/><feComposite operator=\"in\" in2=\"SourceAlpha\" result=\"shadow{idx}\"/>",
+                shadow.blur.x,
+                shadow.blur.y,
+                shadow.offset.x,
+                shadow.offset.y,
+                svg_color(&shadow.color),
+                shadow.color.w
+            );
+            merged.push(format!("shadow{idx}"));
+        }
+        out.push_str("<feMerge>");
+        for node in merged {
+            let _ = write!(out, "<feMergeNode in=\"{node}\"/>");
+        }
+        out.push_str("</feMerge></filter>");
+        let _ = writeln!(self.body, "{out}");
+        Some(id)
+    }
+
+    ///Opens a group that applies the shadows, glow and blur of `rule` to what is written inside of it, for something at `bounds`.
+    ///Returns whether it was opened, so it's closed by `end_effects`
+    fn begin_effects(&mut self, rule: &DrawRule, bounds: &Rect) -> bool {
+        let Some(id) = self.define_effects_filter(rule.effects(), bounds) else {
+            return false;
+        };
+        let _ = writeln!(self.body, "<g filter=\"url(#{id})\">");
+        true
+    }
+
+    #[inline]
+    ///Closes the group opened by `begin_effects`, if it was opened
+    fn end_effects(&mut self, opened: bool) {
+        if opened {
+            self.body.push_str("</g>\n");
+        }
+    }
+
+    ///Writes the borders of `rule` around the rect at `position` with the given `size` and `radii`. When the sides are different,
+    ///each one is written clipped to its trapezoid, the same way the skia painters do
+    fn write_borders(
+        &mut self,
+        rule: &DrawRule,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+        attributes: &str,
+    ) {
+        let borders = rule.borders();
+        if rule.has_uniform_borders() {
+            if borders.top.is_visible() {
+                self.write_border(position, size, radii, &borders.top, None, attributes);
+            }
+            return;
+        }
+        let (t, r, b, l) = (
+            borders.top.width * 0.5,
+            borders.right.width * 0.5,
+            borders.bottom.width * 0.5,
+            borders.left.width * 0.5,
+        );
+        let (x, y, w, h) = (position.x, position.y, size.x, size.y);
+        let outer = [
+            (x - l, y - t),
+            (x + w + r, y - t),
+            (x + w + r, y + h + b),
+            (x - l, y + h + b),
+        ];
+        let inner = [
+            (x + l, y + t),
+            (x + w - r, y + t),
+            (x + w - r, y + h - b),
+            (x + l, y + h - b),
+        ];
+        for (idx, side) in borders.as_array().iter().enumerate() {
+            if !side.is_visible() {
+                continue;
+            }
+            let next = (idx + 1) % 4;
+            let clip =
+                self.define_polygon_clip(&[outer[idx], outer[next], inner[next], inner[idx]]);
+            self.write_border(position, size, radii, side, Some(&clip), attributes);
+        }
+    }
+
+    ///Creates the attributes that apply the blend mode and the color filter of the given `rule`. Empty if it's got none of them
+    fn rule_attributes(&mut self, rule: &DrawRule) -> String {
+        let mut out = String::new();
+        let mode = rule.blend_mode();
+        //normal blending is the default, so it's not written
+        if let Some(name) = mode.css_name().filter(|_| mode != BlendMode::SrcOver) {
+            let _ = write!(out, " style=\"mix-blend-mode:{name}\"");
+        }
+        if let Some(filter) = rule.color_filter() {
+            let id = self.define_filter(filter);
+            let _ = write!(out, " filter=\"url(#{id})\"");
+        }
+        out
+    }
+
+    ///Writes every piece of `info` given on `runs` with where its baseline starts. A single one is written as a `text` element placed
+    ///at it, while many are written as `tspan`s of the same one
+    fn write_text(&mut self, info: &CandyText, runs: &[(&str, Vector2<f32>)]) {
+        let bounds = info.bounds();
+        let font = info.font();
+        let size = font.size();
+        //Same clipping used by the skia painters
+        let clip = self.define_clip(&info.clip_bounds(), &Vector2::zeros());
+        let effects = self.begin_effects(&info.rule, &bounds);
+        let attributes = self.rule_attributes(&info.rule);
+        let fill = self.fill_attributes(&info.rule, &bounds);
+        let position = match runs {
+            [(_, origin)] => format!("x=\"{}\" y=\"{}\" ", origin.x, origin.y),
+            _ => String::new(),
+        };
+        let _ = write!(
+            self.body,
+            "<text {position}font-family=\"{}\" font-size=\"{}\"{fill} clip-path=\"url(#{clip})\"{attributes} xml:space=\"preserve\">",
+            escape_xml(&font.typeface().family_name()),
+            size,
+        );
+        match runs {
+            [(content, _)] => self.body.push_str(&escape_xml(content)),
+            _ => {
+                for (content, origin) in runs {
+                    let _ = write!(
+                        self.body,
+                        "<tspan x=\"{}\" y=\"{}\">{}</tspan>",
+                        origin.x,
+                        origin.y,
+                        escape_xml(content)
+                    );
+                }
+            }
+        }
+        self.body.push_str("</text>\n");
+        self.end_effects(effects);
+    }
+}
+
+impl BiDimensionalRenderer for SvgPainter {
+    fn resize(&mut self, _: &Window, width: u32, height: u32) {
+        self.size = Vector2::new(width as f32, height as f32);
+    }
+
+    fn flush(&mut self) {}
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for SvgPainter {
+    fn square(&mut self, square_info: &CandySquare) {
+        let rule = square_info.rule();
+        let (position, size) = (*square_info.position(), *square_info.size());
+        let bounds = Rect::new(position.x, position.y, size.x, size.y);
+        let radii = rule.corner_radii();
+        let effects = self.begin_effects(rule, &bounds);
+        let attributes = self.rule_attributes(rule);
+        let fill = self.fill_attributes(rule, &bounds);
+        //the fill is grouped with its inner shadows, as it may already have got a filter of its own
+        let inner = self.define_inner_shadows_filter(&rule.effects().inner_shadows, &bounds);
+        if let Some(ref id) = inner {
+            let _ = writeln!(self.body, "<g filter=\"url(#{id})\">");
+        }
+        let _ = writeln!(
+            self.body,
+            "{}{fill}{attributes}/>",
+            svg_rounded_rect(&position, &size, &radii),
+        );
+        if inner.is_some() {
+            self.body.push_str("</g>\n");
+        }
+        self.write_borders(rule, &position, &size, &radii, &attributes);
+        self.end_effects(effects);
+    }
+
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        let _ = writeln!(
+            self.body,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" fill-opacity=\"{}\"/>",
+            position.x,
+            position.y,
+            radius,
+            svg_color(color),
+            color.w
+        );
+    }
+
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        self.write_text(info, &[(&info.content()[range], *info.position())]);
+    }
+
+    fn text(&mut self, info: &CandyText) {
+        self.write_text(info, &info.runs());
+    }
+
+    fn render_image(&mut self, image: &CandyImage) {
+        let Some(handler) = image.to_skia_image() else {
+            return;
+        };
+        let (position, size) = (*image.position(), *image.size());
+        let bounds = image.bounds();
+        //the image is defined once, so each patch only references it
+        let Some(id) = self.define_image(&handler, image.sampling()) else {
+            return;
+        };
+        let effects = self.begin_effects(image.rule(), &bounds);
+        let clip = self.define_rounded_clip(&position, &size, &image.rule().corner_radii());
+        let attributes = self.rule_attributes(image.rule());
+        let _ = writeln!(
+            self.body,
+            "<g clip-path=\"url(#{clip})\" opacity=\"{}\"{attributes}>",
+            image.rule().get_color().w
+        );
+        //each patch is a nested viewport showing only its part of the image, stretched to its size
+        for (from, to) in image.patches() {
+            let _ = writeln!(
+                self.body,
+                "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\" preserveAspectRatio=\"none\"><use href=\"#{id}\"/></svg>",
+                to.x, to.y, to.width, to.height, from.x, from.y, from.width, from.height
+            );
+        }
+        self.body.push_str("</g>\n");
+        self.end_effects(effects);
+    }
+
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        let Some(atlas) = sheet.atlas().to_skia_image() else {
+            return;
+        };
+        let Some(id) = self.define_image(&atlas, sheet.sampling()) else {
+            return;
+        };
+        let attributes = self.rule_attributes(&sheet.rule);
+        let _ = writeln!(
+            self.body,
+            "<g opacity=\"{}\"{attributes}>",
+            sheet.rule.get_color().w
+        );
+        //each sprite is a nested viewport showing only its region of the atlas. Only the alpha of the tints is used
+        for (sprite, region) in sheet.visible() {
+            let (scos, ssin, translation) = sprite.transform(region, sheet.position());
+            let _ = writeln!(
+                self.body,
+                "<g transform=\"matrix({scos} {ssin} {} {scos} {} {})\" opacity=\"{}\"><svg width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\"><use href=\"#{id}\"/></svg></g>",
+                -ssin,
+                translation.x,
+                translation.y,
+                sprite.tint.map_or(1.0, |tint| tint.w),
+                region.width,
+                region.height,
+                region.x,
+                region.y,
+                region.width,
+                region.height
+            );
+        }
+        self.body.push_str("</g>\n");
+    }
+
+    fn path(&mut self, path: &CandyPath) {
+        let rule = &path.rule;
+        let position = path.position();
+        let tight = skia::to_skia_path(path).compute_tight_bounds();
+        let effects = self.begin_effects(
+            rule,
+            &Rect::new(
+                position.x + tight.left,
+                position.y + tight.top,
+                tight.width(),
+                tight.height(),
+            ),
+        );
+        let attributes = self.rule_attributes(rule);
+        //gradients follow the shape of the path, not the area it was given. They're placed before it's translated
+        let fill = self.fill_attributes(
+            rule,
+            &Rect::new(tight.left, tight.top, tight.width(), tight.height()),
+        );
+        let _ = write!(
+            self.body,
+            "<path d=\"{}\" transform=\"translate({} {})\"{fill} fill-rule=\"{}\"",
+            svg_path_data(path),
+            position.x,
+            position.y,
+            match path.fill_rule {
+                FillRule::NonZero => "nonzero",
+                FillRule::EvenOdd => "evenodd",
+            }
+        );
+        self.body.push_str(&attributes);
+        if rule.border_color.w != 0.0 && rule.border_width != 0.0 {
+            let stroke = &path.stroke;
+            let _ = write!(
+                self.body,
+                " stroke=\"{}\" stroke-opacity=\"{}\" stroke-width=\"{}\" stroke-linecap=\"{}\" stroke-linejoin=\"{}\" stroke-miterlimit=\"{}\"",
+                svg_color(&rule.border_color),
+                rule.border_color.w,
+                rule.border_width,
+                match stroke.cap {
+                    StrokeCap::Butt => "butt",
+                    StrokeCap::Round => "round",
+                    StrokeCap::Square => "square",
+                },
+                match stroke.join {
+                    StrokeJoin::Miter => "miter",
+                    StrokeJoin::Round => "round",
+                    StrokeJoin::Bevel => "bevel",
+                },
+                stroke.miter_limit
+            );
+            if stroke.is_dashed() {
+                let dash = stroke
+                    .dash
+                    .iter()
+                    .map(|len| len.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let _ = write!(
+                    self.body,
+                    " stroke-dasharray=\"{dash}\" stroke-dashoffset=\"{}\"",
+                    stroke.dash_offset
+                );
+            }
+        }
+        self.body.push_str("/>\n");
+        self.end_effects(effects);
+    }
+
+    fn save(&mut self) {
+        self.saves.push(self.open_groups);
+    }
+
+    ///Opens a group with the given opacity and blend mode, which SVG already composites as a single unit. `bounds` isn't needed
+    fn save_layer(&mut self, alpha: f32, blend_mode: BlendMode, _: Option<&Rect>) {
+        self.save();
+        let blend = blend_mode
+            .css_name()
+            .map(|name| format!(" style=\"mix-blend-mode:{name}\""))
+            .unwrap_or_default();
+        let _ = writeln!(
+            self.body,
+            "<g opacity=\"{}\"{blend}>",
+            alpha.clamp(0.0, 1.0)
+        );
+        self.open_groups += 1;
+    }
+
+    fn restore(&mut self) {
+        let Some(open) = self.saves.pop() else {
+            return;
+        };
+        while self.open_groups > open {
+            self.body.push_str("</g>\n");
+            self.open_groups -= 1;
+        }
+    }
+
+    ///Opens a group whose children are clipped by the given rect. It's closed by the next `restore`
+    fn clip_rounded_rect(&mut self, rect: &Rect, radius: &Vector2<f32>) {
+        let clip = self.define_clip(rect, radius);
+        let _ = writeln!(self.body, "<g clip-path=\"url(#{clip})\">");
+        self.open_groups += 1;
+    }
+
+    fn clip_rounded_rect_radii(&mut self, rect: &Rect, radii: &CornerRadii) {
+        let clip = self.define_rounded_clip(
+            &Vector2::new(rect.x, rect.y),
+            &Vector2::new(rect.width, rect.height),
+            radii,
+        );
+        let _ = writeln!(self.body, "<g clip-path=\"url(#{clip})\">");
+        self.open_groups += 1;
+    }
+
+    ///Opens a group whose children are transformed by the given `matrix`. It's closed by the next `restore`
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        let _ = writeln!(
+            self.body,
+            "<g transform=\"matrix({} {} {} {} {} {})\">",
+            matrix[(0, 0)],
+            matrix[(1, 0)],
+            matrix[(0, 1)],
+            matrix[(1, 1)],
+            matrix[(0, 2)],
+            matrix[(1, 2)]
+        );
+        self.open_groups += 1;
+    }
+
+    fn background(&mut self, color: &Vector4<f32>) {
+        //Clearing is the same as discarding everything drawn before
+        self.clear();
+        let _ = writeln!(
+            self.body,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\" fill-opacity=\"{}\"/>",
+            svg_color(color),
+            color.w
+        );
+    }
+}
@@ -0,0 +1,1256 @@
+use std::ops::Range;
+
+use candy_shared_types::{
+    BlendMode, BorderSide, BorderStyle, ColorFilter, CornerRadii, Damage, Fill, RendererImage,
+    TileMode,
+};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::path::Verb;
+use tiny_skia::{
+    Color, FillRule, FilterQuality, GradientStop, IntRect, LineCap, LineJoin, LinearGradient, Mask,
+    Paint, Path, PathBuilder, Pattern, Pixmap, PixmapPaint, Point, RadialGradient, Rect,
+    SpreadMode, Stroke, StrokeDash, Transform,
+};
+use winit::window::Window;
+
+use crate::primitives::{DEFAULT_IMAGE_CACHE_BUDGET, LruCache};
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer, BiDimensionalRendererConstructor,
+    HeadlessRendererConstructor,
+    bidimensional::presenter::Presenter,
+    primitives::{
+        self, CandyImage, CandyPath, CandySpriteSheet, CandySquare, CandyText, DrawRule,
+        ImageSampling, PathSegment, StrokeCap, StrokeJoin, arc_to_cubics,
+    },
+};
+
+#[inline]
+///Converts the given `sampling` into the closest filter quality tiny-skia has got
+fn filter_quality(sampling: ImageSampling) -> FilterQuality {
+    match sampling {
+        ImageSampling::Nearest => FilterQuality::Nearest,
+        ImageSampling::Linear | ImageSampling::Mipmap => FilterQuality::Bilinear,
+        ImageSampling::Cubic => FilterQuality::Bicubic,
+    }
+}
+
+///The amount the control points of a cubic must be moved to approximate a quarter of an ellipse
+const KAPPA: f32 = 0.552_284_8;
+
+///Converts the given `color` into a tiny-skia color, clamping every channel to 0..1
+fn to_color(color: &Vector4<f32>) -> Color {
+    Color::from_rgba(
+        color.x.clamp(0.0, 1.0),
+        color.y.clamp(0.0, 1.0),
+        color.z.clamp(0.0, 1.0),
+        color.w.clamp(0.0, 1.0),
+    )
+    .unwrap_or(Color::BLACK)
+}
+
+///Creates a paint that fills with a solid `color`
+fn solid_paint(color: &Vector4<f32>) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(to_color(color));
+    paint.anti_alias = true;
+    paint
+}
+
+///Creates a paint that fills with the given `color` of the `rule`, going through its color filter and blended with its blend mode
+fn rule_paint(rule: &DrawRule, color: &Vector4<f32>) -> Paint<'static> {
+    let mut paint = match rule.color_filter() {
+        Some(filter) => solid_paint(&filter.apply(color)),
+        None => solid_paint(color),
+    };
+    paint.blend_mode = to_blend_mode(rule.blend_mode());
+    paint
+}
+
+///Converts the given tile `mode` into the closest spread mode tiny-skia has got. It can't leave the outside empty, so decal pads it
+fn to_spread_mode(mode: TileMode) -> SpreadMode {
+    match mode {
+        TileMode::Clamp | TileMode::Decal => SpreadMode::Pad,
+        TileMode::Repeat => SpreadMode::Repeat,
+        TileMode::Mirror => SpreadMode::Reflect,
+    }
+}
+
+///Creates a paint that fills what is located at `bounds` with the fill of the `rule`, going through its color filter and blended with
+///its blend mode. The pixels of patterns are read from `images`, and kept on `filtered` when they go through the color filter.
+///tiny-skia can't draw sweep gradients nor runtime shaders, so they're filled with their representative color
+fn fill_paint<'a>(
+    rule: &DrawRule,
+    bounds: &Rect,
+    images: &'a mut LruCache<u32, (u64, Pixmap)>,
+    filtered: &'a mut Option<Pixmap>,
+) -> Paint<'a> {
+    let mut paint = rule_paint(rule, &rule.get_color());
+    let filter = |color: &Vector4<f32>| match rule.color_filter() {
+        Some(filter) => to_color(&filter.apply(color)),
+        None => to_color(color),
+    };
+    //gradients are placed on a 1x1 space, which is mapped to the bounds
+    let unit = Transform::from_row(
+        bounds.width(),
+        0.0,
+        0.0,
+        bounds.height(),
+        bounds.x(),
+        bounds.y(),
+    );
+    let shader = match rule.fill() {
+        Fill::LinearGradient {
+            start,
+            end,
+            stops,
+            tile_mode,
+        } => LinearGradient::new(
+            Point::from_xy(start.x, start.y),
+            Point::from_xy(end.x, end.y),
+            stops
+                .iter()
+                .map(|stop| GradientStop::new(stop.offset, filter(&stop.color)))
+                .collect(),
+            to_spread_mode(*tile_mode),
+            unit,
+        ),
+        Fill::RadialGradient {
+            center,
+            radius,
+            stops,
+            tile_mode,
+        } => RadialGradient::new(
+            Point::from_xy(center.x, center.y),
+            Point::from_xy(center.x, center.y),
+            *radius,
+            stops
+                .iter()
+                .map(|stop| GradientStop::new(stop.offset, filter(&stop.color)))
+                .collect(),
+            to_spread_mode(*tile_mode),
+            unit,
+        ),
+        Fill::Pattern {
+            image,
+            offset,
+            scale,
+            tile_mode,
+        } => {
+            let Some(pixmap) = cached_pixmap(images, image) else {
+                return paint;
+            };
+            *filtered = rule
+                .color_filter()
+                .map(|filter| filter_pixmap(pixmap, filter));
+            Some(Pattern::new(
+                filtered.as_ref().unwrap_or(pixmap).as_ref(),
+                to_spread_mode(*tile_mode),
+                FilterQuality::Bilinear,
+                1.0,
+                Transform::from_row(
+                    scale.x,
+                    0.0,
+                    0.0,
+                    scale.y,
+                    bounds.x() + offset.x,
+                    bounds.y() + offset.y,
+                ),
+            ))
+        }
+        _ => None,
+    };
+    if let Some(shader) = shader {
+        paint.shader = shader;
+    }
+    paint
+}
+
+///Creates a copy of the given `pixmap` whose pixels went through `filter`
+fn filter_pixmap(pixmap: &Pixmap, filter: &ColorFilter) -> Pixmap {
+    let mut out = pixmap.clone();
+    for pixel in out.data_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        //the filter works on colors that aren't premultiplied
+        let demultiply = |value: u8| {
+            if alpha == 0.0 {
+                0.0
+            } else {
+                (value as f32 / 255.0 / alpha).min(1.0)
+            }
+        };
+        let color = filter.apply(&Vector4::new(
+            demultiply(pixel[0]),
+            demultiply(pixel[1]),
+            demultiply(pixel[2]),
+            alpha,
+        ));
+        pixel[0] = (color.x * color.w * 255.0).round() as u8;
+        pixel[1] = (color.y * color.w * 255.0).round() as u8;
+        pixel[2] = (color.z * color.w * 255.0).round() as u8;
+        pixel[3] = (color.w * 255.0).round() as u8;
+    }
+    out
+}
+
+///Creates the path of a rect located at (`x`, `y`) with the given `size` whose corners are rounded by `radii`, which are scaled down
+///when they don't fit
+fn rounded_rect(x: f32, y: f32, size: Vector2<f32>, radii: &CornerRadii) -> Option<Path> {
+    let (w, h) = (size.x, size.y);
+    let radii = radii.fitted(&size.map(|value| value.max(0.0)));
+    if radii == CornerRadii::default() {
+        return Some(PathBuilder::from_rect(Rect::from_xywh(x, y, w, h)?));
+    }
+    let [tl, tr, br, bl] = radii.as_array();
+    let mut builder = PathBuilder::new();
+    builder.move_to(x + tl.x, y);
+    builder.line_to(x + w - tr.x, y);
+    builder.cubic_to(
+        x + w - tr.x + tr.x * KAPPA,
+        y,
+        x + w,
+        y + tr.y - tr.y * KAPPA,
+        x + w,
+        y + tr.y,
+    );
+    builder.line_to(x + w, y + h - br.y);
+    builder.cubic_to(
+        x + w,
+        y + h - br.y + br.y * KAPPA,
+        x + w - br.x + br.x * KAPPA,
+        y + h,
+        x + w - br.x,
+        y + h,
+    );
+    builder.line_to(x + bl.x, y + h);
+    builder.cubic_to(
+        x + bl.x - bl.x * KAPPA,
+        y + h,
+        x,
+        y + h - bl.y + bl.y * KAPPA,
+        x,
+        y + h - bl.y,
+    );
+    builder.line_to(x, y + tl.y);
+    builder.cubic_to(
+        x,
+        y + tl.y - tl.y * KAPPA,
+        x + tl.x - tl.x * KAPPA,
+        y,
+        x + tl.x,
+        y,
+    );
+    builder.close();
+    builder.finish()
+}
+
+///Creates the stroke a border `side` is drawn with. A double border is stroked twice, so its width is a third of the side's one
+fn side_stroke(side: &BorderSide) -> Stroke {
+    let dash = side.style.dash_pattern(side.width);
+    Stroke {
+        width: match side.style {
+            BorderStyle::Double => side.width / 3.0,
+            _ => side.width,
+        },
+        line_cap: if side.style == BorderStyle::Dotted {
+            LineCap::Round
+        } else {
+            LineCap::Butt
+        },
+        dash: if dash.is_empty() {
+            None
+        } else {
+            StrokeDash::new(dash, 0.0)
+        },
+        ..Default::default()
+    }
+}
+
+///Converts the segments of the given candy `path` into a tiny-skia path located at its position
+fn convert_candy_path(path: &CandyPath) -> Option<Path> {
+    let offset = *path.position();
+    let mut builder = PathBuilder::new();
+    //arcs need to know where they start, which is the last point, or the start of the contour if it was closed
+    let mut current = Vector2::zeros();
+    let mut start = Vector2::zeros();
+    for segment in path.segments() {
+        match *segment {
+            PathSegment::MoveTo(to) => {
+                let p = to + offset;
+                builder.move_to(p.x, p.y);
+                (current, start) = (to, to);
+            }
+            PathSegment::LineTo(to) => {
+                let p = to + offset;
+                builder.line_to(p.x, p.y);
+                current = to;
+            }
+            PathSegment::QuadTo { control, to } => {
+                let (c, p) = (control + offset, to + offset);
+                builder.quad_to(c.x, c.y, p.x, p.y);
+                current = to;
+            }
+            PathSegment::CubicTo {
+                control1,
+                control2,
+                to,
+            } => {
+                let (c1, c2, p) = (control1 + offset, control2 + offset, to + offset);
+                builder.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                current = to;
+            }
+            PathSegment::ArcTo {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                for [c1, c2, p] in arc_to_cubics(current, radius, rotation, large_arc, sweep, to) {
+                    let (c1, c2, p) = (c1 + offset, c2 + offset, p + offset);
+                    builder.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                }
+                current = to;
+            }
+            PathSegment::Close => {
+                builder.close();
+                current = start;
+            }
+        }
+    }
+    builder.finish()
+}
+
+///The most a conic may be away from the quads it's approximated with, in pixels
+const CONIC_TOLERANCE: f32 = 0.25;
+
+///Appends the conic from `from` to `to` with the `control` point and `weight` to `builder`, approximated by quads. It's split in half
+///until the quads are within [CONIC_TOLERANCE] of it, which a circular arc of a quarter of a turn needs a few times
+fn conic_to_quads(
+    builder: &mut PathBuilder,
+    from: (f32, f32),
+    control: (f32, f32),
+    to: (f32, f32),
+    weight: f32,
+) {
+    //how far the conic is from its quad, which gets four times smaller every split
+    let a = weight - 1.0;
+    let k = a / (4.0 * (2.0 + a));
+    let mut error =
+        (k * (from.0 - 2.0 * control.0 + to.0)).hypot(k * (from.1 - 2.0 * control.1 + to.1));
+    let mut splits = 0;
+    while error > CONIC_TOLERANCE && splits < 5 {
+        error *= 0.25;
+        splits += 1;
+    }
+    split_conic(builder, from, control, to, weight, splits);
+}
+
+///Splits the conic in half `splits` times, appending each piece as a quad
+fn split_conic(
+    builder: &mut PathBuilder,
+    from: (f32, f32),
+    control: (f32, f32),
+    to: (f32, f32),
+    weight: f32,
+    splits: u32,
+) {
+    if splits == 0 || !weight.is_finite() {
+        builder.quad_to(control.0, control.1, to.0, to.1);
+        return;
+    }
+    let scale = (1.0 + weight).recip();
+    let weighted = (control.0 * weight, control.1 * weight);
+    let left = ((from.0 + weighted.0) * scale, (from.1 + weighted.1) * scale);
+    let right = ((weighted.0 + to.0) * scale, (weighted.1 + to.1) * scale);
+    let middle = ((left.0 + right.0) * 0.5, (left.1 + right.1) * 0.5);
+    let half = (0.5 + weight * 0.5).sqrt();
+    split_conic(builder, from, left, middle, half, splits - 1);
+    split_conic(builder, middle, right, to, half, splits - 1);
+}
+
+///Converts the given skia `path` into a tiny-skia one, moving every point by `offset`
+fn convert_path(path: &skia_safe::Path, offset: skia_safe::Point) -> Option<Path> {
+    let mut builder = PathBuilder::new();
+    let mut iter = skia_safe::path::Iter::new(path, false);
+    while let Some((verb, points)) = iter.next() {
+        let p = |idx: usize| (points[idx].x + offset.x, points[idx].y + offset.y);
+        match verb {
+            Verb::Move => {
+                let (x, y) = p(0);
+                builder.move_to(x, y);
+            }
+            Verb::Line => {
+                let (x, y) = p(1);
+                builder.line_to(x, y);
+            }
+            Verb::Quad => {
+                let ((x1, y1), (x, y)) = (p(1), p(2));
+                builder.quad_to(x1, y1, x, y);
+            }
+            Verb::Conic => {
+                let weight = iter.conic_weight().unwrap_or(1.0);
+                conic_to_quads(&mut builder, p(0), p(1), p(2), weight);
+            }
+            Verb::Cubic => {
+                let ((x1, y1), (x2, y2), (x, y)) = (p(1), p(2), p(3));
+                builder.cubic_to(x1, y1, x2, y2, x, y);
+            }
+            Verb::Close => builder.close(),
+            _ => {}
+        }
+    }
+    builder.finish()
+}
+
+///Copies the pixels of the given `image` into a tiny-skia pixmap
+fn image_to_pixmap(image: &RendererImage) -> Option<Pixmap> {
+    let mut pixmap = Pixmap::new(image.width(), image.height())?;
+    pixmap.data_mut().copy_from_slice(image.pixels());
+    Some(pixmap)
+}
+
+///Retrieves the pixels of the given `image` from `images`, copying them only if they aren't there yet, or only the regions that changed
+///since they were copied
+fn cached_pixmap<'a>(
+    images: &'a mut LruCache<u32, (u64, Pixmap)>,
+    image: &RendererImage,
+) -> Option<&'a Pixmap> {
+    let id = image.unique_id();
+    if !images.contains(&id) {
+        let pixmap = image_to_pixmap(image)?;
+        let bytes = pixmap.data().len();
+        return Some(&images.insert(id, (image.generation(), pixmap), bytes).1);
+    }
+    let (generation, pixmap) = images.get_mut(&id)?;
+    if *generation != image.generation() {
+        image.copy_changes_since(*generation, pixmap.data_mut())?;
+        *generation = image.generation();
+    }
+    Some(pixmap)
+}
+
+///Converts the given `mode` into its tiny-skia counterpart
+fn to_blend_mode(mode: BlendMode) -> tiny_skia::BlendMode {
+    use tiny_skia::BlendMode as Ts;
+    match mode {
+        BlendMode::SrcOver => Ts::SourceOver,
+        BlendMode::Dst => Ts::Destination,
+        BlendMode::Src => Ts::Source,
+        BlendMode::Clear => Ts::Clear,
+        BlendMode::Plus => Ts::Plus,
+        BlendMode::Multiply => Ts::Multiply,
+        BlendMode::Screen => Ts::Screen,
+        BlendMode::Overlay => Ts::Overlay,
+        BlendMode::Darken => Ts::Darken,
+        BlendMode::Lighten => Ts::Lighten,
+        BlendMode::ColorDodge => Ts::ColorDodge,
+        BlendMode::ColorBurn => Ts::ColorBurn,
+        BlendMode::HardLight => Ts::HardLight,
+        BlendMode::SoftLight => Ts::SoftLight,
+        BlendMode::Difference => Ts::Difference,
+        BlendMode::Exclusion => Ts::Exclusion,
+        BlendMode::Hue => Ts::Hue,
+        BlendMode::Saturation => Ts::Saturation,
+        BlendMode::Color => Ts::Color,
+        BlendMode::Luminosity => Ts::Luminosity,
+    }
+}
+
+///Retrieves the radius of each of the 3 box blurs that, in a row, are close to a gaussian blur with the given `sigma`
+fn box_radius(sigma: f32) -> usize {
+    //3 boxes with a width of w have got a variance of 3 * (w² - 1) / 12
+    let width = (4.0 * sigma * sigma + 1.0).sqrt();
+    ((width - 1.0) * 0.5).round().max(0.0) as usize
+}
+
+///Blurs `len` pixels of `data`, the first at `start` and each one `stride` bytes after the previous, with a box of the given `radius`.
+///What's outside of them is transparent. `line` is just a buffer, so it's not allocated for each line
+fn box_blur_line(
+    data: &mut [u8],
+    start: usize,
+    stride: usize,
+    len: usize,
+    radius: usize,
+    line: &mut Vec<[u8; 4]>,
+) {
+    line.clear();
+    line.extend((0..len).map(|idx| {
+        let at = start + idx * stride;
+        [data[at], data[at + 1], data[at + 2], data[at + 3]]
+    }));
+    let size = (radius * 2 + 1) as u32;
+    let mut sum = [0u32; 4];
+    for pixel in line.iter().take(radius + 1) {
+        for channel in 0..4 {
+            sum[channel] += pixel[channel] as u32;
+        }
+    }
+    for idx in 0..len {
+        let at = start + idx * stride;
+        for channel in 0..4 {
+            data[at + channel] = ((sum[channel] + size / 2) / size) as u8;
+        }
+        if let Some(pixel) = line.get(idx + radius + 1) {
+            for channel in 0..4 {
+                sum[channel] += pixel[channel] as u32;
+            }
+        }
+        if idx >= radius {
+            let pixel = line[idx - radius];
+            for channel in 0..4 {
+                sum[channel] -= pixel[channel] as u32;
+            }
+        }
+    }
+}
+
+///Blurs the pixels of `pixmap` by `sigma` on each axis, in pixels. As they're premultiplied, each channel can be blurred on its own
+fn blur_pixmap(pixmap: &mut Pixmap, sigma: Vector2<f32>) {
+    let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+    let (rx, ry) = (box_radius(sigma.x.abs()), box_radius(sigma.y.abs()));
+    let data = pixmap.data_mut();
+    let mut line = Vec::new();
+    for _ in 0..3 {
+        if rx > 0 {
+            for y in 0..height {
+                box_blur_line(data, y * width * 4, 4, width, rx, &mut line);
+            }
+        }
+        if ry > 0 {
+            for x in 0..width {
+                box_blur_line(data, x * 4, width * 4, height, ry, &mut line);
+            }
+        }
+    }
+}
+
+///A layer started by `save_layer`. While it's open, everything is drawn on a new pixmap, which is composited on `target` when restored
+struct Layer {
+    ///The pixmap that was being drawn on before this layer
+    target: Pixmap,
+    alpha: f32,
+    blend_mode: BlendMode,
+}
+
+///What `save` and `save_layer` store to be brought back on `restore`
+struct SavedState {
+    transform: Transform,
+    mask: Option<Mask>,
+    layer: Option<Layer>,
+}
+
+///A 2D renderer written in pure rust using tiny-skia. Everything is drawn on the CPU and, if created for a window, presented to it
+///through a software buffer. As the primitives still hold skia fonts, skia is used to read glyph outlines, so it's still linked even
+///though nothing is drawn with it
+pub struct TinySkiaRenderer {
+    pixmap: Pixmap,
+    ///Pixels of the images drawn, by their unique id, so they're copied only once. The least recently drawn ones are evicted when they
+    ///go over the budget, so images that change every frame don't pile up
+    images: LruCache<u32, (u64, Pixmap)>,
+    presenter: Option<Presenter>,
+    ///Whether the pixel buffer was just created, so nothing drawn before can be kept on the next frame
+    fresh: bool,
+    ///The damage of the frame being drawn, presented when flushed. `None` if the frame didn't start with `begin_frame`, so everything is
+    damage: Option<Damage>,
+    ///The transform everything is drawn with
+    transform: Transform,
+    ///The mask of the current clip. If `None`, nothing is clipped
+    mask: Option<Mask>,
+    ///The transforms, clips and layers saved, to be brought back on `restore`
+    saved: Vec<SavedState>,
+}
+
+impl std::fmt::Debug for TinySkiaRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = f.debug_struct("TinySkiaRenderer");
+        out.field("width", &self.pixmap.width())
+            .field("height", &self.pixmap.height());
+        out.field("cached_images", &self.images.len());
+        out.field("presenting", &self.presenter.is_some()).finish()
+    }
+}
+
+impl TinySkiaRenderer {
+    ///Creates a new renderer with a pixel buffer of the given `width` and `height`
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            pixmap: Pixmap::new(width.max(1), height.max(1))
+                .expect("Could not allocate the pixmap"),
+            images: LruCache::new(DEFAULT_IMAGE_CACHE_BUDGET),
+            presenter: None,
+            fresh: true,
+            damage: None,
+            transform: Transform::identity(),
+            mask: None,
+            saved: Vec::new(),
+        }
+    }
+
+    ///Creates a new renderer whose buffer has the size of the given `window` and is presented to it when flushed
+    pub fn for_window(window: &Window) -> Self {
+        let size = window.inner_size();
+        let mut out = Self::new(size.width, size.height);
+        out.presenter = Presenter::new(window);
+        if let Some(ref mut presenter) = out.presenter {
+            presenter.resize(size.width, size.height);
+        }
+        out
+    }
+
+    #[inline]
+    ///Retrieves the pixels drawn until now
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    ///Recreates the pixel buffer of this renderer with the given `width` and `height`. Everything drawn until now is discarded,
+    ///as well as the clips, as their masks have the old size
+    pub fn resize_surface(&mut self, width: u32, height: u32) {
+        self.pixmap =
+            Pixmap::new(width.max(1), height.max(1)).expect("Could not allocate the pixmap");
+        self.mask = None;
+        self.saved.clear();
+        self.fresh = true;
+        if let Some(ref mut presenter) = self.presenter {
+            presenter.resize(width, height);
+        }
+    }
+
+    ///Creates a mask that only allows drawing inside of `path`, transformed by the current transform, and of the current clip
+    fn clip_mask(&self, path: &Path) -> Option<Mask> {
+        if let Some(ref current) = self.mask {
+            let mut mask = current.clone();
+            mask.intersect_path(path, FillRule::Winding, true, self.transform);
+            return Some(mask);
+        }
+        let mut mask = Mask::new(self.pixmap.width(), self.pixmap.height())?;
+        mask.fill_path(path, FillRule::Winding, true, self.transform);
+        Some(mask)
+    }
+
+    ///Strokes the rounded rect at `position` with the given `size` and `radii` with the border `side` of `rule`, clipped by `mask`.
+    ///Double borders are stroked twice, a third of their width outside and inside of it
+    fn stroke_border(
+        &mut self,
+        rule: &DrawRule,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+        side: &BorderSide,
+        mask: Option<Mask>,
+    ) {
+        let paint = rule_paint(rule, &side.color);
+        let stroke = side_stroke(side);
+        let offsets: &[f32] = if side.style == BorderStyle::Double {
+            &[side.width / 3.0, -side.width / 3.0]
+        } else {
+            &[0.0]
+        };
+        for offset in offsets {
+            let radii = radii.outset(*offset);
+            let Some(path) = rounded_rect(
+                position.x - offset,
+                position.y - offset,
+                size.add_scalar(offset * 2.0),
+                &radii,
+            ) else {
+                continue;
+            };
+            self.pixmap
+                .stroke_path(&path, &paint, &stroke, self.transform, mask.as_ref());
+        }
+    }
+
+    ///Draws what `draw` draws on a new pixmap covering `bounds`, with the transform it's given, and composites it blurred by `sigma`.
+    ///It's clipped by `mask` if given, otherwise by the current clip
+    fn draw_blurred(
+        &mut self,
+        bounds: Rect,
+        sigma: Vector2<f32>,
+        mask: Option<&Mask>,
+        draw: impl FnOnce(&mut Pixmap, Transform),
+    ) {
+        let (sx, sy) = (sigma.x.abs() * 3.0, sigma.y.abs() * 3.0);
+        //what is farther than the blur from the pixmap can't be seen, even blurred
+        let visible = IntRect::from_xywh(
+            -sx.ceil() as i32,
+            -sy.ceil() as i32,
+            self.pixmap.width() + (sx.ceil() * 2.0) as u32,
+            self.pixmap.height() + (sy.ceil() * 2.0) as u32,
+        );
+        let Some(area) = bounds
+            .outset(sx, sy)
+            .and_then(|bounds| bounds.transform(self.transform))
+            .and_then(|bounds| bounds.round_out())
+            .zip(visible)
+            .and_then(|(area, visible)| area.intersect(&visible))
+        else {
+            return;
+        };
+        let Some(mut offscreen) = Pixmap::new(area.width(), area.height()) else {
+            return;
+        };
+        draw(
+            &mut offscreen,
+            self.transform
+                .post_translate(-area.x() as f32, -area.y() as f32),
+        );
+        blur_pixmap(&mut offscreen, sigma);
+        self.pixmap.draw_pixmap(
+            area.x(),
+            area.y(),
+            offscreen.as_ref(),
+            &PixmapPaint::default(),
+            Transform::identity(),
+            mask.or(self.mask.as_ref()),
+        );
+    }
+
+    ///Draws the glow and shadows of `rule` cast by the given `shape`
+    fn outer_effects(&mut self, rule: &DrawRule, shape: &Path) {
+        let effects = rule.effects();
+        if let Some(glow) = effects.glow {
+            let paint = solid_paint(&glow.color);
+            self.draw_blurred(
+                shape.bounds(),
+                Vector2::new(glow.radius, glow.radius),
+                None,
+                |pixmap, transform| {
+                    pixmap.fill_path(shape, &paint, FillRule::Winding, transform, None)
+                },
+            );
+        }
+        for shadow in &effects.shadows {
+            let Some(offset) = shape
+                .clone()
+                .transform(Transform::from_translate(shadow.offset.x, shadow.offset.y))
+            else {
+                continue;
+            };
+            let paint = solid_paint(&shadow.color);
+            self.draw_blurred(offset.bounds(), shadow.blur, None, |pixmap, transform| {
+                pixmap.fill_path(&offset, &paint, FillRule::Winding, transform, None)
+            });
+        }
+    }
+
+    ///Draws the inner shadows of `rule` within the given `shape`. Each one is the blurred area around the shape offset by the shadow,
+    ///clipped by it
+    fn inner_shadows(&mut self, rule: &DrawRule, shape: &Path) {
+        let shadows = &rule.effects().inner_shadows;
+        if shadows.is_empty() {
+            return;
+        }
+        let Some(mask) = self.clip_mask(shape) else {
+            return;
+        };
+        let bounds = shape.bounds();
+        for shadow in shadows {
+            let spread = shadow.blur.x.abs().max(shadow.blur.y.abs()) * 3.0
+                + shadow.offset.x.abs().max(shadow.offset.y.abs());
+            let mut around = PathBuilder::new();
+            if let Some(outer) = bounds.outset(spread, spread) {
+                around.push_rect(outer);
+            }
+            if let Some(offset) = shape
+                .clone()
+                .transform(Transform::from_translate(shadow.offset.x, shadow.offset.y))
+            {
+                around.push_path(&offset);
+            }
+            let Some(around) = around.finish() else {
+                continue;
+            };
+            let paint = solid_paint(&shadow.color);
+            self.draw_blurred(bounds, shadow.blur, Some(&mask), |pixmap, transform| {
+                pixmap.fill_path(&around, &paint, FillRule::EvenOdd, transform, None)
+            });
+        }
+    }
+
+    ///Replaces what was drawn below `shape`, within it, by itself blurred by `sigma`
+    fn backdrop_blur(&mut self, shape: &Path, sigma: Vector2<f32>) {
+        let (sx, sy) = (sigma.x.abs() * 3.0, sigma.y.abs() * 3.0);
+        let Some(area) = shape
+            .bounds()
+            .transform(self.transform)
+            .and_then(|bounds| bounds.outset(sx, sy))
+            .and_then(|bounds| bounds.round_out())
+            .zip(IntRect::from_xywh(
+                0,
+                0,
+                self.pixmap.width(),
+                self.pixmap.height(),
+            ))
+            .and_then(|(area, whole)| area.intersect(&whole))
+        else {
+            return;
+        };
+        let (Some(mut below), Some(mask)) = (self.pixmap.clone_rect(area), self.clip_mask(shape))
+        else {
+            return;
+        };
+        blur_pixmap(&mut below, sigma);
+        self.pixmap.draw_pixmap(
+            area.x(),
+            area.y(),
+            below.as_ref(),
+            &PixmapPaint {
+                blend_mode: tiny_skia::BlendMode::Source,
+                ..Default::default()
+            },
+            Transform::identity(),
+            Some(&mask),
+        );
+    }
+
+    ///Draws the borders of `rule` around the rounded rect at `position` with the given `size` and `radii`. When the sides are different,
+    ///each one is drawn clipped to its trapezoid, the same way the skia painters do
+    fn borders(
+        &mut self,
+        rule: &DrawRule,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+    ) {
+        let borders = rule.borders();
+        if rule.has_uniform_borders() {
+            if borders.top.is_visible() {
+                let mask = self.mask.clone();
+                self.stroke_border(rule, position, size, radii, &borders.top, mask);
+            }
+            return;
+        }
+        let (t, r, b, l) = (
+            borders.top.width * 0.5,
+            borders.right.width * 0.5,
+            borders.bottom.width * 0.5,
+            borders.left.width * 0.5,
+        );
+        let (x, y, w, h) = (position.x, position.y, size.x, size.y);
+        let outer = [
+            (x - l, y - t),
+            (x + w + r, y - t),
+            (x + w + r, y + h + b),
+            (x - l, y + h + b),
+        ];
+        let inner = [
+            (x + l, y + t),
+            (x + w - r, y + t),
+            (x + w - r, y + h - b),
+            (x + l, y + h - b),
+        ];
+        for (idx, side) in borders.as_array().iter().enumerate() {
+            if !side.is_visible() {
+                continue;
+            }
+            let next = (idx + 1) % 4;
+            let mut trapezoid = PathBuilder::new();
+            trapezoid.move_to(outer[idx].0, outer[idx].1);
+            trapezoid.line_to(outer[next].0, outer[next].1);
+            trapezoid.line_to(inner[next].0, inner[next].1);
+            trapezoid.line_to(inner[idx].0, inner[idx].1);
+            trapezoid.close();
+            let Some(mask) = trapezoid
+                .finish()
+                .and_then(|trapezoid| self.clip_mask(&trapezoid))
+            else {
+                continue;
+            };
+            self.stroke_border(rule, position, size, radii, side, Some(mask));
+        }
+    }
+
+    ///Draws every piece of `info` given on `runs` with where its baseline starts, clipped by the bounds of the text
+    fn draw_text(&mut self, info: &CandyText, runs: &[(&str, Vector2<f32>)]) {
+        let font = info.font();
+        let clip = info.clip_bounds();
+        let mask = Rect::from_xywh(clip.x, clip.y, clip.width, clip.height)
+            .and_then(|rect| self.clip_mask(&PathBuilder::from_rect(rect)));
+        let Some(mask) = mask else {
+            //The clip is empty, so nothing would be drawn
+            return;
+        };
+
+        let bounds = info.bounds();
+        let Some(bounds) = Rect::from_xywh(bounds.x, bounds.y, bounds.width, bounds.height) else {
+            return;
+        };
+        let mut filtered = None;
+        let paint = fill_paint(&info.rule, &bounds, &mut self.images, &mut filtered);
+        for (content, origin) in runs {
+            let glyphs = font.str_to_glyphs_vec(content);
+            let mut positions = vec![skia_safe::Point::default(); glyphs.len()];
+            font.get_pos(
+                &glyphs,
+                &mut positions,
+                Some(skia_safe::Point::new(origin.x, origin.y)),
+            );
+            for (glyph, position) in glyphs.iter().zip(positions) {
+                let Some(path) = font
+                    .get_path(*glyph)
+                    .and_then(|path| convert_path(&path, position))
+                else {
+                    continue;
+                };
+                self.pixmap.fill_path(
+                    &path,
+                    &paint,
+                    FillRule::Winding,
+                    self.transform,
+                    Some(&mask),
+                );
+            }
+        }
+    }
+}
+
+impl HeadlessRendererConstructor for TinySkiaRenderer {
+    #[inline]
+    fn new_headless(width: u32, height: u32) -> Self {
+        Self::new(width, height)
+    }
+}
+
+impl BiDimensionalRendererConstructor for TinySkiaRenderer {
+    #[cfg(feature = "opengl")]
+    fn new(window: &Window, _: &glutin::config::Config) -> Self {
+        Self::for_window(window)
+    }
+
+    #[inline]
+    fn from_window(window: &Window) -> Option<Self> {
+        Some(Self::for_window(window))
+    }
+}
+
+impl BiDimensionalRenderer for TinySkiaRenderer {
+    fn resize(&mut self, _: &Window, width: u32, height: u32) {
+        self.resize_surface(width, height);
+    }
+
+    ///Presents everything drawn to the window, if there's one. Layers that weren't restored are composited before it
+    fn flush(&mut self) {
+        while !self.saved.is_empty() {
+            self.restore();
+        }
+        let damage = self.damage.take().unwrap_or_else(Damage::full);
+        if let Some(ref mut presenter) = self.presenter {
+            presenter.present(self.pixmap.data(), &damage);
+        }
+    }
+
+    ///The pixel buffer keeps the last frame, so only the damage is repainted, unless it was just created
+    fn begin_frame(&mut self, damage: &Damage) -> Damage {
+        let repaint = if std::mem::take(&mut self.fresh) {
+            Damage::full()
+        } else {
+            damage.clone()
+        };
+        self.damage = Some(repaint.clone());
+        repaint
+    }
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for TinySkiaRenderer {
+    fn square(&mut self, square_info: &CandySquare) {
+        let rule = square_info.rule();
+        let (position, size) = (*square_info.position(), *square_info.size());
+        let radii = rule.corner_radii();
+        let Some(path) = rounded_rect(position.x, position.y, size, &radii) else {
+            return;
+        };
+        let effects = rule.effects();
+        if let Some(blur) = effects.backdrop_blur {
+            self.backdrop_blur(&path, blur);
+        }
+        self.outer_effects(rule, &path);
+
+        //Everything of the square is drawn on a layer to be blurred at once
+        let layer = effects
+            .blur
+            .and_then(|_| Pixmap::new(self.pixmap.width(), self.pixmap.height()))
+            .map(|layer| std::mem::replace(&mut self.pixmap, layer));
+        let mut filtered = None;
+        let paint = fill_paint(rule, &path.bounds(), &mut self.images, &mut filtered);
+        self.pixmap.fill_path(
+            &path,
+            &paint,
+            FillRule::Winding,
+            self.transform,
+            self.mask.as_ref(),
+        );
+        self.inner_shadows(rule, &path);
+        self.borders(rule, &position, &size, &radii);
+        if let (Some(target), Some(blur)) = (layer, effects.blur) {
+            let mut drawn = std::mem::replace(&mut self.pixmap, target);
+            blur_pixmap(&mut drawn, blur);
+            //The clip was already applied to what was drawn on the layer, so it's composited as it is
+            self.pixmap.draw_pixmap(
+                0,
+                0,
+                drawn.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        let Some(path) = PathBuilder::from_circle(position.x, position.y, radius) else {
+            return;
+        };
+        self.pixmap.fill_path(
+            &path,
+            &solid_paint(color),
+            FillRule::Winding,
+            self.transform,
+            self.mask.as_ref(),
+        );
+    }
+
+    #[inline]
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        self.draw_text(info, &[(&info.content()[range], *info.position())]);
+    }
+
+    #[inline]
+    fn text(&mut self, info: &CandyText) {
+        self.draw_text(info, &info.runs());
+    }
+
+    fn render_image(&mut self, image: &CandyImage) {
+        let rule = image.rule();
+        let position = *image.position();
+        let size = *image.size();
+        let Some(path) = rounded_rect(position.x, position.y, size, &rule.corner_radii()) else {
+            return;
+        };
+        self.outer_effects(rule, &path);
+        //the patches are clipped by the rounded rect of the image the same way the skia painters do
+        let Some(mask) = self.clip_mask(&path) else {
+            return;
+        };
+
+        let Some(pixmap) = cached_pixmap(&mut self.images, image.image_handler()) else {
+            return;
+        };
+        let filtered = rule
+            .color_filter()
+            .map(|filter| filter_pixmap(pixmap, filter));
+        let pixmap = filtered.as_ref().unwrap_or(pixmap);
+        let quality = filter_quality(image.sampling());
+        for (from, to) in image.patches() {
+            let Some(rect) = Rect::from_xywh(to.x, to.y, to.width, to.height) else {
+                continue;
+            };
+            let (sx, sy) = (to.width / from.width, to.height / from.height);
+            let paint = Paint {
+                shader: Pattern::new(
+                    pixmap.as_ref(),
+                    SpreadMode::Pad,
+                    quality,
+                    rule.get_color().w,
+                    Transform::from_row(sx, 0.0, 0.0, sy, to.x - from.x * sx, to.y - from.y * sy),
+                ),
+                anti_alias: true,
+                blend_mode: to_blend_mode(rule.blend_mode()),
+                ..Default::default()
+            };
+            self.pixmap
+                .fill_rect(rect, &paint, self.transform, Some(&mask));
+        }
+    }
+
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        let rule = &sheet.rule;
+        let Some(pixmap) = cached_pixmap(&mut self.images, sheet.atlas().image_handler()) else {
+            return;
+        };
+        let quality = filter_quality(sheet.sampling());
+        //tiny-skia can't multiply the sprites by a color, so only the alpha of their tints is used
+        for (sprite, region) in sheet.visible() {
+            let Some(rect) = Rect::from_xywh(0.0, 0.0, region.width, region.height) else {
+                continue;
+            };
+            let (scos, ssin, translation) = sprite.transform(region, sheet.position());
+            let placement =
+                Transform::from_row(scos, ssin, -ssin, scos, translation.x, translation.y);
+            let paint = Paint {
+                shader: Pattern::new(
+                    pixmap.as_ref(),
+                    SpreadMode::Pad,
+                    quality,
+                    rule.get_color().w * sprite.tint.map_or(1.0, |tint| tint.w),
+                    Transform::from_translate(-region.x, -region.y),
+                ),
+                anti_alias: true,
+                blend_mode: to_blend_mode(rule.blend_mode()),
+                ..Default::default()
+            };
+            self.pixmap.fill_rect(
+                rect,
+                &paint,
+                self.transform.pre_concat(placement),
+                self.mask.as_ref(),
+            );
+        }
+    }
+
+    fn path(&mut self, path: &CandyPath) {
+        let Some(converted) = convert_candy_path(path) else {
+            return;
+        };
+        let rule = &path.rule;
+        let mut filtered = None;
+        //gradients follow the shape of the path, not the area it was given
+        let paint = fill_paint(rule, &converted.bounds(), &mut self.images, &mut filtered);
+        self.pixmap.fill_path(
+            &converted,
+            &paint,
+            match path.fill_rule {
+                primitives::FillRule::NonZero => FillRule::Winding,
+                primitives::FillRule::EvenOdd => FillRule::EvenOdd,
+            },
+            self.transform,
+            self.mask.as_ref(),
+        );
+
+        if rule.border_color.w == 0.0 || rule.border_width == 0.0 {
+            return;
+        }
+        let style = &path.stroke;
+        let stroke = Stroke {
+            width: rule.border_width,
+            miter_limit: style.miter_limit,
+            line_cap: match style.cap {
+                StrokeCap::Butt => LineCap::Butt,
+                StrokeCap::Round => LineCap::Round,
+                StrokeCap::Square => LineCap::Square,
+            },
+            line_join: match style.join {
+                StrokeJoin::Miter => LineJoin::Miter,
+                StrokeJoin::Round => LineJoin::Round,
+                StrokeJoin::Bevel => LineJoin::Bevel,
+            },
+            dash: if style.is_dashed() {
+                StrokeDash::new(style.dash_pattern(), style.dash_offset)
+            } else {
+                None
+            },
+        };
+        self.pixmap.stroke_path(
+            &converted,
+            &rule_paint(rule, &rule.border_color),
+            &stroke,
+            self.transform,
+            self.mask.as_ref(),
+        );
+    }
+
+    fn background(&mut self, color: &Vector4<f32>) {
+        if self.mask.is_none() {
+            self.pixmap.fill(to_color(color));
+            return;
+        }
+        //Only what is inside of the clip is cleared
+        let rect = Rect::from_xywh(
+            0.0,
+            0.0,
+            self.pixmap.width() as f32,
+            self.pixmap.height() as f32,
+        );
+        if let Some(rect) = rect {
+            let mut paint = solid_paint(color);
+            paint.blend_mode = tiny_skia::BlendMode::Source;
+            self.pixmap
+                .fill_rect(rect, &paint, Transform::identity(), self.mask.as_ref());
+        }
+    }
+
+    fn save(&mut self) {
+        self.saved.push(SavedState {
+            transform: self.transform,
+            mask: self.mask.clone(),
+            layer: None,
+        });
+    }
+
+    ///Starts drawing on a new transparent pixmap with the size of the current one. `bounds` isn't used, as the pixmap is composited whole
+    fn save_layer(
+        &mut self,
+        alpha: f32,
+        blend_mode: BlendMode,
+        _: Option<&candy_shared_types::Rect>,
+    ) {
+        let Some(layer) = Pixmap::new(self.pixmap.width(), self.pixmap.height()) else {
+            self.save();
+            return;
+        };
+        self.saved.push(SavedState {
+            transform: self.transform,
+            mask: self.mask.clone(),
+            layer: Some(Layer {
+                target: std::mem::replace(&mut self.pixmap, layer),
+                alpha,
+                blend_mode,
+            }),
+        });
+    }
+
+    fn restore(&mut self) {
+        let Some(state) = self.saved.pop() else {
+            return;
+        };
+        self.transform = state.transform;
+        self.mask = state.mask;
+        if let Some(layer) = state.layer {
+            let drawn = std::mem::replace(&mut self.pixmap, layer.target);
+            //The clip was already applied to what was drawn on the layer, so it's composited as it is
+            self.pixmap.draw_pixmap(
+                0,
+                0,
+                drawn.as_ref(),
+                &PixmapPaint {
+                    opacity: layer.alpha.clamp(0.0, 1.0),
+                    blend_mode: to_blend_mode(layer.blend_mode),
+                    quality: FilterQuality::Nearest,
+                },
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    #[inline]
+    fn clip_rounded_rect(&mut self, rect: &candy_shared_types::Rect, radius: &Vector2<f32>) {
+        self.clip_rounded_rect_radii(rect, &CornerRadii::uniform(*radius));
+    }
+
+    fn clip_rounded_rect_radii(&mut self, rect: &candy_shared_types::Rect, radii: &CornerRadii) {
+        let size = Vector2::new(rect.width, rect.height);
+        match rounded_rect(rect.x, rect.y, size, radii) {
+            Some(path) => self.mask = self.clip_mask(&path),
+            //An empty clip allows nothing to be drawn
+            None => {
+                self.mask = Mask::new(self.pixmap.width(), self.pixmap.height());
+            }
+        }
+    }
+
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        self.transform = self.transform.pre_concat(Transform::from_row(
+            matrix[(0, 0)],
+            matrix[(1, 0)],
+            matrix[(0, 1)],
+            matrix[(1, 1)],
+            matrix[(0, 2)],
+            matrix[(1, 2)],
+        ));
+    }
+}
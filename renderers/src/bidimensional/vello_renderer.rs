@@ -0,0 +1,978 @@
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use candy_shared_types::{
+    BlendMode, BorderSide, BorderStyle, ColorFilter, CornerRadii, Damage, GradientStop,
+    RendererImage, TileMode,
+};
+use nalgebra::{Matrix3, Vector2, Vector4};
+use skia_safe::{Point, typeface::TypefaceId};
+use vello_common::filter_effects::{Filter, FilterFunction};
+use vello_cpu::{
+    Glyph, Image, ImageSource, PaintType, Pixmap, RenderContext,
+    color::{AlphaColor, Srgb},
+    kurbo::{Affine, BezPath, Cap, Circle, Join, Rect, Shape, Stroke},
+    peniko::{Blob, Compose, Extend, Fill, FontData, Gradient, ImageQuality, ImageSampler, Mix},
+};
+use winit::window::Window;
+
+use crate::{
+    BiDimensionalPainter, BiDimensionalRenderer, BiDimensionalRendererConstructor,
+    HeadlessRendererConstructor,
+    bidimensional::presenter::Presenter,
+    primitives::{
+        CandyFont, CandyImage, CandyPath, CandySpriteSheet, CandySquare, CandyText,
+        DEFAULT_IMAGE_CACHE_BUDGET, DrawRule, FillRule, ImageSampling, LruCache, PathSegment,
+        StrokeCap, StrokeJoin, arc_to_cubics,
+    },
+};
+
+#[inline]
+///Converts the given `sampling` into the closest image quality vello has got
+fn image_quality(sampling: ImageSampling) -> ImageQuality {
+    match sampling {
+        ImageSampling::Nearest => ImageQuality::Low,
+        ImageSampling::Linear | ImageSampling::Mipmap => ImageQuality::Medium,
+        ImageSampling::Cubic => ImageQuality::High,
+    }
+}
+
+///The amount the control points of a cubic must be moved to approximate a quarter of an ellipse
+const KAPPA: f64 = 0.552_284_8;
+
+///Converts the given `color` into a vello color
+fn to_color(color: &Vector4<f32>) -> AlphaColor<Srgb> {
+    AlphaColor::new([
+        color.x.clamp(0.0, 1.0),
+        color.y.clamp(0.0, 1.0),
+        color.z.clamp(0.0, 1.0),
+        color.w.clamp(0.0, 1.0),
+    ])
+}
+
+///Creates the path of a rect located at `position` with the given `size` whose corners are rounded by `radii`, which are scaled down
+///when they don't fit
+fn rounded_rect(position: &Vector2<f32>, size: &Vector2<f32>, radii: &CornerRadii) -> BezPath {
+    let (x, y) = (position.x as f64, position.y as f64);
+    let (w, h) = (size.x.max(0.0) as f64, size.y.max(0.0) as f64);
+    let radii = radii.fitted(&size.map(|value| value.max(0.0)));
+    if radii == CornerRadii::default() {
+        return Rect::new(x, y, x + w, y + h).to_path(0.1);
+    }
+    let [tl, tr, br, bl] = radii
+        .as_array()
+        .map(|radius| (radius.x as f64, radius.y as f64));
+    let mut path = BezPath::new();
+    path.move_to((x + tl.0, y));
+    path.line_to((x + w - tr.0, y));
+    path.curve_to(
+        (x + w - tr.0 + tr.0 * KAPPA, y),
+        (x + w, y + tr.1 - tr.1 * KAPPA),
+        (x + w, y + tr.1),
+    );
+    path.line_to((x + w, y + h - br.1));
+    path.curve_to(
+        (x + w, y + h - br.1 + br.1 * KAPPA),
+        (x + w - br.0 + br.0 * KAPPA, y + h),
+        (x + w - br.0, y + h),
+    );
+    path.line_to((x + bl.0, y + h));
+    path.curve_to(
+        (x + bl.0 - bl.0 * KAPPA, y + h),
+        (x, y + h - bl.1 + bl.1 * KAPPA),
+        (x, y + h - bl.1),
+    );
+    path.line_to((x, y + tl.1));
+    path.curve_to(
+        (x, y + tl.1 - tl.1 * KAPPA),
+        (x + tl.0 - tl.0 * KAPPA, y),
+        (x + tl.0, y),
+    );
+    path.close_path();
+    path
+}
+
+///Creates the stroke a border `side` is drawn with. A double border is stroked twice, so its width is a third of the side's one
+fn side_stroke(side: &BorderSide) -> Stroke {
+    let width = match side.style {
+        BorderStyle::Double => side.width / 3.0,
+        _ => side.width,
+    };
+    let mut stroke = Stroke::new(width as f64);
+    let dash = side.style.dash_pattern(side.width);
+    if !dash.is_empty() {
+        stroke = stroke.with_dashes(0.0, dash.into_iter().map(|len| len as f64));
+    }
+    if side.style == BorderStyle::Dotted {
+        stroke = stroke.with_caps(Cap::Round);
+    }
+    stroke
+}
+
+///Converts the segments of the given candy `path` into a vello path located at its position
+fn convert_candy_path(path: &CandyPath) -> BezPath {
+    let offset = *path.position();
+    let point = |p: Vector2<f32>| ((p.x + offset.x) as f64, (p.y + offset.y) as f64);
+    let mut out = BezPath::new();
+    //arcs need to know where they start, which is the last point, or the start of the contour if it was closed
+    let mut current = Vector2::zeros();
+    let mut start = Vector2::zeros();
+    for segment in path.segments() {
+        match *segment {
+            PathSegment::MoveTo(to) => {
+                out.move_to(point(to));
+                (current, start) = (to, to);
+            }
+            PathSegment::LineTo(to) => {
+                out.line_to(point(to));
+                current = to;
+            }
+            PathSegment::QuadTo { control, to } => {
+                out.quad_to(point(control), point(to));
+                current = to;
+            }
+            PathSegment::CubicTo {
+                control1,
+                control2,
+                to,
+            } => {
+                out.curve_to(point(control1), point(control2), point(to));
+                current = to;
+            }
+            PathSegment::ArcTo {
+                radius,
+                rotation,
+                large_arc,
+                sweep,
+                to,
+            } => {
+                for [c1, c2, p] in arc_to_cubics(current, radius, rotation, large_arc, sweep, to) {
+                    out.curve_to(point(c1), point(c2), point(p));
+                }
+                current = to;
+            }
+            PathSegment::Close => {
+                out.close_path();
+                current = start;
+            }
+        }
+    }
+    out
+}
+
+///Copies the pixels of the given `image` into a vello pixmap. `None` if it's wider or taller than the 65535 pixels vello can hold
+fn image_to_pixmap(image: &RendererImage) -> Option<Arc<Pixmap>> {
+    let w = u16::try_from(image.width()).ok()?;
+    let h = u16::try_from(image.height()).ok()?;
+    let mut pixmap = Pixmap::new(w, h);
+    pixmap
+        .data_as_u8_slice_mut()
+        .copy_from_slice(image.pixels());
+    pixmap.recompute_may_have_opacities();
+    Some(Arc::new(pixmap))
+}
+
+///Retrieves the pixels of the given `image` from `images`, copying them only if they aren't there yet, or only the regions that changed
+///since they were copied
+fn cached_pixmap(
+    images: &mut LruCache<u32, (u64, Arc<Pixmap>)>,
+    image: &RendererImage,
+) -> Option<Arc<Pixmap>> {
+    let id = image.unique_id();
+    if !images.contains(&id) {
+        let pixmap = image_to_pixmap(image)?;
+        let bytes = pixmap.data_as_u8_slice().len();
+        return Some(
+            images
+                .insert(id, (image.generation(), pixmap), bytes)
+                .1
+                .clone(),
+        );
+    }
+    let (generation, pixmap) = images.get_mut(&id)?;
+    if *generation != image.generation() {
+        //the last frame may still hold the pixmap, in which case it's copied before being written
+        let target = Arc::make_mut(pixmap);
+        image.copy_changes_since(*generation, target.data_as_u8_slice_mut())?;
+        target.recompute_may_have_opacities();
+        *generation = image.generation();
+    }
+    Some(pixmap.clone())
+}
+
+///Converts the given tile `mode` into the closest extend vello has got. It can't leave the outside empty, so decal pads it
+fn to_extend(mode: TileMode) -> Extend {
+    match mode {
+        TileMode::Clamp | TileMode::Decal => Extend::Pad,
+        TileMode::Repeat => Extend::Repeat,
+        TileMode::Mirror => Extend::Reflect,
+    }
+}
+
+///Converts the given `mode` into its vello counterpart
+fn to_blend_mode(mode: BlendMode) -> vello_cpu::peniko::BlendMode {
+    let (mix, compose) = match mode {
+        BlendMode::SrcOver => (Mix::Normal, Compose::SrcOver),
+        BlendMode::Dst => (Mix::Normal, Compose::Dest),
+        BlendMode::Src => (Mix::Normal, Compose::Copy),
+        BlendMode::Clear => (Mix::Normal, Compose::Clear),
+        BlendMode::Plus => (Mix::Normal, Compose::Plus),
+        BlendMode::Multiply => (Mix::Multiply, Compose::SrcOver),
+        BlendMode::Screen => (Mix::Screen, Compose::SrcOver),
+        BlendMode::Overlay => (Mix::Overlay, Compose::SrcOver),
+        BlendMode::Darken => (Mix::Darken, Compose::SrcOver),
+        BlendMode::Lighten => (Mix::Lighten, Compose::SrcOver),
+        BlendMode::ColorDodge => (Mix::ColorDodge, Compose::SrcOver),
+        BlendMode::ColorBurn => (Mix::ColorBurn, Compose::SrcOver),
+        BlendMode::HardLight => (Mix::HardLight, Compose::SrcOver),
+        BlendMode::SoftLight => (Mix::SoftLight, Compose::SrcOver),
+        BlendMode::Difference => (Mix::Difference, Compose::SrcOver),
+        BlendMode::Exclusion => (Mix::Exclusion, Compose::SrcOver),
+        BlendMode::Hue => (Mix::Hue, Compose::SrcOver),
+        BlendMode::Saturation => (Mix::Saturation, Compose::SrcOver),
+        BlendMode::Color => (Mix::Color, Compose::SrcOver),
+        BlendMode::Luminosity => (Mix::Luminosity, Compose::SrcOver),
+    };
+    vello_cpu::peniko::BlendMode::new(mix, compose)
+}
+
+///Creates a copy of the given `pixmap` whose pixels went through `filter`
+fn filter_pixmap(pixmap: &Pixmap, filter: &ColorFilter) -> Arc<Pixmap> {
+    let mut out = pixmap.clone();
+    for pixel in out.data_as_u8_slice_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+        //the filter works on colors that aren't premultiplied
+        let demultiply = |value: u8| {
+            if alpha == 0.0 {
+                0.0
+            } else {
+                (value as f32 / 255.0 / alpha).min(1.0)
+            }
+        };
+        let color = filter.apply(&Vector4::new(
+            demultiply(pixel[0]),
+            demultiply(pixel[1]),
+            demultiply(pixel[2]),
+            alpha,
+        ));
+        pixel[0] = (color.x * color.w * 255.0).round() as u8;
+        pixel[1] = (color.y * color.w * 255.0).round() as u8;
+        pixel[2] = (color.z * color.w * 255.0).round() as u8;
+        pixel[3] = (color.w * 255.0).round() as u8;
+    }
+    out.recompute_may_have_opacities();
+    Arc::new(out)
+}
+
+///A 2D renderer that uses Vello's CPU rasterizer, so it works without any GPU. Commands are only rasterized when flushed, after which
+///the pixels can be read, or, if created for a window, are presented to it through a software buffer.
+///As the primitives still hold skia fonts, the font data of their typefaces is given to vello to draw texts
+pub struct VelloRenderer {
+    context: RenderContext,
+    pixmap: Pixmap,
+    ///Font data of the typefaces used until now, so they're read only once
+    fonts: HashMap<TypefaceId, FontData>,
+    ///Pixels of the images drawn, by their unique id, so they're copied only once. The least recently drawn ones are evicted when
+    ///they go over the budget, so images that change every frame don't pile up
+    images: LruCache<u32, (u64, Arc<Pixmap>)>,
+    presenter: Option<Presenter>,
+    ///Amount of clip and compositing layers pushed that weren't popped yet
+    layers: usize,
+    ///The transforms and amounts of clip layers saved, to be brought back on `restore`
+    saved: Vec<(Affine, usize)>,
+}
+
+impl std::fmt::Debug for VelloRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VelloRenderer")
+            .field("width", &self.pixmap.width())
+            .field("height", &self.pixmap.height())
+            .field("cached_fonts", &self.fonts.len())
+            .field("cached_images", &self.images.len())
+            .field("presenting", &self.presenter.is_some())
+            .finish()
+    }
+}
+
+impl VelloRenderer {
+    ///Creates a new renderer with a pixel buffer of the given `width` and `height`. Vello limits both of them to 65535
+    pub fn new(width: u32, height: u32) -> Self {
+        let (w, h) = Self::clamp_size(width, height);
+        Self {
+            context: RenderContext::new(w, h),
+            pixmap: Pixmap::new(w, h),
+            fonts: HashMap::new(),
+            images: LruCache::new(DEFAULT_IMAGE_CACHE_BUDGET),
+            presenter: None,
+            layers: 0,
+            saved: Vec::new(),
+        }
+    }
+
+    ///Creates a new renderer whose buffer has the size of the given `window` and is presented to it when flushed
+    pub fn for_window(window: &Window) -> Self {
+        let size = window.inner_size();
+        let mut out = Self::new(size.width, size.height);
+        out.presenter = Presenter::new(window);
+        if let Some(ref mut presenter) = out.presenter {
+            presenter.resize(size.width, size.height);
+        }
+        out
+    }
+
+    fn clamp_size(width: u32, height: u32) -> (u16, u16) {
+        (
+            width.clamp(1, u16::MAX as u32) as u16,
+            height.clamp(1, u16::MAX as u32) as u16,
+        )
+    }
+
+    #[inline]
+    ///Retrieves the pixels rasterized on the last flush, as premultiplied RGBA
+    pub fn pixmap(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    ///Recreates the context and the pixel buffer of this renderer with the given `width` and `height`. Everything drawn until now is discarded
+    pub fn resize_surface(&mut self, width: u32, height: u32) {
+        let (w, h) = Self::clamp_size(width, height);
+        self.context = RenderContext::new(w, h);
+        self.pixmap = Pixmap::new(w, h);
+        self.layers = 0;
+        self.saved.clear();
+        if let Some(ref mut presenter) = self.presenter {
+            presenter.resize(width, height);
+        }
+    }
+
+    ///Retrieves the font data vello needs to draw with the given `font`
+    fn font_data(&mut self, font: &CandyFont) -> Option<FontData> {
+        let typeface = font.typeface();
+        let id = typeface.unique_id();
+        if let Some(data) = self.fonts.get(&id) {
+            return Some(data.clone());
+        }
+        let (bytes, index) = typeface.to_font_data()?;
+        let data = FontData::new(Blob::new(Arc::new(bytes)), index as u32);
+        self.fonts.insert(id, data.clone());
+        Some(data)
+    }
+
+    ///Sets the paint to the given `color` of the `rule`, going through its color filter, and the blend mode to the one of the rule
+    fn set_rule_paint(&mut self, rule: &DrawRule, color: &Vector4<f32>) {
+        let color = rule
+            .color_filter()
+            .map_or(*color, |filter| filter.apply(color));
+        self.context.set_paint(to_color(&color));
+        self.context
+            .set_blend_mode(to_blend_mode(rule.blend_mode()));
+    }
+
+    ///Sets the paint to the fill of the `rule`, going through its color filter, with its gradients and patterns placed on `bounds`, and
+    ///the blend mode to the one of the rule. The paint transform is changed, so it must be reset after drawing. vello can't draw runtime
+    ///shaders, so they're filled with their fallback color
+    fn set_fill_paint(&mut self, rule: &DrawRule, bounds: &Rect) {
+        use candy_shared_types::Fill as Cf;
+        self.set_rule_paint(rule, &rule.get_color());
+        let filter = |color: &Vector4<f32>| {
+            to_color(
+                &rule
+                    .color_filter()
+                    .map_or(*color, |filter| filter.apply(color)),
+            )
+        };
+        let stops = |stops: &[GradientStop]| {
+            stops
+                .iter()
+                .map(|stop| (stop.offset, filter(&stop.color)))
+                .collect::<Vec<_>>()
+        };
+        let (x, y) = (bounds.x0, bounds.y0);
+        //gradients are placed on a 1x1 space, which is mapped to the bounds
+        let unit = Affine::new([bounds.width(), 0.0, 0.0, bounds.height(), x, y]);
+        let (paint, transform): (PaintType, Affine) = match rule.fill() {
+            Cf::LinearGradient {
+                start,
+                end,
+                stops: colors,
+                tile_mode,
+            } => (
+                Gradient::new_linear(
+                    (start.x as f64, start.y as f64),
+                    (end.x as f64, end.y as f64),
+                )
+                .with_extend(to_extend(*tile_mode))
+                .with_stops(stops(colors).as_slice())
+                .into(),
+                unit,
+            ),
+            Cf::RadialGradient {
+                center,
+                radius,
+                stops: colors,
+                tile_mode,
+            } => (
+                Gradient::new_radial((center.x as f64, center.y as f64), *radius)
+                    .with_extend(to_extend(*tile_mode))
+                    .with_stops(stops(colors).as_slice())
+                    .into(),
+                unit,
+            ),
+            Cf::SweepGradient {
+                center,
+                start_angle,
+                end_angle,
+                stops: colors,
+                tile_mode,
+            } => (
+                Gradient::new_sweep(
+                    (center.x as f64, center.y as f64),
+                    start_angle.to_radians(),
+                    end_angle.to_radians(),
+                )
+                .with_extend(to_extend(*tile_mode))
+                .with_stops(stops(colors).as_slice())
+                .into(),
+                unit,
+            ),
+            Cf::Pattern {
+                image,
+                offset,
+                scale,
+                tile_mode,
+            } => {
+                let Some(pixmap) = cached_pixmap(&mut self.images, image) else {
+                    return;
+                };
+                let pixmap = match rule.color_filter() {
+                    Some(filter) => filter_pixmap(&pixmap, filter),
+                    None => pixmap,
+                };
+                (
+                    PaintType::Image(Image {
+                        image: ImageSource::Pixmap(pixmap),
+                        sampler: ImageSampler::default().with_extend(to_extend(*tile_mode)),
+                    }),
+                    Affine::new([
+                        scale.x as f64,
+                        0.0,
+                        0.0,
+                        scale.y as f64,
+                        x + offset.x as f64,
+                        y + offset.y as f64,
+                    ]),
+                )
+            }
+            _ => return,
+        };
+        self.context.set_paint(paint);
+        self.context.set_paint_transform(transform);
+    }
+
+    ///Sets the paint to the given solid `color`, drawn the usual way
+    fn set_solid_paint(&mut self, color: &Vector4<f32>) {
+        self.context.set_paint(to_color(color));
+        self.context
+            .set_blend_mode(to_blend_mode(BlendMode::SrcOver));
+    }
+
+    ///Strokes the rounded rect at `position` with the given `size` and `radii` with the border `side` of `rule`. Double borders are stroked
+    ///twice, a third of their width outside and inside of it
+    fn stroke_border(
+        &mut self,
+        rule: &DrawRule,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+        side: &BorderSide,
+    ) {
+        self.context.set_stroke(side_stroke(side));
+        self.set_rule_paint(rule, &side.color);
+        if side.style != BorderStyle::Double {
+            self.context
+                .stroke_path(&rounded_rect(position, size, radii));
+            return;
+        }
+        let offset = side.width / 3.0;
+        for offset in [offset, -offset] {
+            let radii = radii.outset(offset);
+            self.context.stroke_path(&rounded_rect(
+                &position.add_scalar(-offset),
+                &size.add_scalar(offset * 2.0),
+                &radii,
+            ));
+        }
+    }
+
+    ///Fills `shape` with the given `color`, blurred by `blur`. Vello only blurs the same amount on both axes, so the biggest one is used
+    fn fill_blurred(&mut self, shape: &BezPath, color: &Vector4<f32>, blur: Vector2<f32>) {
+        let radius = blur.x.abs().max(blur.y.abs());
+        if radius > 0.0 {
+            self.context
+                .push_filter_layer(Filter::from_function(FilterFunction::Blur { radius }));
+        }
+        self.set_solid_paint(color);
+        self.context.fill_path(shape);
+        if radius > 0.0 {
+            self.context.pop_layer();
+        }
+    }
+
+    ///Draws the glow and shadows of `rule` cast by the given `shape`. As vello records everything to rasterize it all at once, what's
+    ///below can't be read, so the backdrop blur isn't drawn
+    fn outer_effects(&mut self, rule: &DrawRule, shape: &BezPath) {
+        let effects = rule.effects();
+        if let Some(glow) = effects.glow {
+            self.fill_blurred(shape, &glow.color, Vector2::new(glow.radius, glow.radius));
+        }
+        for shadow in &effects.shadows {
+            let offset = Affine::translate((shadow.offset.x as f64, shadow.offset.y as f64));
+            self.fill_blurred(&(offset * shape.clone()), &shadow.color, shadow.blur);
+        }
+    }
+
+    ///Draws the inner shadows of `rule` within the given `shape`, whose bounds are at `position` with the given `size`. Each one is the
+    ///blurred area around the shape offset by the shadow, clipped by it
+    fn inner_shadows(
+        &mut self,
+        rule: &DrawRule,
+        shape: &BezPath,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+    ) {
+        let shadows = &rule.effects().inner_shadows;
+        if shadows.is_empty() {
+            return;
+        }
+        self.context.push_clip_layer(shape);
+        self.context.set_fill_rule(Fill::EvenOdd);
+        for shadow in shadows {
+            let spread = (shadow.blur.x.abs().max(shadow.blur.y.abs()) * 3.0
+                + shadow.offset.x.abs().max(shadow.offset.y.abs())) as f64;
+            let mut around = Rect::new(
+                position.x as f64 - spread,
+                position.y as f64 - spread,
+                (position.x + size.x) as f64 + spread,
+                (position.y + size.y) as f64 + spread,
+            )
+            .to_path(0.1);
+            let offset = Affine::translate((shadow.offset.x as f64, shadow.offset.y as f64));
+            around.extend(offset * shape.clone());
+            self.fill_blurred(&around, &shadow.color, shadow.blur);
+        }
+        self.context.set_fill_rule(Fill::NonZero);
+        self.context.pop_layer();
+    }
+
+    ///Draws the borders of `rule` around the rounded rect at `position` with the given `size` and `radii`. When the sides are different,
+    ///each one is drawn clipped to its trapezoid, the same way the skia painters do
+    fn borders(
+        &mut self,
+        rule: &DrawRule,
+        position: &Vector2<f32>,
+        size: &Vector2<f32>,
+        radii: &CornerRadii,
+    ) {
+        let borders = rule.borders();
+        if rule.has_uniform_borders() {
+            if borders.top.is_visible() {
+                self.stroke_border(rule, position, size, radii, &borders.top);
+            }
+            return;
+        }
+        let (t, r, b, l) = (
+            borders.top.width as f64 * 0.5,
+            borders.right.width as f64 * 0.5,
+            borders.bottom.width as f64 * 0.5,
+            borders.left.width as f64 * 0.5,
+        );
+        let (x, y, w, h) = (
+            position.x as f64,
+            position.y as f64,
+            size.x as f64,
+            size.y as f64,
+        );
+        let outer = [
+            (x - l, y - t),
+            (x + w + r, y - t),
+            (x + w + r, y + h + b),
+            (x - l, y + h + b),
+        ];
+        let inner = [
+            (x + l, y + t),
+            (x + w - r, y + t),
+            (x + w - r, y + h - b),
+            (x + l, y + h - b),
+        ];
+        for (idx, side) in borders.as_array().iter().enumerate() {
+            if !side.is_visible() {
+                continue;
+            }
+            let next = (idx + 1) % 4;
+            let mut trapezoid = BezPath::new();
+            trapezoid.move_to(outer[idx]);
+            trapezoid.line_to(outer[next]);
+            trapezoid.line_to(inner[next]);
+            trapezoid.line_to(inner[idx]);
+            trapezoid.close_path();
+            self.context.push_clip_layer(&trapezoid);
+            self.stroke_border(rule, position, size, radii, side);
+            self.context.pop_layer();
+        }
+    }
+
+    ///Draws every piece of `info` given on `runs` with where its baseline starts as a single glyph run, clipped by the bounds of the
+    ///text
+    fn draw_text(&mut self, info: &CandyText, runs: &[(&str, Vector2<f32>)]) {
+        let font = info.font();
+        let Some(data) = self.font_data(font) else {
+            return;
+        };
+        //Same clipping used by the skia painters
+        let bounds = info.clip_bounds();
+        let clip = Rect::new(
+            bounds.x as f64,
+            bounds.y as f64,
+            (bounds.x + bounds.width) as f64,
+            (bounds.y + bounds.height) as f64,
+        );
+
+        let mut glyphs = Vec::new();
+        for (content, origin) in runs {
+            let ids = font.str_to_glyphs_vec(content);
+            let mut positions = vec![Point::default(); ids.len()];
+            font.get_pos(&ids, &mut positions, Some(Point::new(origin.x, origin.y)));
+            glyphs.extend(ids.iter().zip(positions).map(|(id, position)| Glyph {
+                id: *id as u32,
+                x: position.x,
+                y: position.y,
+            }));
+        }
+
+        self.context.push_clip_layer(&clip.to_path(0.1));
+        let bounds = info.bounds();
+        self.set_fill_paint(
+            &info.rule,
+            &Rect::new(
+                bounds.x as f64,
+                bounds.y as f64,
+                bounds.right() as f64,
+                bounds.bottom() as f64,
+            ),
+        );
+        self.context
+            .glyph_run(&data)
+            .font_size(font.size())
+            .fill_glyphs(glyphs.into_iter());
+        self.context.reset_paint_transform();
+        self.context.pop_layer();
+    }
+}
+
+impl HeadlessRendererConstructor for VelloRenderer {
+    #[inline]
+    fn new_headless(width: u32, height: u32) -> Self {
+        Self::new(width, height)
+    }
+}
+
+impl BiDimensionalRendererConstructor for VelloRenderer {
+    #[cfg(feature = "opengl")]
+    fn new(window: &Window, _: &glutin::config::Config) -> Self {
+        Self::for_window(window)
+    }
+
+    #[inline]
+    fn from_window(window: &Window) -> Option<Self> {
+        Some(Self::for_window(window))
+    }
+}
+
+impl BiDimensionalRenderer for VelloRenderer {
+    fn resize(&mut self, _: &Window, width: u32, height: u32) {
+        self.resize_surface(width, height);
+    }
+
+    ///Rasterizes every command made since the last flush into the pixel buffer and presents it, if there's a window.
+    ///Clips and transforms that weren't restored are discarded. As the whole buffer is rasterized again, all of it is presented
+    fn flush(&mut self) {
+        for _ in 0..self.layers {
+            self.context.pop_layer();
+        }
+        self.layers = 0;
+        self.saved.clear();
+        self.context.flush();
+        self.context.render_to_pixmap(&mut self.pixmap);
+        self.context.reset();
+        if let Some(ref mut presenter) = self.presenter {
+            presenter.present(self.pixmap.data_as_u8_slice(), &Damage::full());
+        }
+    }
+
+    fn painter(&mut self) -> &mut dyn BiDimensionalPainter {
+        self
+    }
+}
+
+impl BiDimensionalPainter for VelloRenderer {
+    fn square(&mut self, square_info: &CandySquare) {
+        let rule = square_info.rule();
+        let (position, size) = (*square_info.position(), *square_info.size());
+        let radii = rule.corner_radii();
+        let shape = rounded_rect(&position, &size, &radii);
+        self.outer_effects(rule, &shape);
+
+        let blur = rule
+            .effects()
+            .blur
+            .map(|blur| blur.x.abs().max(blur.y.abs()));
+        if let Some(radius) = blur {
+            self.context
+                .push_filter_layer(Filter::from_function(FilterFunction::Blur { radius }));
+        }
+        self.set_fill_paint(rule, &shape.bounding_box());
+        self.context.fill_path(&shape);
+        self.context.reset_paint_transform();
+        self.inner_shadows(rule, &shape, &position, &size);
+        self.borders(rule, &position, &size, &radii);
+        if blur.is_some() {
+            self.context.pop_layer();
+        }
+    }
+
+    fn circle(&mut self, position: &Vector2<f32>, color: &Vector4<f32>, radius: f32) {
+        let circle = Circle::new((position.x as f64, position.y as f64), radius as f64);
+        self.set_solid_paint(color);
+        self.context.fill_path(&circle.to_path(0.1));
+    }
+
+    #[inline]
+    fn text_sliced(&mut self, info: &CandyText, range: Range<usize>) {
+        self.draw_text(info, &[(&info.content()[range], *info.position())]);
+    }
+
+    #[inline]
+    fn text(&mut self, info: &CandyText) {
+        self.draw_text(info, &info.runs());
+    }
+
+    fn render_image(&mut self, image: &CandyImage) {
+        let Some(pixmap) = cached_pixmap(&mut self.images, image.image_handler()) else {
+            return;
+        };
+
+        let rule = image.rule();
+        let pixmap = match rule.color_filter() {
+            Some(filter) => filter_pixmap(&pixmap, filter),
+            None => pixmap,
+        };
+        let position = *image.position();
+        let size = *image.size();
+        //the patches are clipped by the rounded rect of the image the same way the skia painters do
+        let path = rounded_rect(&position, &size, &rule.corner_radii());
+        self.outer_effects(rule, &path);
+        self.context.push_clip_layer(&path);
+        self.context
+            .set_blend_mode(to_blend_mode(rule.blend_mode()));
+        self.context.set_paint(PaintType::Image(Image {
+            image: ImageSource::Pixmap(pixmap),
+            sampler: ImageSampler::default()
+                .with_quality(image_quality(image.sampling()))
+                .with_alpha(rule.get_color().w),
+        }));
+        for (from, to) in image.patches() {
+            let (sx, sy) = (
+                (to.width / from.width) as f64,
+                (to.height / from.height) as f64,
+            );
+            self.context.set_paint_transform(Affine::new([
+                sx,
+                0.0,
+                0.0,
+                sy,
+                to.x as f64 - from.x as f64 * sx,
+                to.y as f64 - from.y as f64 * sy,
+            ]));
+            self.context.fill_rect(&Rect::new(
+                to.x as f64,
+                to.y as f64,
+                to.right() as f64,
+                to.bottom() as f64,
+            ));
+        }
+        self.context.reset_paint_transform();
+        self.context.pop_layer();
+    }
+
+    fn sprites(&mut self, sheet: &CandySpriteSheet) {
+        let Some(pixmap) = cached_pixmap(&mut self.images, sheet.atlas().image_handler()) else {
+            return;
+        };
+
+        let rule = &sheet.rule;
+        let transform = *self.context.transform();
+        self.context
+            .set_blend_mode(to_blend_mode(rule.blend_mode()));
+        //vello can't multiply the sprites by a color, so only the alpha of their tints is used
+        for (sprite, region) in sheet.visible() {
+            let (scos, ssin, translation) = sprite.transform(region, sheet.position());
+            self.context.set_transform(
+                transform
+                    * Affine::new([
+                        scos as f64,
+                        ssin as f64,
+                        -ssin as f64,
+                        scos as f64,
+                        translation.x as f64,
+                        translation.y as f64,
+                    ]),
+            );
+            self.context.set_paint(PaintType::Image(Image {
+                image: ImageSource::Pixmap(pixmap.clone()),
+                sampler: ImageSampler::default()
+                    .with_quality(image_quality(sheet.sampling()))
+                    .with_alpha(rule.get_color().w * sprite.tint.map_or(1.0, |tint| tint.w)),
+            }));
+            self.context
+                .set_paint_transform(Affine::translate((-region.x as f64, -region.y as f64)));
+            self.context.fill_rect(&Rect::new(
+                0.0,
+                0.0,
+                region.width as f64,
+                region.height as f64,
+            ));
+        }
+        self.context.reset_paint_transform();
+        self.context.set_transform(transform);
+    }
+
+    fn path(&mut self, path: &CandyPath) {
+        let rule = &path.rule;
+        let converted = convert_candy_path(path);
+        self.context.set_fill_rule(match path.fill_rule {
+            FillRule::NonZero => Fill::NonZero,
+            FillRule::EvenOdd => Fill::EvenOdd,
+        });
+        //gradients follow the shape of the path, not the area it was given
+        self.set_fill_paint(rule, &converted.bounding_box());
+        self.context.fill_path(&converted);
+        self.context.reset_paint_transform();
+        self.context.set_fill_rule(Fill::NonZero);
+
+        if rule.border_color.w == 0.0 || rule.border_width == 0.0 {
+            return;
+        }
+        let style = &path.stroke;
+        let mut stroke = Stroke::new(rule.border_width as f64)
+            .with_miter_limit(style.miter_limit as f64)
+            .with_caps(match style.cap {
+                StrokeCap::Butt => Cap::Butt,
+                StrokeCap::Round => Cap::Round,
+                StrokeCap::Square => Cap::Square,
+            })
+            .with_join(match style.join {
+                StrokeJoin::Miter => Join::Miter,
+                StrokeJoin::Round => Join::Round,
+                StrokeJoin::Bevel => Join::Bevel,
+            });
+        if style.is_dashed() {
+            stroke = stroke.with_dashes(
+                style.dash_offset as f64,
+                style.dash_pattern().into_iter().map(|len| len as f64),
+            );
+        }
+        self.context.set_stroke(stroke);
+        self.set_rule_paint(rule, &rule.border_color);
+        self.context.stroke_path(&converted);
+    }
+
+    ///Discards everything drawn since the last flush and fills the whole buffer with the given `color`. If there's any state saved,
+    ///only what's inside of the current clip is filled, as the layers of it can't be discarded
+    fn background(&mut self, color: &Vector4<f32>) {
+        let rect = Rect::new(
+            0.0,
+            0.0,
+            self.pixmap.width() as f64,
+            self.pixmap.height() as f64,
+        );
+        if self.layers == 0 && self.saved.is_empty() {
+            self.context.reset();
+            self.set_solid_paint(color);
+            self.context.fill_rect(&rect);
+            return;
+        }
+        let transform = *self.context.transform();
+        self.context.reset_transform();
+        self.set_solid_paint(color);
+        self.context.fill_rect(&rect);
+        self.context.set_transform(transform);
+    }
+
+    fn save(&mut self) {
+        self.saved.push((*self.context.transform(), self.layers));
+    }
+
+    fn save_layer(
+        &mut self,
+        alpha: f32,
+        blend_mode: BlendMode,
+        bounds: Option<&candy_shared_types::Rect>,
+    ) {
+        self.save();
+        let clip = bounds.map(|rect| {
+            Rect::new(
+                rect.x as f64,
+                rect.y as f64,
+                (rect.x + rect.width) as f64,
+                (rect.y + rect.height) as f64,
+            )
+            .to_path(0.1)
+        });
+        self.context.push_layer(
+            clip.as_ref(),
+            Some(to_blend_mode(blend_mode)),
+            Some(alpha.clamp(0.0, 1.0)),
+            None,
+            None,
+        );
+        self.layers += 1;
+    }
+
+    fn restore(&mut self) {
+        let Some((transform, layers)) = self.saved.pop() else {
+            return;
+        };
+        while self.layers > layers {
+            self.context.pop_layer();
+            self.layers -= 1;
+        }
+        self.context.set_transform(transform);
+    }
+
+    #[inline]
+    fn clip_rounded_rect(&mut self, rect: &candy_shared_types::Rect, radius: &Vector2<f32>) {
+        self.clip_rounded_rect_radii(rect, &CornerRadii::uniform(*radius));
+    }
+
+    fn clip_rounded_rect_radii(&mut self, rect: &candy_shared_types::Rect, radii: &CornerRadii) {
+        let path = rounded_rect(
+            &Vector2::new(rect.x, rect.y),
+            &Vector2::new(rect.width, rect.height),
+            radii,
+        );
+        self.context.push_clip_layer(&path);
+        self.layers += 1;
+    }
+
+    fn concat(&mut self, matrix: &Matrix3<f32>) {
+        let matrix = Affine::new([
+            matrix[(0, 0)] as f64,
+            matrix[(1, 0)] as f64,
+            matrix[(0, 1)] as f64,
+            matrix[(1, 1)] as f64,
+            matrix[(0, 2)] as f64,
+            matrix[(1, 2)] as f64,
+        ]);
+        let transform = *self.context.transform() * matrix;
+        self.context.set_transform(transform);
+    }
+}
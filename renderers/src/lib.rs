@@ -3,10 +3,25 @@ pub mod primitives;
 mod threedimensional;
 
 pub use bidimensional::*;
+#[cfg(feature = "opengl")]
 use glutin::config::Config;
 pub use threedimensional::*;
 use winit::window::Window;
 
+///The 2D renderer used by default. When opengl is avaible it draws on the window using the GPU, otherwise everything is drawn on the CPU
+#[cfg(feature = "opengl")]
+pub type Default2DRenderer = Candy2DefaultRenderer;
+///The 2D renderer used by default. When opengl is avaible it draws on the window using the GPU, otherwise everything is drawn on the CPU
+#[cfg(all(not(feature = "opengl"), feature = "tiny-skia"))]
+pub type Default2DRenderer = TinySkiaRenderer;
+///The 2D renderer used by default. When opengl is avaible it draws on the window using the GPU, otherwise everything is drawn on the CPU
+#[cfg(all(not(feature = "opengl"), not(feature = "tiny-skia"), feature = "vello"))]
+pub type Default2DRenderer = VelloRenderer;
+///The 2D renderer used by default. When opengl is avaible it draws on the window using the GPU, otherwise everything is drawn on the CPU.
+///Without opengl, tiny-skia or vello, it can't present to a window, so it's only used headless
+#[cfg(not(any(feature = "opengl", feature = "tiny-skia", feature = "vello")))]
+pub type Default2DRenderer = Candy2DRasterRenderer;
+
 ///Trait used to define renderers for Candy. It uses 2 renderers inside to draw 2D and 3D and this is used mainly for requesting commands from them
 pub trait CandyRenderer {
     type TwoD: BiDimensionalRenderer + BiDimensionalRendererConstructor;
@@ -15,7 +30,12 @@ pub trait CandyRenderer {
     #[cfg(feature = "opengl")]
     fn new(window: &Window, config: &Config) -> Self;
 
-    #[cfg(feature = "opengl")]
+    ///Creates a renderer that presents to `window` without opengl, such as the ones that draw on the CPU. `None` if its 2D renderer
+    ///can't present to a window by itself
+    fn from_window(window: &Window) -> Option<Self>
+    where
+        Self: Sized;
+
     ///Method called when this renderer is resized. The `width` and `height` are the new dimensions that were given
     fn resize(&mut self, window: &Window, width: u32, height: u32);
 
@@ -28,7 +48,7 @@ pub trait CandyRenderer {
 
 #[derive(Debug)]
 ///The default renderer of a candy, used to render both 2D and 3D
-pub struct CandyDefaultRenderer<TwoD = Candy2DefaultRenderer, ThreeD = Candy3DefaultRenderer> {
+pub struct CandyDefaultRenderer<TwoD = Default2DRenderer, ThreeD = Candy3DefaultRenderer> {
     twod: TwoD,
     threed: ThreeD,
 }
@@ -47,7 +67,12 @@ where
             threed: ThreeD::new(window),
         }
     }
-    #[cfg(feature = "opengl")]
+    fn from_window(window: &Window) -> Option<Self> {
+        Some(Self {
+            twod: TwoD::from_window(window)?,
+            threed: ThreeD::new(window),
+        })
+    }
     fn resize(&mut self, window: &Window, width: u32, height: u32) {
         self.twod.resize(window, width, height);
     }
@@ -60,3 +85,17 @@ where
         &mut self.twod
     }
 }
+
+impl<TwoD, ThreeD> CandyDefaultRenderer<TwoD, ThreeD>
+where
+    TwoD: HeadlessRendererConstructor,
+    ThreeD: Default,
+{
+    ///Creates a new renderer that doesn't draw on any window. The 2D renderer will have a surface of the given `width` and `height` in pixels
+    pub fn new_headless(width: u32, height: u32) -> Self {
+        Self {
+            twod: TwoD::new_headless(width, height),
+            threed: ThreeD::default(),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use candy_shared_types::RendererImage;
+use skia_safe::{
+    AlphaType, Codec, ColorType, Data, ImageInfo,
+    codec::{self, Options, ZeroInitialized},
+};
+
+use super::TwodCandyImg;
+
+///Frames shorter than this are shown for [DEFAULT_FRAME_DURATION] instead, the same way browsers do, as many files have got a duration
+///of 0 and expect it
+const MIN_FRAME_DURATION: Duration = Duration::from_millis(10);
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+///A single frame of an [AnimatedImage], already composited over the frames it depends on
+pub struct ImageFrame {
+    pub image: RendererImage,
+    ///For how long this frame is shown
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone)]
+///An image with many frames, such as an animated gif, webp or apng. Every frame is decoded when it's created, following the disposal
+///and blending of each one, so playing it is only picking the frame to draw
+pub struct AnimatedImage {
+    frames: Vec<ImageFrame>,
+    ///How many times the animation is repeated after playing once. `None` if it loops forever
+    repetitions: Option<usize>,
+}
+
+impl AnimatedImage {
+    #[inline]
+    ///Reads and decodes every frame of the image on the file at `path`
+    pub fn from_source<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    ///Decodes every frame of the image encoded on `bytes`. Images with a single frame are valid as well, and just don't play
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+        let mut codec = Codec::from_data(Data::new_copy(bytes))
+            .ok_or_else(|| invalid("the image could not be decoded"))?;
+        let dimensions = codec.dimensions();
+        let info = ImageInfo::new(dimensions, ColorType::RGBA8888, AlphaType::Premul, None);
+        let row_bytes = info.min_row_bytes();
+        let (width, height) = (dimensions.width as u32, dimensions.height as u32);
+        let count = codec.get_frame_count().max(1);
+
+        //the pixels of every frame are kept until the end, as frames are drawn over the ones they require
+        let mut decoded: Vec<Vec<u8>> = Vec::with_capacity(count);
+        let mut frames = Vec::with_capacity(count);
+        for index in 0..count {
+            let frame = codec.get_frame_info(index).unwrap_or_default();
+            let prior = usize::try_from(frame.required_frame)
+                .ok()
+                .filter(|prior| *prior < decoded.len());
+            let mut pixels = match prior {
+                Some(prior) => decoded[prior].clone(),
+                None => vec![0; info.compute_byte_size(row_bytes)],
+            };
+            let options = Options {
+                zero_initialized: if prior.is_some() {
+                    ZeroInitialized::No
+                } else {
+                    ZeroInitialized::Yes
+                },
+                subset: None,
+                frame_index: index,
+                prior_frame: prior,
+            };
+            match codec.get_pixels_with_options(&info, &mut pixels, row_bytes, Some(&options)) {
+                codec::Result::Success | codec::Result::IncompleteInput => {}
+                error => return Err(invalid(codec::result_to_string(error))),
+            }
+            let image = RendererImage::from_rgba(width, height, &pixels)
+                .ok_or_else(|| invalid("the frame could not be created"))?;
+            let duration = Duration::from_millis(frame.duration.max(0) as u64);
+            frames.push(ImageFrame {
+                image,
+                duration: if duration <= MIN_FRAME_DURATION {
+                    DEFAULT_FRAME_DURATION
+                } else {
+                    duration
+                },
+            });
+            decoded.push(pixels);
+        }
+        Ok(Self {
+            frames,
+            repetitions: codec.get_repetition_count(),
+        })
+    }
+
+    #[inline]
+    pub fn frames(&self) -> &[ImageFrame] {
+        &self.frames
+    }
+
+    #[inline]
+    ///Retrieves the frame at `index`
+    pub fn frame(&self, index: usize) -> Option<&ImageFrame> {
+        self.frames.get(index)
+    }
+
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[inline]
+    ///Checks if there's more than a single frame to be played
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    #[inline]
+    ///Retrieves how many times the animation is repeated after playing once. `None` if it loops forever
+    pub fn repetitions(&self) -> Option<usize> {
+        self.repetitions
+    }
+
+    #[inline]
+    ///Sets how many times the animation is repeated after playing once. `None` loops it forever
+    pub fn set_repetitions(&mut self, repetitions: Option<usize>) -> &mut Self {
+        self.repetitions = repetitions;
+        self
+    }
+
+    #[inline]
+    ///Retrieves how long playing the animation once takes
+    pub fn loop_duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.duration).sum()
+    }
+
+    ///Retrieves the index of the frame shown when the animation was playing for `elapsed`, and for how long it's still shown. The
+    ///duration is `None` when the animation is over, so it stays on that frame
+    pub fn frame_at(&self, elapsed: Duration) -> (usize, Option<Duration>) {
+        let total = self.loop_duration();
+        if !self.is_animated() || total.is_zero() {
+            return (0, None);
+        }
+        let over = self.repetitions.is_some_and(|repetitions| {
+            total
+                .checked_mul((repetitions as u32).saturating_add(1))
+                .is_some_and(|end| elapsed >= end)
+        });
+        if over {
+            return (self.frames.len() - 1, None);
+        }
+        let mut time = Duration::from_nanos((elapsed.as_nanos() % total.as_nanos()) as u64);
+        for (index, frame) in self.frames.iter().enumerate() {
+            if time < frame.duration {
+                return (index, Some(frame.duration - time));
+            }
+            time -= frame.duration;
+        }
+        (self.frames.len() - 1, None)
+    }
+
+    ///Retrieves when the frame at `index` starts being shown, on the first time the animation is played
+    pub fn frame_start(&self, index: usize) -> Duration {
+        self.frames
+            .iter()
+            .take(index)
+            .map(|frame| frame.duration)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(durations: &[u64], repetitions: Option<usize>) -> AnimatedImage {
+        let frames = durations
+            .iter()
+            .map(|duration| ImageFrame {
+                image: RendererImage::new(1, 1, vec![0; 4]).unwrap(),
+                duration: Duration::from_millis(*duration),
+            })
+            .collect();
+        AnimatedImage {
+            frames,
+            repetitions,
+        }
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn frame_at_picks_the_frame_and_its_remaining_time() {
+        let animation = animation(&[100, 50, 200], None);
+        assert_eq!(animation.frame_at(ms(0)), (0, Some(ms(100))));
+        assert_eq!(animation.frame_at(ms(120)), (1, Some(ms(30))));
+        assert_eq!(animation.frame_at(ms(160)), (2, Some(ms(190))));
+        assert_eq!(animation.frame_start(2), ms(150));
+    }
+
+    #[test]
+    fn frame_at_loops_forever_without_repetitions() {
+        let animation = animation(&[100, 50, 200], None);
+        assert_eq!(animation.frame_at(ms(350 * 20 + 120)), (1, Some(ms(30))));
+    }
+
+    #[test]
+    fn frame_at_stays_on_the_last_frame_after_the_repetitions() {
+        let animation = animation(&[100, 50, 200], Some(1));
+        assert_eq!(animation.frame_at(ms(400)), (0, Some(ms(50))));
+        assert_eq!(animation.frame_at(ms(699)), (2, Some(ms(1))));
+        assert_eq!(animation.frame_at(ms(700)), (2, None));
+        assert_eq!(animation.frame_at(ms(10_000)), (2, None));
+    }
+
+    #[test]
+    fn single_frames_are_not_played() {
+        assert_eq!(animation(&[100], None).frame_at(ms(150)), (0, None));
+    }
+}
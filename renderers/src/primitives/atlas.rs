@@ -0,0 +1,370 @@
+use std::{collections::HashMap, sync::Arc};
+
+use candy_shared_types::{Rect, RendererImage};
+use nalgebra::{Vector2, Vector4};
+use skia_safe::Image;
+
+use super::{DrawRule, ImageSampling, TwodCandyImg, skia_image_of};
+
+///Transparent pixels left between the images packed on an atlas, so the ones around don't bleed into each other when they're filtered
+const PACKING_GAP: u32 = 1;
+
+#[inline]
+///Retrieves the width and height of `image`, in pixels
+fn size_of(image: &RendererImage) -> (u32, u32) {
+    (TwodCandyImg::width(image), TwodCandyImg::height(image))
+}
+
+#[derive(Debug, Clone)]
+///Many small images, such as icons, packed into a single one, so they can be drawn together by a [CandySpriteSheet] instead of one by one.
+///Each image is a region of the atlas, retrieved by its index or by its name
+pub struct TextureAtlas {
+    image: RendererImage,
+    regions: Vec<Rect>,
+    names: HashMap<String, usize>,
+}
+
+impl TextureAtlas {
+    ///Creates an atlas of `image` without any region yet
+    pub fn new(image: RendererImage) -> Self {
+        Self {
+            image,
+            regions: Vec::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    ///Creates an atlas of a sprite sheet, whose regions are the tiles of `image` with the given `tile` size, row by row
+    pub fn from_grid(image: RendererImage, tile: Vector2<f32>) -> Self {
+        let mut out = Self::new(image);
+        if tile.x <= 0.0 || tile.y <= 0.0 {
+            return out;
+        }
+        let (width, height) = size_of(&out.image);
+        let columns = (width as f32 / tile.x).floor() as usize;
+        let rows = (height as f32 / tile.y).floor() as usize;
+        for row in 0..rows {
+            for column in 0..columns {
+                out.regions.push(Rect::new(
+                    column as f32 * tile.x,
+                    row as f32 * tile.y,
+                    tile.x,
+                    tile.y,
+                ));
+            }
+        }
+        out
+    }
+
+    ///Packs the given `images` into a single one, at most `max_width` pixels wide, placing them on shelves from the tallest to the
+    ///shortest. Each one becomes a region with its name, in the order they're given. `None` if an image is wider than `max_width` or if
+    ///their pixels couldn't be read
+    pub fn pack<S: Into<String>>(
+        images: impl IntoIterator<Item = (S, RendererImage)>,
+        max_width: u32,
+    ) -> Option<Self> {
+        let images: Vec<(String, RendererImage)> = images
+            .into_iter()
+            .map(|(name, image)| (name.into(), image))
+            .collect();
+        let mut order: Vec<usize> = (0..images.len()).collect();
+        order.sort_by_key(|index| std::cmp::Reverse(size_of(&images[*index].1).1));
+
+        //the place of each image, by the order they're given
+        let mut places = vec![(0, 0); images.len()];
+        let (mut x, mut y, mut shelf, mut width) = (0, 0, 0, 0);
+        for index in order {
+            let (w, h) = size_of(&images[index].1);
+            if w > max_width {
+                return None;
+            }
+            if x + w > max_width {
+                x = 0;
+                y += shelf + PACKING_GAP;
+                shelf = 0;
+            }
+            places[index] = (x, y);
+            x += w + PACKING_GAP;
+            shelf = shelf.max(h);
+            width = width.max(x - PACKING_GAP);
+        }
+        let (width, height) = (width.max(1), (y + shelf).max(1));
+
+        let stride = width as usize * 4;
+        let mut pixels = vec![0; stride * height as usize];
+        for ((_, image), (x, y)) in images.iter().zip(&places) {
+            let source = image.to_rgba()?;
+            let (w, h) = size_of(image);
+            let row = w as usize * 4;
+            for line in 0..h as usize {
+                let start = (*y as usize + line) * stride + *x as usize * 4;
+                pixels[start..start + row].copy_from_slice(&source[line * row..(line + 1) * row]);
+            }
+        }
+
+        let mut out = Self::new(RendererImage::from_rgba(width, height, &pixels)?);
+        for ((name, image), (x, y)) in images.into_iter().zip(places) {
+            let (w, h) = size_of(&image);
+            out.add_region(
+                Some(name),
+                Rect::new(x as f32, y as f32, w as f32, h as f32),
+            );
+        }
+        Some(out)
+    }
+
+    ///Adds a region of the atlas with the given `rect`, in its pixels, and `name`, if any. Returns its index
+    pub fn add_region(&mut self, name: Option<String>, rect: Rect) -> usize {
+        let index = self.regions.len();
+        self.regions.push(rect);
+        if let Some(name) = name {
+            self.names.insert(name, index);
+        }
+        index
+    }
+
+    #[inline]
+    ///Retrieves the region at `index`, in pixels of the atlas
+    pub fn region(&self, index: usize) -> Option<&Rect> {
+        self.regions.get(index)
+    }
+
+    #[inline]
+    pub fn regions(&self) -> &[Rect] {
+        &self.regions
+    }
+
+    #[inline]
+    ///Retrieves the index of the region with the given `name`
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    #[inline]
+    ///Returns the image every region is read from
+    pub fn image_handler(&self) -> &RendererImage {
+        &self.image
+    }
+
+    #[inline]
+    pub fn to_skia_image(&self) -> Option<Image> {
+        skia_image_of(&self.image)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A region of a [TextureAtlas] drawn by a [CandySpriteSheet]
+pub struct Sprite {
+    ///The index of the region on the atlas
+    pub region: usize,
+    ///Where the top left corner of the sprite is drawn, relative to the position of the sheet, before it's rotated
+    pub position: Vector2<f32>,
+    pub scale: f32,
+    ///Rotation in degrees clockwise, around the center of the sprite
+    pub rotation: f32,
+    ///Color the pixels of the sprite are multiplied with, such as to paint monochrome icons. Painters that can't do it only use its alpha
+    pub tint: Option<Vector4<f32>>,
+}
+
+impl Sprite {
+    pub fn new(region: usize, position: Vector2<f32>) -> Self {
+        Self {
+            region,
+            position,
+            scale: 1.0,
+            rotation: 0.0,
+            tint: None,
+        }
+    }
+
+    #[inline]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    #[inline]
+    pub fn with_rotation(mut self, degrees: f32) -> Self {
+        self.rotation = degrees;
+        self
+    }
+
+    #[inline]
+    pub fn with_tint(mut self, tint: Vector4<f32>) -> Self {
+        self.tint = Some(tint);
+        self
+    }
+
+    ///Retrieves the transform that moves the pixels of `region` to where this sprite is drawn, offset by `origin`, as the scaled cosine,
+    ///the scaled sine and the translation, the same way as a skia `RSXform`. The region's top left corner is its origin
+    pub fn transform(&self, region: &Rect, origin: &Vector2<f32>) -> (f32, f32, Vector2<f32>) {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let (scos, ssin) = (cos * self.scale, sin * self.scale);
+        let half = Vector2::new(region.width, region.height) / 2.0;
+        let center = origin + self.position + half * self.scale;
+        let translation = Vector2::new(
+            center.x - (scos * half.x - ssin * half.y),
+            center.y - (ssin * half.x + scos * half.y),
+        );
+        (scos, ssin, translation)
+    }
+}
+
+#[derive(Debug)]
+///Many sprites of the same [TextureAtlas] that are drawn together, in a single batch when the painter can. The color of the rule is
+///multiplied with every sprite
+pub struct CandySpriteSheet {
+    atlas: Arc<TextureAtlas>,
+    position: Vector2<f32>,
+    sprites: Vec<Sprite>,
+    sampling: ImageSampling,
+    pub rule: DrawRule,
+}
+
+impl CandySpriteSheet {
+    ///Creates a sheet drawing the sprites of `atlas` relative to `position`, without any sprite yet
+    pub fn new(atlas: Arc<TextureAtlas>, position: Vector2<f32>) -> Self {
+        Self {
+            atlas,
+            position,
+            sprites: Vec::new(),
+            sampling: ImageSampling::default(),
+            rule: DrawRule::new(),
+        }
+    }
+
+    #[inline]
+    pub fn atlas(&self) -> &Arc<TextureAtlas> {
+        &self.atlas
+    }
+
+    #[inline]
+    pub fn position(&self) -> &Vector2<f32> {
+        &self.position
+    }
+
+    #[inline]
+    pub fn position_mut(&mut self) -> &mut Vector2<f32> {
+        &mut self.position
+    }
+
+    #[inline]
+    pub fn sprites(&self) -> &[Sprite] {
+        &self.sprites
+    }
+
+    #[inline]
+    pub fn sprites_mut(&mut self) -> &mut Vec<Sprite> {
+        &mut self.sprites
+    }
+
+    #[inline]
+    ///Adds `sprite` to be drawn after the ones added before
+    pub fn push(&mut self, sprite: Sprite) -> &mut Self {
+        self.sprites.push(sprite);
+        self
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    #[inline]
+    ///Retrieves how the pixels of the atlas are read when the sprites are scaled or rotated
+    pub fn sampling(&self) -> ImageSampling {
+        self.sampling
+    }
+
+    #[inline]
+    ///Sets how the pixels of the atlas are read when the sprites are scaled or rotated
+    pub fn set_sampling(&mut self, sampling: ImageSampling) -> &mut Self {
+        self.sampling = sampling;
+        self
+    }
+
+    ///Iterates over the sprites whose region exists, with it
+    pub fn visible(&self) -> impl Iterator<Item = (&Sprite, &Rect)> {
+        self.sprites.iter().filter_map(|sprite| {
+            self.atlas
+                .region(sprite.region)
+                .map(|region| (sprite, region))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(width: u32, height: u32, value: u8) -> RendererImage {
+        RendererImage::new(width, height, vec![value; (width * height * 4) as usize]).unwrap()
+    }
+
+    #[test]
+    fn pack_places_images_on_shelves_from_the_tallest() {
+        let images = [
+            ("short", filled(3, 1, 3)),
+            ("tall", filled(2, 3, 1)),
+            ("wide", filled(4, 2, 2)),
+        ];
+        let atlas = TextureAtlas::pack(images, 7).unwrap();
+        assert_eq!(atlas.len(), 3);
+        assert_eq!(atlas.index_of("short"), Some(0));
+        assert_eq!(atlas.region(1), Some(&Rect::new(0.0, 0.0, 2.0, 3.0)));
+        assert_eq!(atlas.region(2), Some(&Rect::new(3.0, 0.0, 4.0, 2.0)));
+        //the shortest doesn't fit on the first shelf, so it goes below it, after the gap
+        assert_eq!(atlas.region(0), Some(&Rect::new(0.0, 4.0, 3.0, 1.0)));
+
+        let image = atlas.image_handler();
+        assert_eq!((image.width(), image.height()), (7, 5));
+        let pixel = |x: usize, y: usize| image.pixels()[(y * 7 + x) * 4];
+        assert_eq!(pixel(1, 2), 1);
+        assert_eq!(pixel(2, 0), 0);
+        assert_eq!(pixel(6, 1), 2);
+        assert_eq!(pixel(2, 4), 3);
+    }
+
+    #[test]
+    fn pack_fails_on_images_wider_than_the_atlas() {
+        assert!(TextureAtlas::pack([("wide", filled(8, 1, 0))], 7).is_none());
+    }
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!((value - expected).abs() < 1e-4, "{value} != {expected}");
+    }
+
+    #[test]
+    fn transform_scales_from_the_top_left_corner() {
+        let sprite = Sprite::new(0, Vector2::new(1.0, 2.0)).with_scale(2.0);
+        let region = Rect::new(0.0, 0.0, 10.0, 20.0);
+        let (scos, ssin, translation) = sprite.transform(&region, &Vector2::new(5.0, 5.0));
+        assert_near(scos, 2.0);
+        assert_near(ssin, 0.0);
+        assert_near(translation.x, 6.0);
+        assert_near(translation.y, 7.0);
+    }
+
+    #[test]
+    fn transform_rotates_around_the_center() {
+        let sprite = Sprite::new(0, Vector2::zeros()).with_rotation(90.0);
+        let region = Rect::new(0.0, 0.0, 10.0, 20.0);
+        let (scos, ssin, translation) = sprite.transform(&region, &Vector2::zeros());
+        assert_near(scos, 0.0);
+        assert_near(ssin, 1.0);
+        //the center, at (5, 10), stays where it is
+        assert_near(translation.x, 15.0);
+        assert_near(translation.y, 5.0);
+    }
+}
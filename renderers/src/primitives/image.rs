@@ -1,23 +1,123 @@
+use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
 
-use candy_shared_types::RendererImage;
+use candy_shared_types::{Rect, RendererImage};
 use nalgebra::Vector2;
-use skia_safe::{Data, Image};
+use skia_safe::{AlphaType, CachingHint, ColorType, Data, ISize, Image, ImageInfo, Surface};
 
-use super::CandySquare;
+use super::{CandySquare, DEFAULT_IMAGE_CACHE_BUDGET, ImageCache, LruCache};
 
 /// A handler for Images on Candy. This is now shown due to rust limitations with dyn, but this is dependent of CandyImgConstructor
 pub trait TwodCandyImg: Sized + std::fmt::Debug {
-    fn from_source<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self>;
+    ///Decodes the image encoded on `bytes`. Data that can't be decoded is an error of kind [std::io::ErrorKind::InvalidData]
+    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self>;
+
+    #[inline]
+    ///Reads and decodes the image on the file at `path`
+    fn from_source<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+    ///Creates an image with the given size from its premultiplied RGBA `pixels`, row by row. `None` if there aren't `width * height`
+    ///pixels
+    fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Option<Self>;
+    ///Copies the pixels of this image as premultiplied RGBA, row by row
+    fn to_rgba(&self) -> Option<Vec<u8>>;
+    ///Updates the region at (`x`, `y`) with the given `width` and `height` from `pixels`, premultiplied RGBA with the same size of this
+    ///image, so only that region is copied again by the renderers. Returns whether it could be done
+    fn update_rgba(&mut self, pixels: &[u8], x: u32, y: u32, width: u32, height: u32) -> bool;
     fn width(&self) -> u32;
     fn height(&self) -> u32;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How an image is resized to the square it's drawn in, the same way as the css `object-fit`
+pub enum ImageFit {
+    ///Stretched to fill the whole square, ignoring its aspect ratio
+    #[default]
+    Fill,
+    ///Scaled, keeping its aspect ratio, to be as big as it can while still fitting in the square
+    Contain,
+    ///Scaled, keeping its aspect ratio, to cover the whole square. What goes out of it is clipped
+    Cover,
+    ///Drawn with its own size. What goes out of the square is clipped
+    None,
+    ///The same as `None` or `Contain`, whichever makes the image smaller
+    ScaleDown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the pixels of an image are read when it's drawn with another size
+pub enum ImageSampling {
+    ///The nearest pixel, which keeps pixel art sharp
+    Nearest,
+    ///The 4 nearest pixels, linearly interpolated
+    #[default]
+    Linear,
+    ///Linear, but read from smaller copies of the image when it's scaled down, so it doesn't look noisy
+    Mipmap,
+    ///The 16 nearest pixels, smoother than linear when scaled up. Whatever can't do it uses linear
+    Cubic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///The distances, in pixels of the image, from each side of it that split it in 9 parts to be scaled like a nine-patch. The corners keep
+///their size, the edges are stretched along their side and the center is stretched on both axes
+pub struct NineSlice {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl NineSlice {
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    #[inline]
+    ///Creates a slice with the same distance from every side
+    pub fn uniform(inset: f32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+///Splits the segment from `start` with the given `size` in 3 parts, where the first one has got the length `head` and the last one
+///`tail`. They're scaled down when they don't fit together
+fn split_segment(start: f32, size: f32, head: f32, tail: f32) -> [f32; 4] {
+    let (head, tail) = (head.max(0.0), tail.max(0.0));
+    let scale = if head + tail > size && head + tail > 0.0 {
+        size.max(0.0) / (head + tail)
+    } else {
+        1.0
+    };
+    [
+        start,
+        start + head * scale,
+        start + size - tail * scale,
+        start + size,
+    ]
+}
+
 #[derive(Debug)]
 ///An Image used to be drawn on by the painter P
 pub struct CandyImage {
     inner: RendererImage,
     square: CandySquare,
+    fit: ImageFit,
+    ///Where the image is placed in the square when it doesn't fill it, from (0,0), the top left corner, to (1,1), the bottom right one
+    alignment: Vector2<f32>,
+    ///The part of the image that is drawn, in its pixels. `None` if it's the whole image
+    source: Option<Rect>,
+    nine_slice: Option<NineSlice>,
+    sampling: ImageSampling,
 }
 impl CandyImage {
     ///Tries to create a new image from `source`. If no `square` is given, the it will use the normal
@@ -27,13 +127,33 @@ impl CandyImage {
         square: Option<CandySquare>,
     ) -> std::io::Result<Self> {
         let inner = RendererImage::from_source(source)?;
-        Ok(Self {
-            square: square.unwrap_or(CandySquare::new(
-                Vector2::zeros(),
-                Vector2::new(inner.width() as f32, inner.height() as f32),
-            )),
-            inner,
-        })
+        Ok(Self::with_default_square(inner, square))
+    }
+
+    ///Tries to create a new image from the encoded `bytes`, such as the contents of a png file or a download. If no `square` is given,
+    ///it will use the normal resolution of the image positioned at (0,0)
+    pub fn from_bytes(bytes: &[u8], square: Option<CandySquare>) -> std::io::Result<Self> {
+        let inner = RendererImage::from_bytes(bytes)?;
+        Ok(Self::with_default_square(inner, square))
+    }
+
+    ///Loads the image at `path` from the given `cache`, decoding it only if it isn't there. If no `square` is given, it will use the
+    ///normal resolution of the image positioned at (0,0)
+    pub fn from_cache<Ph: AsRef<std::path::Path>>(
+        cache: &mut ImageCache,
+        path: Ph,
+        square: Option<CandySquare>,
+    ) -> std::io::Result<Self> {
+        let inner = cache.load(path)?;
+        Ok(Self::with_default_square(inner, square))
+    }
+
+    fn with_default_square(inner: RendererImage, square: Option<CandySquare>) -> Self {
+        let square = square.unwrap_or(CandySquare::new(
+            Vector2::zeros(),
+            Vector2::new(inner.width() as f32, inner.height() as f32),
+        ));
+        Self::new(inner, square)
     }
 }
 impl CandyImage {
@@ -41,7 +161,15 @@ impl CandyImage {
     ///Creates a new CandyImage with the given `img` and defining it's square info to be the given `square`
     ///Note: The color of the `square` will be used to multiply the colors of the texture when drawn
     pub fn new(img: RendererImage, square: CandySquare) -> Self {
-        Self { inner: img, square }
+        Self {
+            inner: img,
+            square,
+            fit: ImageFit::default(),
+            alignment: Vector2::new(0.5, 0.5),
+            source: None,
+            nine_slice: None,
+            sampling: ImageSampling::default(),
+        }
     }
 
     ///Gets the position of this Image
@@ -62,11 +190,238 @@ impl CandyImage {
         self.inner.height() as i32
     }
 
+    #[inline]
+    ///Retrieves how the image is resized to its square
+    pub fn fit(&self) -> ImageFit {
+        self.fit
+    }
+
+    #[inline]
+    ///Sets how the image is resized to its square
+    pub fn set_fit(&mut self, fit: ImageFit) -> &mut Self {
+        self.square.mark_dirty();
+        self.fit = fit;
+        self
+    }
+
+    #[inline]
+    ///Retrieves where the image is placed in its square when it doesn't fill it, from (0,0), the top left corner, to (1,1), the bottom
+    ///right one
+    pub fn alignment(&self) -> &Vector2<f32> {
+        &self.alignment
+    }
+
+    #[inline]
+    ///Sets where the image is placed in its square when it doesn't fill it, from (0,0), the top left corner, to (1,1), the bottom right one.
+    ///By default it's centered
+    pub fn set_alignment(&mut self, alignment: Vector2<f32>) -> &mut Self {
+        self.square.mark_dirty();
+        self.alignment = alignment;
+        self
+    }
+
+    ///Retrieves the part of the image that is drawn, in its pixels. It's the whole image unless another part was set
+    pub fn source_rect(&self) -> Rect {
+        let (w, h) = (self.real_width() as f32, self.real_height() as f32);
+        let Some(ref source) = self.source else {
+            return Rect::new(0.0, 0.0, w, h);
+        };
+        let (x, y) = (source.x.clamp(0.0, w), source.y.clamp(0.0, h));
+        Rect::new(
+            x,
+            y,
+            source.width.clamp(0.0, w - x),
+            source.height.clamp(0.0, h - y),
+        )
+    }
+
+    #[inline]
+    ///Sets the part of the image that is drawn, in its pixels, such as a single frame of a sprite sheet. `None` draws the whole image
+    pub fn set_source_rect(&mut self, source: Option<Rect>) -> &mut Self {
+        self.square.mark_dirty();
+        self.source = source;
+        self
+    }
+
+    #[inline]
+    ///Retrieves how the image is split to be scaled as a nine-patch, if it is
+    pub fn nine_slice(&self) -> Option<NineSlice> {
+        self.nine_slice
+    }
+
+    #[inline]
+    ///Sets the image to be scaled as a nine-patch split by `slice`, filling its whole square, no matter its fit. `None` scales it
+    ///the usual way
+    pub fn set_nine_slice(&mut self, slice: Option<NineSlice>) -> &mut Self {
+        self.square.mark_dirty();
+        self.nine_slice = slice;
+        self
+    }
+
+    #[inline]
+    ///Retrieves how the pixels of the image are read when it's drawn with another size
+    pub fn sampling(&self) -> ImageSampling {
+        self.sampling
+    }
+
+    #[inline]
+    ///Sets how the pixels of the image are read when it's drawn with another size
+    pub fn set_sampling(&mut self, sampling: ImageSampling) -> &mut Self {
+        self.square.mark_dirty();
+        self.sampling = sampling;
+        self
+    }
+
+    ///Retrieves where the source rect of the image is drawn after being fit and aligned in its square. It may go out of the square,
+    ///which is where it's clipped
+    pub fn destination_rect(&self) -> Rect {
+        let bounds = self.bounds();
+        let source = self.source_rect();
+        if source.width <= 0.0 || source.height <= 0.0 {
+            return bounds;
+        }
+        let contain = (bounds.width / source.width).min(bounds.height / source.height);
+        let cover = (bounds.width / source.width).max(bounds.height / source.height);
+        let (w, h) = match self.fit {
+            ImageFit::Fill => (bounds.width, bounds.height),
+            ImageFit::Contain => (source.width * contain, source.height * contain),
+            ImageFit::Cover => (source.width * cover, source.height * cover),
+            ImageFit::None => (source.width, source.height),
+            ImageFit::ScaleDown => {
+                let scale = contain.min(1.0);
+                (source.width * scale, source.height * scale)
+            }
+        };
+        Rect::new(
+            bounds.x + (bounds.width - w) * self.alignment.x,
+            bounds.y + (bounds.height - h) * self.alignment.y,
+            w,
+            h,
+        )
+    }
+
+    ///Retrieves the parts of the image that are drawn, each one as the rect it's read from, in pixels of the image, and the rect it's
+    ///drawn on. It's a single part unless the image is a nine-patch
+    pub fn patches(&self) -> Vec<(Rect, Rect)> {
+        let source = self.source_rect();
+        let Some(slice) = self.nine_slice else {
+            return vec![(source, self.destination_rect())];
+        };
+        let bounds = self.bounds();
+        let (sx, sy) = (
+            split_segment(source.x, source.width, slice.left, slice.right),
+            split_segment(source.y, source.height, slice.top, slice.bottom),
+        );
+        let (dx, dy) = (
+            split_segment(bounds.x, bounds.width, sx[1] - sx[0], sx[3] - sx[2]),
+            split_segment(bounds.y, bounds.height, sy[1] - sy[0], sy[3] - sy[2]),
+        );
+        let mut out = Vec::with_capacity(9);
+        for row in 0..3 {
+            for col in 0..3 {
+                let from = Rect::new(
+                    sx[col],
+                    sy[row],
+                    sx[col + 1] - sx[col],
+                    sy[row + 1] - sy[row],
+                );
+                let to = Rect::new(
+                    dx[col],
+                    dy[row],
+                    dx[col + 1] - dx[col],
+                    dy[row + 1] - dy[row],
+                );
+                if from.width > 0.0 && from.height > 0.0 && to.width > 0.0 && to.height > 0.0 {
+                    out.push((from, to));
+                }
+            }
+        }
+        out
+    }
+
     #[inline]
     ///Returns the inner image handle
     pub fn image_handler(&self) -> &RendererImage {
         &self.inner
     }
+
+    #[inline]
+    ///Returns the inner image handle, so its pixels can be updated
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn image_handler_mut(&mut self) -> &mut RendererImage {
+        self.square.mark_dirty();
+        &mut self.inner
+    }
+
+    #[inline]
+    ///Replaces the drawn image with `image`, keeping the square, the fit and everything else about how it's drawn
+    pub fn set_image(&mut self, image: RendererImage) -> &mut Self {
+        self.square.mark_dirty();
+        self.inner = image;
+        self
+    }
+
+    #[inline]
+    ///Returns the inner image as a skia image, used by the painters that draw with skia
+    pub fn to_skia_image(&self) -> Option<Image> {
+        skia_image_of(&self.inner)
+    }
+}
+
+thread_local! {
+    ///The skia copies of the renderer images drawn until now, by their id, with the generation of the pixels they've got, so they're
+    ///only updated where the image changed. Skia images can't be changed, so the pixels are kept on a raster surface, which is
+    ///snapshotted after being written
+    static SKIA_IMAGES: RefCell<LruCache<u32, (u64, Surface, Option<Image>)>> =
+        RefCell::new(LruCache::new(DEFAULT_IMAGE_CACHE_BUDGET));
+}
+
+///Returns the given renderer `image` as a skia image, converting it only the first time it's drawn and then writing only the regions
+///that changed since
+pub(crate) fn skia_image_of(image: &RendererImage) -> Option<Image> {
+    let info = ImageInfo::new(
+        ISize::new(image.width() as i32, image.height() as i32),
+        ColorType::RGBA8888,
+        AlphaType::Premul,
+        None,
+    );
+    let row_bytes = info.min_row_bytes();
+    SKIA_IMAGES.with_borrow_mut(|images| {
+        let id = image.unique_id();
+        if !images.contains(&id) {
+            let mut surface = skia_safe::surfaces::raster(&info, None, None)?;
+            surface
+                .canvas()
+                .write_pixels(&info, image.pixels(), row_bytes, (0, 0));
+            let snapshot = Some(surface.image_snapshot());
+            let bytes = image.pixels().len();
+            let (_, _, snapshot) =
+                images.insert(id, (image.generation(), surface, snapshot), bytes);
+            return snapshot.clone();
+        }
+        let (generation, surface, snapshot) = images.get_mut(&id)?;
+        if *generation != image.generation() {
+            let region = image.changes_since(*generation).unwrap_or(Rect::new(
+                0.0,
+                0.0,
+                image.width() as f32,
+                image.height() as f32,
+            ));
+            //the snapshot shares the pixels of the surface, so it's dropped first, or they would be copied before being written
+            *snapshot = None;
+            let (x, y) = (region.x as usize, region.y as usize);
+            let region_info = info.with_dimensions((region.width as i32, region.height as i32));
+            surface.canvas().write_pixels(
+                &region_info,
+                &image.pixels()[y * row_bytes + x * 4..],
+                row_bytes,
+                (x as i32, y as i32),
+            );
+            *snapshot = Some(surface.image_snapshot());
+            *generation = image.generation();
+        }
+        snapshot.clone()
+    })
 }
 
 impl Deref for CandyImage {
@@ -83,20 +438,155 @@ impl DerefMut for CandyImage {
     }
 }
 
-impl TwodCandyImg for skia_safe::Image {
-    fn from_source<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<Self> {
-        let data = std::fs::read(path)?;
-        let inner = Image::from_encoded(Data::new_copy(&data)).unwrap();
+///Decodes the encoded `bytes` with skia into premultiplied RGBA pixels. Skia only decodes the pixels when they're read, so data that is
+///corrupted past its header is an error here too
+fn decode(bytes: &[u8]) -> std::io::Result<RendererImage> {
+    let invalid = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "the image could not be decoded",
+        )
+    };
+    let image = Image::from_encoded(Data::new_copy(bytes)).ok_or_else(invalid)?;
+    let info = ImageInfo::new(
+        image.dimensions(),
+        ColorType::RGBA8888,
+        AlphaType::Premul,
+        None,
+    );
+    let row_bytes = info.min_row_bytes();
+    let mut pixels = vec![0; info.compute_byte_size(row_bytes)];
+    if !image.read_pixels(&info, &mut pixels, row_bytes, (0, 0), CachingHint::Disallow) {
+        return Err(invalid());
+    }
+    RendererImage::new(image.width() as u32, image.height() as u32, pixels).ok_or_else(invalid)
+}
+
+impl TwodCandyImg for RendererImage {
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        decode(bytes)
+    }
 
-        Ok(inner)
+    #[inline]
+    fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Option<Self> {
+        Self::new(width, height, pixels.to_vec())
     }
+
+    #[inline]
+    fn to_rgba(&self) -> Option<Vec<u8>> {
+        Some(self.pixels().to_vec())
+    }
+
+    #[inline]
+    fn update_rgba(&mut self, pixels: &[u8], x: u32, y: u32, width: u32, height: u32) -> bool {
+        self.update(pixels, x, y, width, height)
+    }
+
     #[inline]
     fn width(&self) -> u32 {
-        self.width() as u32
+        self.width()
     }
 
     #[inline]
     fn height(&self) -> u32 {
-        self.height() as u32
+        self.height()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(fit: ImageFit) -> CandyImage {
+        let inner = RendererImage::new(40, 20, vec![0; 40 * 20 * 4]).unwrap();
+        let square = CandySquare::new(Vector2::new(10.0, 10.0), Vector2::new(100.0, 100.0));
+        let mut out = CandyImage::new(inner, square);
+        out.set_fit(fit);
+        out
+    }
+
+    fn destination(image: &CandyImage) -> Rect {
+        let patches = image.patches();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].0, image.source_rect());
+        patches[0].1.clone()
+    }
+
+    #[test]
+    fn patches_follow_the_fit() {
+        let whole = Rect::new(0.0, 0.0, 40.0, 20.0);
+        assert_eq!(
+            image(ImageFit::Fill).patches(),
+            vec![(whole, Rect::new(10.0, 10.0, 100.0, 100.0))]
+        );
+        assert_eq!(
+            destination(&image(ImageFit::Contain)),
+            Rect::new(10.0, 35.0, 100.0, 50.0)
+        );
+        assert_eq!(
+            destination(&image(ImageFit::Cover)),
+            Rect::new(-40.0, 10.0, 200.0, 100.0)
+        );
+        assert_eq!(
+            destination(&image(ImageFit::None)),
+            Rect::new(40.0, 50.0, 40.0, 20.0)
+        );
+        assert_eq!(
+            destination(&image(ImageFit::ScaleDown)),
+            Rect::new(40.0, 50.0, 40.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn patches_follow_the_alignment_and_source() {
+        let mut image = image(ImageFit::Contain);
+        image.set_alignment(Vector2::new(0.0, 1.0));
+        assert_eq!(destination(&image), Rect::new(10.0, 60.0, 100.0, 50.0));
+
+        image.set_source_rect(Some(Rect::new(10.0, 0.0, 20.0, 40.0)));
+        assert_eq!(image.source_rect(), Rect::new(10.0, 0.0, 20.0, 20.0));
+        assert_eq!(destination(&image), Rect::new(10.0, 10.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn nine_slice_keeps_the_corners_and_stretches_the_rest() {
+        let mut image = image(ImageFit::Contain);
+        image.set_nine_slice(Some(NineSlice::uniform(5.0)));
+        let patches = image.patches();
+        assert_eq!(patches.len(), 9);
+        assert_eq!(
+            patches[0],
+            (
+                Rect::new(0.0, 0.0, 5.0, 5.0),
+                Rect::new(10.0, 10.0, 5.0, 5.0)
+            )
+        );
+        assert_eq!(
+            patches[4],
+            (
+                Rect::new(5.0, 5.0, 30.0, 10.0),
+                Rect::new(15.0, 15.0, 90.0, 90.0)
+            )
+        );
+        assert_eq!(
+            patches[8],
+            (
+                Rect::new(35.0, 15.0, 5.0, 5.0),
+                Rect::new(105.0, 105.0, 5.0, 5.0)
+            )
+        );
+    }
+
+    #[test]
+    fn nine_slice_corners_shrink_when_they_do_not_fit() {
+        let mut image = image(ImageFit::Fill);
+        image.set_nine_slice(Some(NineSlice::new(5.0, 5.0, 5.0, 5.0)));
+        image.resize(Rect::new(10.0, 10.0, 8.0, 100.0));
+        let patches = image.patches();
+        //the center column is left without any width, so it isn't drawn
+        assert_eq!(patches.len(), 6);
+        assert_eq!(patches[0].1, Rect::new(10.0, 10.0, 4.0, 5.0));
+        assert_eq!(patches[1].1, Rect::new(14.0, 10.0, 4.0, 5.0));
     }
 }
@@ -0,0 +1,298 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::path::Path;
+
+use candy_shared_types::RendererImage;
+
+use super::TwodCandyImg;
+
+///The default budget of an [ImageCache], 64MB of decoded pixels
+pub const DEFAULT_IMAGE_CACHE_BUDGET: usize = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+struct CachedEntry<V> {
+    value: V,
+    ///The amount of bytes the value takes
+    bytes: usize,
+    ///When the value was used for the last time, in the ticks of the cache
+    last_used: u64,
+}
+
+#[derive(Debug)]
+///Values by their key that take at most a budget of bytes together. When they go over it, the least recently used ones are evicted
+pub(crate) struct LruCache<K, V> {
+    entries: HashMap<K, CachedEntry<V>>,
+    budget: usize,
+    used: usize,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    ///Creates an empty cache that keeps at most `budget` bytes
+    pub(crate) fn new(budget: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            budget,
+            used: 0,
+            tick: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn budget(&self) -> usize {
+        self.budget
+    }
+
+    ///Sets the maximum amount of bytes kept, evicting the least recently used values that go over it
+    pub(crate) fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict(None);
+    }
+
+    #[inline]
+    pub(crate) fn used(&self) -> usize {
+        self.used
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub(crate) fn contains<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.entries.contains_key(key)
+    }
+
+    ///Retrieves the value with the given `key`, marking it as the most recently used
+    pub(crate) fn get<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        Some(&entry.value)
+    }
+
+    ///Retrieves the value with the given `key`, so it can be changed, marking it as the most recently used
+    pub(crate) fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.tick += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.tick;
+        Some(&mut entry.value)
+    }
+
+    ///Caches `value`, which takes `bytes`, with the given `key`, replacing the one cached before with it. Then the least recently used
+    ///values are evicted until the cache fits in its budget, except `value`, which is kept even if it alone doesn't fit
+    pub(crate) fn insert(&mut self, key: K, value: V, bytes: usize) -> &V {
+        self.tick += 1;
+        let entry = CachedEntry {
+            value,
+            bytes,
+            last_used: self.tick,
+        };
+        if let Some(old) = self.entries.insert(key.clone(), entry) {
+            self.used -= old.bytes;
+        }
+        self.used += bytes;
+        self.evict(Some(&key));
+        &self.entries[&key].value
+    }
+
+    ///Removes the value with the given `key`, returning it
+    pub(crate) fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let entry = self.entries.remove(key)?;
+        self.used -= entry.bytes;
+        Some(entry.value)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.used = 0;
+    }
+
+    ///Evicts the least recently used values until the cache fits in its budget, never evicting the one with the `kept` key
+    fn evict(&mut self, kept: Option<&K>) {
+        while self.used > self.budget {
+            let oldest = self
+                .entries
+                .iter()
+                .filter(|(key, _)| Some(*key) != kept)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+#[derive(Debug)]
+///Decoded images by their path or URI, so an image drawn many times, such as an avatar on a list, is only decoded once. The images share
+///their pixels, so the painters also upload them only once.
+///When the decoded pixels go over the memory budget, the least recently used images are evicted. Images still in use by a [super::CandyImage]
+///stay alive until it's dropped, they're only forgotten by the cache
+pub struct ImageCache {
+    images: LruCache<String, RendererImage>,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IMAGE_CACHE_BUDGET)
+    }
+}
+
+impl ImageCache {
+    ///Creates an empty cache that keeps at most `budget` bytes of decoded pixels
+    pub fn new(budget: usize) -> Self {
+        Self {
+            images: LruCache::new(budget),
+        }
+    }
+
+    #[inline]
+    ///Retrieves the maximum amount of bytes of decoded pixels this cache keeps
+    pub fn budget(&self) -> usize {
+        self.images.budget()
+    }
+
+    ///Sets the maximum amount of bytes of decoded pixels this cache keeps, evicting the least recently used images that go over it
+    pub fn set_budget(&mut self, budget: usize) -> &mut Self {
+        self.images.set_budget(budget);
+        self
+    }
+
+    #[inline]
+    ///Retrieves the amount of bytes the decoded pixels of the cached images take
+    pub fn memory_usage(&self) -> usize {
+        self.images.used()
+    }
+
+    #[inline]
+    ///Retrieves the amount of images in the cache
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.images.len() == 0
+    }
+
+    #[inline]
+    ///Checks if there is an image cached with the given `key`, without marking it as used
+    pub fn contains(&self, key: &str) -> bool {
+        self.images.contains(key)
+    }
+
+    ///Retrieves the image cached with the given `key`, marking it as the most recently used
+    pub fn get(&mut self, key: &str) -> Option<RendererImage> {
+        self.images.get(key).cloned()
+    }
+
+    ///Caches `image` with the given `key`, replacing the one cached before with it. Then the least recently used images are evicted
+    ///until the cache fits in its budget, except `image`, which is kept even if it alone doesn't fit
+    pub fn insert(&mut self, key: impl Into<String>, image: RendererImage) {
+        let bytes = image.width() as usize * image.height() as usize * 4;
+        self.images.insert(key.into(), image, bytes);
+    }
+
+    ///Retrieves the image cached with the given `key`, or caches the one created by `load` if there is none
+    pub fn get_or_insert_with(
+        &mut self,
+        key: &str,
+        load: impl FnOnce() -> std::io::Result<RendererImage>,
+    ) -> std::io::Result<RendererImage> {
+        if let Some(image) = self.get(key) {
+            return Ok(image);
+        }
+        let image = load()?;
+        self.insert(key, image.clone());
+        Ok(image)
+    }
+
+    #[inline]
+    ///Retrieves the image at `path`, reading and decoding it only if it isn't cached yet
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<RendererImage> {
+        let path = path.as_ref();
+        self.get_or_insert_with(&path.to_string_lossy(), || RendererImage::from_source(path))
+    }
+
+    #[inline]
+    ///Retrieves the image cached with the given `key`, such as an URI, decoding `bytes` only if it isn't cached yet
+    pub fn load_bytes(&mut self, key: &str, bytes: &[u8]) -> std::io::Result<RendererImage> {
+        self.get_or_insert_with(key, || RendererImage::from_bytes(bytes))
+    }
+
+    #[inline]
+    ///Removes the image cached with the given `key`, returning it
+    pub fn remove(&mut self, key: &str) -> Option<RendererImage> {
+        self.images.remove(key)
+    }
+
+    #[inline]
+    ///Removes every cached image
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_values_are_evicted() {
+        let mut cache = LruCache::new(30);
+        cache.insert("a", 1, 10);
+        cache.insert("b", 2, 10);
+        cache.insert("c", 3, 10);
+        assert_eq!(cache.get("a"), Some(&1));
+        cache.insert("d", 4, 10);
+        assert!(!cache.contains("b"));
+        assert!(cache.contains("a") && cache.contains("c") && cache.contains("d"));
+        assert_eq!(cache.used(), 30);
+    }
+
+    #[test]
+    fn value_inserted_is_kept_even_over_the_budget() {
+        let mut cache = LruCache::new(10);
+        cache.insert("a", 1, 5);
+        assert_eq!(*cache.insert("b", 2, 20), 2);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.used(), 20);
+    }
+
+    #[test]
+    fn replacing_a_value_counts_only_its_new_bytes() {
+        let mut cache = LruCache::new(100);
+        cache.insert("a", 1, 40);
+        cache.insert("a", 2, 10);
+        assert_eq!(cache.used(), 10);
+        assert_eq!(cache.remove("a"), Some(2));
+        assert_eq!(cache.used(), 0);
+    }
+
+    #[test]
+    fn lowering_the_budget_evicts() {
+        let mut cache = LruCache::new(100);
+        cache.insert(1, "a", 40);
+        cache.insert(2, "b", 40);
+        cache.get(&1);
+        cache.set_budget(50);
+        assert!(cache.contains(&1));
+        assert!(!cache.contains(&2));
+    }
+}
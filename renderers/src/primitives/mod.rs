@@ -1,10 +1,24 @@
+mod animated_image;
+mod atlas;
 mod font;
 mod image;
+mod image_cache;
+mod paragraph;
+mod path;
+mod picture;
 mod rule;
+mod shader;
 mod square;
 mod text;
+pub use animated_image::*;
+pub use atlas::*;
 pub use font::*;
 pub use image::*;
+pub use image_cache::*;
+pub use paragraph::*;
+pub use path::*;
+pub use picture::*;
 pub use rule::*;
+pub use shader::*;
 pub use square::*;
 pub use text::*;
@@ -0,0 +1,554 @@
+use std::ops::Range;
+
+use nalgebra::Vector2;
+use skia_safe::Paint;
+
+use crate::primitives::CandyFont;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the lines of a paragraph are placed horizontally within its width
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    ///Stretches the spaces between the words of each line so it fills the width. The last line of each paragraph is left aligned
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the lines of a paragraph are placed vertically within its height
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Center,
+    Bottom,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How a text is laid out as a paragraph. It's broken into lines on each line break it's got and, when wrapping, before every word
+///that doesn't fit on the width of the text
+pub struct ParagraphStyle {
+    pub align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    ///The height of each line, as a multiple of the font size
+    pub line_height: f32,
+    ///Whether lines wider than the text are broken between words, or inside of a word that doesn't fit alone
+    pub wrap: bool,
+    ///The most lines shown. When there are more, the last one shown ends with `ellipsis`
+    pub max_lines: Option<usize>,
+    ///What the last line shown ends with when there are more than `max_lines`
+    pub ellipsis: String,
+}
+
+impl Default for ParagraphStyle {
+    fn default() -> Self {
+        Self {
+            align: TextAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            line_height: 1.2,
+            wrap: true,
+            max_lines: None,
+            ellipsis: String::from("…"),
+        }
+    }
+}
+
+impl ParagraphStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    #[inline]
+    pub fn with_vertical_align(mut self, vertical_align: VerticalAlign) -> Self {
+        self.vertical_align = vertical_align;
+        self
+    }
+
+    #[inline]
+    ///Sets the height of each line, as a multiple of the font size
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    #[inline]
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    #[inline]
+    ///Limits the lines shown to `max_lines`, ending the last one with `ellipsis` when there are more
+    pub fn with_max_lines(mut self, max_lines: usize, ellipsis: &str) -> Self {
+        self.max_lines = Some(max_lines);
+        self.ellipsis = ellipsis.to_string();
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///A piece of a line that is drawn at once
+pub struct TextRun {
+    ///The bytes of the content drawn
+    pub range: Range<usize>,
+    ///Where the baseline of the run starts, relative to the top left corner of the paragraph
+    pub origin: Vector2<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///A single line of a paragraph
+pub struct TextLine {
+    ///The bytes of the content on this line, without the spaces and line break it ends with
+    pub range: Range<usize>,
+    ///The pieces the line is drawn with. A single one, unless it's justified, in which case there's one for each word
+    pub runs: Vec<TextRun>,
+    ///Where the line starts horizontally, relative to the left of the paragraph
+    pub x: f32,
+    ///How wide the line is drawn, with the ellipsis if it's got one
+    pub width: f32,
+    ///Where the line starts vertically, relative to the top of the paragraph
+    pub top: f32,
+    ///Where the baseline of the line is, relative to the top of the paragraph
+    pub baseline: f32,
+    ///Where the baseline of the ellipsis starts, relative to the top left corner of the paragraph, if the text was cut on this line
+    pub ellipsis: Option<Vector2<f32>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///The lines a text is broken into and where each one is placed, following a [ParagraphStyle]. Everything is relative to the top left
+///corner of the paragraph
+pub struct ParagraphLayout {
+    lines: Vec<TextLine>,
+    ///The width of the widest line and the height of every line together
+    size: Vector2<f32>,
+    line_height: f32,
+    truncated: bool,
+}
+
+impl ParagraphLayout {
+    ///Lays out the `content` drawn with `font` and `paint` as a paragraph following `style`, on a box of the given `bounds`. The width
+    ///is where lines are wrapped and aligned, and the height where they're aligned vertically. When the width is 0 nothing is wrapped
+    ///and the lines are aligned to the widest one. When the height is 0 they're placed from the top
+    pub fn new(
+        content: &str,
+        font: &CandyFont,
+        paint: Option<&Paint>,
+        style: &ParagraphStyle,
+        bounds: Vector2<f32>,
+    ) -> Self {
+        let measure = |text: &str| font.measure_str(text, paint).0;
+        let max_width = (style.wrap && bounds.x > 0.0).then_some(bounds.x);
+
+        //every line, with whether it's the last one of its paragraph
+        let mut breaks: Vec<(Range<usize>, bool)> = Vec::new();
+        let mut start = 0;
+        for segment in content.split('\n') {
+            let end = start + segment.trim_end_matches('\r').len();
+            wrap_segment(content, start..end, max_width, &measure, &mut breaks);
+            if let Some((_, last)) = breaks.last_mut() {
+                *last = true;
+            }
+            start += segment.len() + 1;
+        }
+
+        let mut truncated = false;
+        if let Some(max_lines) = style.max_lines {
+            if breaks.len() > max_lines {
+                breaks.truncate(max_lines);
+                truncated = true;
+            }
+        }
+
+        let line_height = font.size() * style.line_height;
+        let (_, metrics) = font.metrics();
+        let ascent = -metrics.ascent;
+        //centers the glyphs on the line, splitting what's left of its height above and below them
+        let half_leading = (line_height - (ascent + metrics.descent)) * 0.5;
+        let ellipsis_width = measure(&style.ellipsis);
+        let available = if bounds.x > 0.0 {
+            bounds.x
+        } else {
+            f32::INFINITY
+        };
+
+        let count = breaks.len();
+        let mut lines = Vec::with_capacity(count);
+        for (idx, (mut range, last)) in breaks.into_iter().enumerate() {
+            let top = line_height * idx as f32;
+            let baseline = top + half_leading + ascent;
+            let mut width = measure(&content[range.clone()]);
+            let mut ellipsis = None;
+            if truncated && idx + 1 == count {
+                while range.start < range.end && width + ellipsis_width > available {
+                    let removed = content[range.clone()]
+                        .chars()
+                        .next_back()
+                        .map_or(0, char::len_utf8);
+                    range.end -= removed;
+                    range.end = range.start + content[range.clone()].trim_end().len();
+                    width = measure(&content[range.clone()]);
+                }
+                ellipsis = Some(Vector2::new(width, baseline));
+                width += ellipsis_width;
+            }
+            lines.push((
+                TextLine {
+                    runs: Vec::new(),
+                    range,
+                    x: 0.0,
+                    width,
+                    top,
+                    baseline,
+                    ellipsis,
+                },
+                last,
+            ));
+        }
+
+        let widest = lines.iter().map(|(line, _)| line.width).fold(0.0, f32::max);
+        //lines are aligned on the box, or on the widest one if it's got no width
+        let align_width = if bounds.x > 0.0 { bounds.x } else { widest };
+        let height = line_height * count as f32;
+        let offset = (bounds.y - height).max(0.0)
+            * match style.vertical_align {
+                VerticalAlign::Top => 0.0,
+                VerticalAlign::Center => 0.5,
+                VerticalAlign::Bottom => 1.0,
+            };
+
+        let lines = lines
+            .into_iter()
+            .map(|(mut line, last)| {
+                line.top += offset;
+                line.baseline += offset;
+                line.x = match style.align {
+                    TextAlign::Left | TextAlign::Justify => 0.0,
+                    TextAlign::Center => (align_width - line.width) * 0.5,
+                    TextAlign::Right => align_width - line.width,
+                };
+                if let Some(ellipsis) = &mut line.ellipsis {
+                    ellipsis.x += line.x;
+                    ellipsis.y = line.baseline;
+                }
+                let justify = style.align == TextAlign::Justify
+                    && !last
+                    && line.ellipsis.is_none()
+                    && line.width < align_width;
+                let words = if justify {
+                    words(content, line.range.clone())
+                } else {
+                    Vec::new()
+                };
+                if words.len() > 1 {
+                    let extra = (align_width - line.width) / (words.len() - 1) as f32;
+                    line.runs = words
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, word)| TextRun {
+                            origin: Vector2::new(
+                                measure(&content[line.range.start..word.start])
+                                    + extra * idx as f32,
+                                line.baseline,
+                            ),
+                            range: word,
+                        })
+                        .collect();
+                    line.width = align_width;
+                } else {
+                    line.runs = vec![TextRun {
+                        range: line.range.clone(),
+                        origin: Vector2::new(line.x, line.baseline),
+                    }];
+                }
+                line
+            })
+            .collect();
+
+        Self {
+            lines,
+            size: Vector2::new(widest, height),
+            line_height,
+            truncated,
+        }
+    }
+
+    #[inline]
+    ///Retrieves the lines of the paragraph, from the top to the bottom
+    pub fn lines(&self) -> &[TextLine] {
+        &self.lines
+    }
+
+    #[inline]
+    ///Retrieves the width of the widest line and the height of every line together
+    pub fn size(&self) -> Vector2<f32> {
+        self.size
+    }
+
+    #[inline]
+    pub fn width(&self) -> f32 {
+        self.size.x
+    }
+
+    #[inline]
+    pub fn height(&self) -> f32 {
+        self.size.y
+    }
+
+    #[inline]
+    ///Retrieves the amount of lines shown
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    #[inline]
+    ///Retrieves the height of each line, in pixels
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    #[inline]
+    ///Checks whether there were more lines than the most allowed, so the last one shown ends with an ellipsis
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    ///Retrieves the top left corner of where a caret placed before the byte `index` of `content` is drawn, relative to the top left
+    ///corner of the paragraph. `content`, `font` and `paint` must be the ones it was laid out with. Indices on the spaces a line was
+    ///wrapped at are placed at the end of that line
+    pub fn caret_position(
+        &self,
+        content: &str,
+        font: &CandyFont,
+        paint: Option<&Paint>,
+        index: usize,
+    ) -> Vector2<f32> {
+        let Some(line) = self
+            .lines
+            .iter()
+            .rev()
+            .find(|line| line.range.start <= index)
+            .or(self.lines.first())
+        else {
+            return Vector2::zeros();
+        };
+        let Some(run) = line
+            .runs
+            .iter()
+            .rev()
+            .find(|run| run.range.start <= index)
+            .or(line.runs.first())
+        else {
+            return Vector2::new(line.x, line.top);
+        };
+        let end = index.clamp(run.range.start, run.range.end);
+        Vector2::new(
+            run.origin.x + font.measure_str(&content[run.range.start..end], paint).0,
+            line.top,
+        )
+    }
+}
+
+///Retrieves the byte ranges of every word on `range` of `content`, which are the pieces between whitespace
+fn words(content: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (offset, ch) in content[range.clone()].char_indices() {
+        let idx = range.start + offset;
+        match (ch.is_whitespace(), start) {
+            (true, Some(word)) => {
+                words.push(word..idx);
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(word) = start {
+        words.push(word..range.end);
+    }
+    words
+}
+
+///Breaks the `segment` of `content`, which has got no line breaks, into lines no wider than `max_width`, pushing each one on
+///`breaks`. Lines are broken between words and, if a word doesn't fit alone, between its chars. Without a `max_width` it's kept as a
+///single line
+fn wrap_segment(
+    content: &str,
+    segment: Range<usize>,
+    max_width: Option<f32>,
+    measure: &impl Fn(&str) -> f32,
+    breaks: &mut Vec<(Range<usize>, bool)>,
+) {
+    let Some(max_width) = max_width else {
+        let end = segment.start + content[segment.clone()].trim_end().len();
+        breaks.push((segment.start..end, false));
+        return;
+    };
+    //the first line keeps the spaces the segment starts with
+    let mut start = segment.start;
+    let mut end = start;
+    //each word and the spaces before it are measured once, and the width of the line is their sum
+    let mut width = 0.0;
+    for word in words(content, segment) {
+        let spaces = measure(&content[end..word.start]);
+        let word_width = measure(&content[word.clone()]);
+        if width + spaces + word_width <= max_width {
+            width += spaces + word_width;
+            end = word.end;
+            continue;
+        }
+        if end > start {
+            breaks.push((start..end, false));
+            start = word.start;
+            width = word_width;
+        } else {
+            width += spaces + word_width;
+        }
+        //the word doesn't fit alone either, so it's broken wherever it overflows
+        if width > max_width {
+            width = 0.0;
+            let from = start;
+            for (offset, ch) in content[from..word.end].char_indices() {
+                let at = from + offset;
+                let ch_width = measure(&content[at..at + ch.len_utf8()]);
+                if at > start && width + ch_width > max_width {
+                    breaks.push((start..at, false));
+                    start = at;
+                    width = 0.0;
+                }
+                width += ch_width;
+            }
+        }
+        end = word.end;
+    }
+    breaks.push((start..end, false));
+}
+
+#[cfg(test)]
+mod tests {
+    use skia_safe::FontMgr;
+
+    use super::*;
+
+    fn font() -> CandyFont {
+        let typeface = FontMgr::new()
+            .new_from_data(include_bytes!("../../../inter.ttf"), None)
+            .unwrap();
+        CandyFont::new(typeface, 20.0)
+    }
+
+    fn width(font: &CandyFont, text: &str) -> f32 {
+        font.measure_str(text, None).0
+    }
+
+    fn ranges(layout: &ParagraphLayout) -> Vec<Range<usize>> {
+        layout
+            .lines()
+            .iter()
+            .map(|line| line.range.clone())
+            .collect()
+    }
+
+    #[test]
+    fn lines_are_wrapped_between_words() {
+        let font = font();
+        let content = "aaa bbb ccc";
+        let bounds = Vector2::new(width(&font, "aaa bbb") + 1.0, 0.0);
+        let layout = ParagraphLayout::new(content, &font, None, &ParagraphStyle::new(), bounds);
+        assert_eq!(ranges(&layout), vec![0..7, 8..11]);
+        assert_eq!(layout.lines()[1].top, layout.line_height());
+        assert_eq!(layout.height(), layout.line_height() * 2.0);
+
+        let style = ParagraphStyle::new().with_wrap(false);
+        let layout = ParagraphLayout::new(content, &font, None, &style, bounds);
+        assert_eq!(ranges(&layout), vec![0..11]);
+    }
+
+    #[test]
+    fn line_breaks_and_long_words_break_lines() {
+        let font = font();
+        let style = ParagraphStyle::new();
+        let layout = ParagraphLayout::new("one\r\ntwo", &font, None, &style, Vector2::zeros());
+        assert_eq!(ranges(&layout), vec![0..3, 5..8]);
+
+        let bounds = Vector2::new(width(&font, "aaa") + 1.0, 0.0);
+        let layout = ParagraphLayout::new("aaaaaaaa", &font, None, &style, bounds);
+        assert_eq!(ranges(&layout), vec![0..3, 3..6, 6..8]);
+    }
+
+    #[test]
+    fn justified_lines_fill_the_width_except_the_last() {
+        let font = font();
+        let content = "aaa bb c dddd";
+        let bounds = Vector2::new(width(&font, "aaa bb c") + 20.0, 0.0);
+        let style = ParagraphStyle::new().with_align(TextAlign::Justify);
+        let layout = ParagraphLayout::new(content, &font, None, &style, bounds);
+        assert_eq!(ranges(&layout), vec![0..8, 9..13]);
+
+        let first = &layout.lines()[0];
+        assert_eq!(first.width, bounds.x);
+        let runs: Vec<_> = first.runs.iter().map(|run| run.range.clone()).collect();
+        assert_eq!(runs, vec![0..3, 4..6, 7..8]);
+        assert_eq!(first.runs[0].origin.x, 0.0);
+        let end = first.runs[2].origin.x + width(&font, "c");
+        assert!((end - bounds.x).abs() < 0.5, "{end} != {}", bounds.x);
+
+        let last = &layout.lines()[1];
+        assert_eq!(last.runs.len(), 1);
+        assert_eq!(last.runs[0].origin.x, 0.0);
+    }
+
+    #[test]
+    fn lines_over_the_most_allowed_end_with_an_ellipsis() {
+        let font = font();
+        let content = "one two three four";
+        let bounds = Vector2::new(width(&font, "one two") + 1.0, 0.0);
+        let style = ParagraphStyle::new().with_max_lines(1, "…");
+        let layout = ParagraphLayout::new(content, &font, None, &style, bounds);
+        assert!(layout.is_truncated());
+        assert_eq!(layout.line_count(), 1);
+
+        //the line is cut until the ellipsis fits after it
+        let line = &layout.lines()[0];
+        assert_eq!(line.range.start, 0);
+        assert!(line.range.end < 7);
+        let cut = width(&font, &content[line.range.clone()]);
+        assert_eq!(line.ellipsis, Some(Vector2::new(cut, line.baseline)));
+        assert_eq!(line.width, cut + width(&font, "…"));
+        assert!(line.width <= bounds.x);
+
+        let style = ParagraphStyle::new().with_max_lines(3, "…");
+        let layout = ParagraphLayout::new(content, &font, None, &style, bounds);
+        assert!(!layout.is_truncated());
+        assert!(layout.lines().iter().all(|line| line.ellipsis.is_none()));
+    }
+
+    #[test]
+    fn caret_is_placed_on_the_line_of_its_index() {
+        let font = font();
+        let content = "aaa bbb ccc";
+        let bounds = Vector2::new(width(&font, "aaa bbb") + 1.0, 0.0);
+        let layout = ParagraphLayout::new(content, &font, None, &ParagraphStyle::new(), bounds);
+        let caret = |index| layout.caret_position(content, &font, None, index);
+
+        assert_eq!(caret(0), Vector2::zeros());
+        assert_eq!(caret(4), Vector2::new(width(&font, "aaa "), 0.0));
+        //the space the line was wrapped at stays at the end of the first line
+        assert_eq!(caret(7), Vector2::new(width(&font, "aaa bbb"), 0.0));
+        assert_eq!(caret(8), Vector2::new(0.0, layout.line_height()));
+        assert_eq!(
+            caret(11),
+            Vector2::new(width(&font, "ccc"), layout.line_height())
+        );
+    }
+}
@@ -0,0 +1,406 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use candy_shared_types::{Damage, DirtyArea, Rect, Style};
+use nalgebra::Vector2;
+
+use crate::{bidimensional::skia::to_skia_path, primitives::DrawRule};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A single command of a path. Every point is relative to the position of the path
+pub enum PathSegment {
+    ///Starts a new contour at the given point
+    MoveTo(Vector2<f32>),
+    ///Draws a line from the last point to the given one
+    LineTo(Vector2<f32>),
+    ///Draws a quadratic bezier from the last point to `to`
+    QuadTo {
+        control: Vector2<f32>,
+        to: Vector2<f32>,
+    },
+    ///Draws a cubic bezier from the last point to `to`
+    CubicTo {
+        control1: Vector2<f32>,
+        control2: Vector2<f32>,
+        to: Vector2<f32>,
+    },
+    ///Draws an elliptical arc from the last point to `to`, the same way SVG does. `rotation` is the rotation of the ellipse in degrees,
+    ///`large_arc` chooses the larger of the possible arcs and `sweep` chooses the one drawn clockwise
+    ArcTo {
+        radius: Vector2<f32>,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Vector2<f32>,
+    },
+    ///Closes the current contour with a line to its first point
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Defines which parts of a path are considered inside of it when filled
+pub enum FillRule {
+    #[default]
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the ends of an open contour are drawn when stroked
+pub enum StrokeCap {
+    #[default]
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the corners of a path are drawn when stroked
+pub enum StrokeJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Information about how a path is stroked. The width and color of the stroke are the border width and color of the rule of the path
+pub struct StrokeStyle {
+    pub cap: StrokeCap,
+    pub join: StrokeJoin,
+    ///The limit of the ratio between the length of a miter join and the stroke width, after which it becomes a bevel
+    pub miter_limit: f32,
+    ///Lengths of the dashes and the gaps between them, alternately. If empty, the stroke is solid
+    pub dash: Vec<f32>,
+    ///How far into the dash pattern the stroke starts
+    pub dash_offset: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            cap: StrokeCap::default(),
+            join: StrokeJoin::default(),
+            miter_limit: 4.0,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    ///Retrieves whether this stroke is dashed. A dash pattern with no positive length is ignored
+    pub fn is_dashed(&self) -> bool {
+        !self.dash.is_empty() && self.dash.iter().any(|len| *len > 0.0)
+    }
+
+    ///Retrieves the dash pattern with an even amount of lengths. As on SVG, a pattern with an odd amount is repeated
+    pub fn dash_pattern(&self) -> Vec<f32> {
+        let mut out = self.dash.clone();
+        if out.len() % 2 != 0 {
+            out.extend_from_slice(&self.dash);
+        }
+        out
+    }
+}
+
+///A handler that contains information about how a path should be drawn. It's filled with the color of its rule and stroked with its border.
+///Changes made straight to the fill rule, the stroke or the rule aren't tracked, so [CandyPath::mark_dirty] must be called after them
+#[derive(Debug, Default)]
+pub struct CandyPath {
+    segments: Vec<PathSegment>,
+    position: Vector2<f32>,
+    size: Vector2<f32>,
+    pub fill_rule: FillRule,
+    pub stroke: StrokeStyle,
+    pub rule: DrawRule,
+    dirty: DirtyArea,
+}
+
+impl CandyPath {
+    ///Creates a new empty path located at `position`
+    pub fn new(position: Vector2<f32>) -> Self {
+        Self {
+            segments: Vec::new(),
+            position,
+            size: Vector2::zeros(),
+            fill_rule: FillRule::default(),
+            stroke: StrokeStyle::default(),
+            rule: DrawRule::new(),
+            dirty: DirtyArea::default(),
+        }
+    }
+
+    ///Creates a new path located at `position` made of the given `segments`
+    pub fn from_segments(position: Vector2<f32>, segments: Vec<PathSegment>) -> Self {
+        let mut out = Self::new(position);
+        out.segments = segments;
+        out
+    }
+
+    #[inline]
+    ///Gets the segments of this path
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    #[inline]
+    ///Gets the segments of this path
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn segments_mut(&mut self) -> &mut Vec<PathSegment> {
+        self.mark_dirty();
+        &mut self.segments
+    }
+
+    #[inline]
+    pub fn move_to(&mut self, to: Vector2<f32>) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::MoveTo(to));
+        self
+    }
+
+    #[inline]
+    pub fn line_to(&mut self, to: Vector2<f32>) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::LineTo(to));
+        self
+    }
+
+    #[inline]
+    pub fn quad_to(&mut self, control: Vector2<f32>, to: Vector2<f32>) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::QuadTo { control, to });
+        self
+    }
+
+    #[inline]
+    pub fn cubic_to(
+        &mut self,
+        control1: Vector2<f32>,
+        control2: Vector2<f32>,
+        to: Vector2<f32>,
+    ) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::CubicTo {
+            control1,
+            control2,
+            to,
+        });
+        self
+    }
+
+    #[inline]
+    ///Draws an elliptical arc to `to`. See [PathSegment::ArcTo]
+    pub fn arc_to(
+        &mut self,
+        radius: Vector2<f32>,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Vector2<f32>,
+    ) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::ArcTo {
+            radius,
+            rotation,
+            large_arc,
+            sweep,
+            to,
+        });
+        self
+    }
+
+    #[inline]
+    pub fn close(&mut self) -> &mut Self {
+        self.mark_dirty();
+        self.segments.push(PathSegment::Close);
+        self
+    }
+
+    #[inline]
+    ///Removes every segment of this path
+    pub fn clear(&mut self) {
+        self.mark_dirty();
+        self.segments.clear();
+    }
+
+    ///Gets the position of this path
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn position_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
+        &mut self.position
+    }
+
+    ///Gets the size of the area this path was given
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn size_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
+        &mut self.size
+    }
+
+    ///Gets the position of this path
+    pub fn position(&self) -> &Vector2<f32> {
+        &self.position
+    }
+
+    ///Gets the size of the area this path was given. The segments are not scaled to it, it's only used as the bounds of the path
+    pub fn size(&self) -> &Vector2<f32> {
+        &self.size
+    }
+
+    pub fn resize(&mut self, rect: Rect) {
+        if rect != self.bounds() {
+            self.mark_dirty();
+        }
+        self.size.x = rect.width;
+        self.size.y = rect.height;
+        self.position.x = rect.x;
+        self.position.y = rect.y;
+    }
+
+    pub fn bounds(&self) -> Rect {
+        Rect {
+            x: self.position.x,
+            y: self.position.y,
+            width: self.size.x,
+            height: self.size.y,
+        }
+    }
+
+    ///Applies the given style to this path. The background color fills it and the border strokes it
+    #[inline]
+    pub fn apply_style(&mut self, style: &dyn Style) {
+        self.mark_dirty();
+        self.rule.apply_style(style);
+    }
+
+    #[inline]
+    pub fn with_style(mut self, style: &dyn Style) -> Self {
+        self.rule.apply_style(style);
+        self
+    }
+
+    ///Retrieves the area covered when this path is drawn, with its stroke and effects. Miter joins may go beyond the stroke up to
+    ///its miter limit
+    pub fn visual_bounds(&self) -> Rect {
+        let hull = to_skia_path(self).compute_tight_bounds();
+        let miter = self.rule.border_width * self.stroke.miter_limit.max(1.0) * 0.5;
+        self.rule
+            .visual_bounds(&Rect::new(
+                hull.left + self.position.x,
+                hull.top + self.position.y,
+                hull.width(),
+                hull.height(),
+            ))
+            .outset(miter)
+    }
+
+    ///Marks this path as changed, so both the area it was drawn on and the one it covers now are repainted
+    pub fn mark_dirty(&mut self) {
+        if !self.dirty.is_dirty() {
+            self.dirty.mark(self.visual_bounds());
+        }
+    }
+
+    #[inline]
+    ///Checks whether this path changed since its damage was last taken
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    ///Adds the area this path was drawn on and the one it covers now to `damage`, if it changed since the last time, and marks it as
+    ///clean
+    pub fn take_damage(&mut self, damage: &mut Damage) {
+        if self.dirty.is_dirty() {
+            let current = self.visual_bounds();
+            self.dirty.take(current, damage);
+        }
+    }
+}
+
+///Converts the SVG like arc that goes from `from` to `to` into cubic beziers, each of them as [control1, control2, end]. Used by the
+///painters that can't draw arcs given this way
+pub(crate) fn arc_to_cubics(
+    from: Vector2<f32>,
+    radius: Vector2<f32>,
+    rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Vector2<f32>,
+) -> Vec<[Vector2<f32>; 3]> {
+    if from == to {
+        return Vec::new();
+    }
+    let (mut rx, mut ry) = (radius.x.abs(), radius.y.abs());
+    if rx == 0.0 || ry == 0.0 {
+        //an arc without radius is a line
+        return vec![[from, to, to]];
+    }
+    let (sin, cos) = rotation.to_radians().sin_cos();
+
+    //from here on, this follows the conversion from endpoint to center parameterization of the SVG spec
+    let half = (from - to) * 0.5;
+    let x1 = cos * half.x + sin * half.y;
+    let y1 = -sin * half.x + cos * half.y;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        let scale = lambda.sqrt();
+        rx *= scale;
+        ry *= scale;
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let middle = (from + to) * 0.5;
+    let center = Vector2::new(
+        cos * cx1 - sin * cy1 + middle.x,
+        sin * cx1 + cos * cy1 + middle.y,
+    );
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let (ux, uy) = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let (vx, vy) = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let start = angle(1.0, 0.0, ux, uy);
+    let mut delta = angle(ux, uy, vx, vy);
+    if !sweep && delta > 0.0 {
+        delta -= TAU;
+    } else if sweep && delta < 0.0 {
+        delta += TAU;
+    }
+
+    //every segment covers at most a quarter of the ellipse, so the approximation stays precise
+    let count = (delta.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = delta / count as f32;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let map = |x: f32, y: f32| {
+        Vector2::new(
+            cos * rx * x - sin * ry * y + center.x,
+            sin * rx * x + cos * ry * y + center.y,
+        )
+    };
+
+    let mut out = Vec::with_capacity(count);
+    for idx in 0..count {
+        let (s0, c0) = (start + step * idx as f32).sin_cos();
+        let (s1, c1) = (start + step * (idx + 1) as f32).sin_cos();
+        out.push([
+            map(c0 - k * s0, s0 + k * c0),
+            map(c1 + k * s1, s1 - k * c1),
+            map(c1, s1),
+        ]);
+    }
+    if let Some(last) = out.last_mut() {
+        last[2] = to;
+    }
+    out
+}
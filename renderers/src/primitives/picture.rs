@@ -0,0 +1,32 @@
+use skia_safe::Picture;
+
+#[derive(Debug, Clone)]
+///Draw calls recorded once by a painter, between `begin_picture` and `end_picture`, so they can be drawn again as a whole without
+///issuing each of them. It's drawn with the clip and transform of the moment it's drawn, not of when it was recorded.
+///Cloning it is cheap, as the recording is shared
+pub struct CandyPicture {
+    inner: Picture,
+}
+
+impl CandyPicture {
+    pub(crate) fn new(inner: Picture) -> Self {
+        Self { inner }
+    }
+
+    #[inline]
+    ///Retrieves roughly how many draw calls were recorded
+    pub fn op_count(&self) -> usize {
+        self.inner.approximate_op_count()
+    }
+
+    #[inline]
+    ///Retrieves roughly how many bytes the recording takes
+    pub fn bytes_used(&self) -> usize {
+        self.inner.approximate_bytes_used()
+    }
+
+    #[inline]
+    pub(crate) fn skia_picture(&self) -> &Picture {
+        &self.inner
+    }
+}
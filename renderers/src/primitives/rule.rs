@@ -1,5 +1,17 @@
+use std::borrow::Cow;
+
+use candy_shared_types::BlendMode;
+use candy_shared_types::BorderSide;
+use candy_shared_types::BorderStyle;
+use candy_shared_types::Borders;
+use candy_shared_types::ColorFilter;
+use candy_shared_types::CornerRadii;
 use candy_shared_types::Effect;
+use candy_shared_types::Effects;
+use candy_shared_types::Fill;
+use candy_shared_types::GradientStop;
 use candy_shared_types::Style;
+use candy_shared_types::TileMode;
 use candy_shared_types::vec4f32_to_color;
 use candy_shared_types::vec4f32_to_color_value;
 
@@ -7,19 +19,129 @@ use nalgebra::Vector2;
 use nalgebra::Vector4;
 
 use skia_safe::Color4f;
+use skia_safe::ImageFilter;
+use skia_safe::color_filters;
 use skia_safe::image_filters;
 use skia_safe::image_filters::CropRect;
 
+use skia_safe::Matrix;
 use skia_safe::Paint;
+use skia_safe::PathEffect;
 use skia_safe::Point;
 use skia_safe::Rect;
+use skia_safe::SamplingOptions;
+use skia_safe::Shader;
+
+use crate::bidimensional::skia::to_skia_blend_mode;
+use crate::primitives::image::skia_image_of;
+use crate::primitives::shader_builder;
+
+///Converts the tile mode of candy into the one of skia
+fn to_skia_tile_mode(mode: TileMode) -> skia_safe::TileMode {
+    match mode {
+        TileMode::Clamp => skia_safe::TileMode::Clamp,
+        TileMode::Repeat => skia_safe::TileMode::Repeat,
+        TileMode::Mirror => skia_safe::TileMode::Mirror,
+        TileMode::Decal => skia_safe::TileMode::Decal,
+    }
+}
 
-#[derive(Debug, Default)]
+///Splits the given `stops` into the colors and positions skia expects
+fn split_stops(stops: &[GradientStop]) -> (Vec<Color4f>, Vec<f32>) {
+    stops
+        .iter()
+        .map(|stop| (vec4f32_to_color_value(stop.color), stop.offset))
+        .unzip()
+}
+
+///Creates the shader of the given `fill`. Gradients are created on a 1x1 space, which is mapped to the bounds of what is drawn later
+fn fill_shader(fill: &Fill) -> Option<Shader> {
+    match fill {
+        Fill::Solid(_) => None,
+        Fill::LinearGradient {
+            start,
+            end,
+            stops,
+            tile_mode,
+        } => {
+            let (colors, positions) = split_stops(stops);
+            Shader::linear_gradient(
+                (Point::new(start.x, start.y), Point::new(end.x, end.y)),
+                colors.as_slice(),
+                positions.as_slice(),
+                to_skia_tile_mode(*tile_mode),
+                None,
+                None,
+            )
+        }
+        Fill::RadialGradient {
+            center,
+            radius,
+            stops,
+            tile_mode,
+        } => {
+            let (colors, positions) = split_stops(stops);
+            Shader::radial_gradient(
+                Point::new(center.x, center.y),
+                *radius,
+                colors.as_slice(),
+                positions.as_slice(),
+                to_skia_tile_mode(*tile_mode),
+                None,
+                None,
+            )
+        }
+        Fill::SweepGradient {
+            center,
+            start_angle,
+            end_angle,
+            stops,
+            tile_mode,
+        } => {
+            let (colors, positions) = split_stops(stops);
+            Shader::sweep_gradient(
+                Point::new(center.x, center.y),
+                colors.as_slice(),
+                positions.as_slice(),
+                to_skia_tile_mode(*tile_mode),
+                (*start_angle, *end_angle),
+                None,
+                None,
+            )
+        }
+        Fill::Pattern {
+            image,
+            scale,
+            tile_mode,
+            ..
+        } => {
+            let mode = to_skia_tile_mode(*tile_mode);
+            skia_image_of(image)?.to_shader(
+                (mode, mode),
+                SamplingOptions::default(),
+                &Matrix::scale((scale.x, scale.y)),
+            )
+        }
+        //its uniforms depend on the bounds, so it's only created when it's drawn
+        Fill::Shader(_) => None,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct DrawRule {
     pub border_color: Vector4<f32>,
     pub border_radius: Vector2<f32>,
     pub border_width: f32,
+    pub border_style: BorderStyle,
     pub inner: Paint,
+    fill: Fill,
+    ///Radius of each corner, when they're not all the same. When set, it's used instead of `border_radius`
+    corner_radii: Option<CornerRadii>,
+    ///Border of each side, when they're not all the same. When set, it's used instead of `border_width`, `border_color` and `border_style`
+    borders: Option<Borders>,
+    blend_mode: BlendMode,
+    color_filter: Option<ColorFilter>,
+    effects: Effects,
 }
 
 impl DrawRule {
@@ -29,6 +151,13 @@ impl DrawRule {
             border_width: 0.0,
             border_radius: Vector2::zeros(),
             border_color: Vector4::new(0.0, 0.0, 0.0, 0.0),
+            border_style: BorderStyle::Solid,
+            fill: Fill::default(),
+            corner_radii: None,
+            borders: None,
+            blend_mode: BlendMode::default(),
+            color_filter: None,
+            effects: Effects::default(),
         }
     }
 
@@ -36,45 +165,304 @@ impl DrawRule {
         unsafe { std::mem::transmute(self.inner.color4f()) }
     }
 
+    ///Sets the fill of this rule to be the given solid `color`
     pub fn set_color(&mut self, color: &Vector4<f32>) {
         self.inner.set_color4f(vec4f32_to_color(color), None);
+        self.inner.set_shader(None);
+        self.fill = Fill::Solid(*color);
     }
 
-    pub fn apply_effect(&mut self, effect: &dyn Effect, mut rect: Rect) {
-        let mut effects = Vec::new();
-        if let Some(shadow) = effect.shadow() {
-            rect = {
-                let d = shadow.offset; //delta
-                let b = shadow.blur; //blur
-                skia_safe::Rect::new(
-                    rect.left + d.x - b.x * 3.0,
-                    rect.top + d.y - b.y * 3.0,
-                    rect.right + d.x + b.x * 3.0,
-                    rect.bottom + d.y + b.y * 3.0,
-                )
+    #[inline]
+    ///Retrieves how what is drawn with this rule is filled
+    pub fn fill(&self) -> &Fill {
+        &self.fill
+    }
+
+    ///Sets how what is drawn with this rule is filled. When it's not a solid color, the color of this rule is set to be the representative
+    ///color of the fill, so whatever can't draw gradients or patterns still draws something close to it
+    pub fn set_fill(&mut self, fill: Fill) {
+        if let Fill::Solid(ref color) = fill {
+            self.set_color(color);
+            return;
+        }
+        let color = fill.representative_color();
+        //the shader is modulated by the alpha of the paint, so it must be opaque
+        self.inner.set_color4f(
+            vec4f32_to_color(&Vector4::new(color.x, color.y, color.z, 1.0)),
+            None,
+        );
+        self.inner.set_shader(fill_shader(&fill));
+        self.fill = fill;
+    }
+
+    ///Retrieves the paint used to draw something located at the given `bounds`. For gradients and patterns, their shader is moved
+    ///to the bounds, and runtime shaders are created for them. Otherwise the paint is used as it is
+    pub fn paint_for(&self, bounds: &Rect) -> Cow<'_, Paint> {
+        if let Fill::Shader(ref shader) = self.fill {
+            let shader = shader_builder(
+                shader,
+                Vector2::new(bounds.width(), bounds.height()),
+                Vector2::zeros(),
+            )
+            .and_then(|builder| builder.make_shader(&Matrix::translate((bounds.left, bounds.top))));
+            let mut paint = self.inner.clone();
+            //a shader that can't be created draws nothing, instead of its fallback, so the error is noticed
+            match shader {
+                Some(shader) => paint.set_shader(shader),
+                None => paint.set_alpha(0),
             };
-            effects.push(image_filters::drop_shadow(
-                Point::new(shadow.offset.x, shadow.offset.y),
-                (shadow.blur.x, shadow.blur.y),
-                vec4f32_to_color_value(shadow.color),
-                None,
-                None,
-                CropRect::from(rect),
+            return Cow::Owned(paint);
+        }
+        let Some(shader) = self.inner.shader() else {
+            return Cow::Borrowed(&self.inner);
+        };
+        let matrix = match self.fill {
+            Fill::Pattern { offset, .. } => {
+                Matrix::translate((bounds.left + offset.x, bounds.top + offset.y))
+            }
+            _ => {
+                let mut matrix = Matrix::translate((bounds.left, bounds.top));
+                matrix.pre_scale((bounds.width(), bounds.height()), None);
+                matrix
+            }
+        };
+        let mut paint = self.inner.clone();
+        paint.set_shader(shader.with_local_matrix(&matrix));
+        Cow::Owned(paint)
+    }
+
+    #[inline]
+    ///Retrieves how what is drawn with this rule is blended with what's below it
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    ///Sets how what is drawn with this rule, including its border, is blended with what's below it
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.inner.set_blend_mode(to_skia_blend_mode(mode));
+        self.blend_mode = mode;
+    }
+
+    #[inline]
+    ///Retrieves the filter the colors drawn with this rule go through
+    pub fn color_filter(&self) -> Option<&ColorFilter> {
+        self.color_filter.as_ref()
+    }
+
+    ///Sets the filter the colors drawn with this rule, including its border, go through. `None` removes it
+    pub fn set_color_filter(&mut self, filter: Option<ColorFilter>) {
+        self.inner.set_color_filter(
+            filter
+                .as_ref()
+                .map(|filter| color_filters::matrix_row_major(&filter.matrix(), None)),
+        );
+        self.color_filter = filter;
+    }
+
+    ///Creates the paint the border is stroked with. It's got the border color and width, as well as the blend mode and color filter of this rule
+    pub fn border_paint(&self) -> Paint {
+        let mut paint = Paint::new(vec4f32_to_color(&self.border_color), None);
+        paint
+            .set_anti_alias(true)
+            .set_style(skia_safe::PaintStyle::Stroke)
+            .set_stroke_width(self.border_width)
+            .set_blend_mode(to_skia_blend_mode(self.blend_mode));
+        if let Some(filter) = self.inner.color_filter() {
+            paint.set_color_filter(filter);
+        }
+        paint
+    }
+
+    ///Retrieves the radius of each corner of what is drawn with this rule
+    pub fn corner_radii(&self) -> CornerRadii {
+        self.corner_radii
+            .unwrap_or_else(|| CornerRadii::uniform(self.border_radius))
+    }
+
+    ///Sets the radius of each corner of what is drawn with this rule. `border_radius` becomes the top left one, for whatever
+    ///can only round every corner the same way
+    pub fn set_corner_radii(&mut self, radii: CornerRadii) {
+        self.border_radius = radii.top_left;
+        self.corner_radii = (!radii.is_uniform()).then_some(radii);
+    }
+
+    #[inline]
+    ///Retrieves whether every corner has the same radius, which is the `border_radius`
+    pub fn has_uniform_corners(&self) -> bool {
+        self.corner_radii.is_none()
+    }
+
+    ///Retrieves the border of each side of what is drawn with this rule
+    pub fn borders(&self) -> Borders {
+        self.borders.unwrap_or_else(|| {
+            Borders::uniform(BorderSide::new(
+                self.border_width,
+                self.border_color,
+                self.border_style,
             ))
+        })
+    }
+
+    ///Sets the border of each side of what is drawn with this rule. `border_width`, `border_color` and `border_style` become the ones of
+    ///the top side, for whatever can only draw the same border on every side
+    pub fn set_borders(&mut self, borders: Borders) {
+        self.border_width = borders.top.width;
+        self.border_color = borders.top.color;
+        self.border_style = borders.top.style;
+        self.borders = (!borders.is_uniform()).then_some(borders);
+    }
+
+    #[inline]
+    ///Retrieves whether every side has the same border, which is the one made of `border_width`, `border_color` and `border_style`
+    pub fn has_uniform_borders(&self) -> bool {
+        self.borders.is_none()
+    }
+
+    ///Creates the paint the given border `side` is stroked with, with the blend mode and color filter of this rule.
+    ///A double border is stroked by this paint twice, so its width is a third of the side's one
+    pub fn side_paint(&self, side: &BorderSide) -> Paint {
+        let mut paint = self.border_paint();
+        paint
+            .set_color4f(vec4f32_to_color(&side.color), None)
+            .set_stroke_width(match side.style {
+                BorderStyle::Double => side.width / 3.0,
+                _ => side.width,
+            });
+        let dash = side.style.dash_pattern(side.width);
+        if !dash.is_empty() {
+            paint.set_path_effect(PathEffect::dash(&dash, 0.0));
+        }
+        if side.style == BorderStyle::Dotted {
+            paint.set_stroke_cap(skia_safe::paint::Cap::Round);
+        }
+        paint
+    }
+
+    ///Retrieves whether anything is drawn with this rule: a fill that isn't fully transparent, a visible border or an effect
+    pub fn is_visible(&self) -> bool {
+        let filled = match self.fill {
+            Fill::Solid(ref color) => color.w != 0.0,
+            _ => true,
         };
-        if effects.is_empty() {
-            return;
+        filled || self.borders().is_visible() || !self.effects.is_empty()
+    }
+
+    #[inline]
+    ///Retrieves the effects of what is drawn with this rule
+    pub fn effects(&self) -> &Effects {
+        &self.effects
+    }
+
+    #[inline]
+    ///Sets the effects of what is drawn with this rule
+    pub fn set_effects(&mut self, effects: Effects) {
+        self.effects = effects;
+    }
+
+    #[inline]
+    ///Reads every effect of the given `effect` into this rule
+    pub fn apply_effect(&mut self, effect: &dyn Effect) {
+        self.effects = Effects::from_effect(effect);
+    }
+
+    ///Creates the filter the layer of something drawn with this rule at the given `bounds` goes through, to draw its shadows, glow,
+    ///blur and shader. The filter is cropped to the area the effects cover around the bounds. `None` if there's no such effect
+    pub fn image_filter_for(&self, bounds: &Rect) -> Option<ImageFilter> {
+        let effects = &self.effects;
+        if !effects.needs_layer() {
+            return None;
+        }
+        let area = effects.bounds(&candy_shared_types::Rect::new(
+            bounds.left,
+            bounds.top,
+            bounds.width(),
+            bounds.height(),
+        ));
+        let crop = CropRect::from(Rect::from_xywh(area.x, area.y, area.width, area.height));
+        let source = effects
+            .blur
+            .and_then(|blur| image_filters::blur((blur.x, blur.y), None, None, crop));
+        let filter = if effects.shadows.is_empty() && effects.glow.is_none() {
+            source
+        } else {
+            self.shadows_filter(source, crop)
+        };
+        let Some(ref shader) = effects.shader else {
+            return filter;
+        };
+        //a shader that can't be created is skipped, but the other effects are still drawn
+        match shader_builder(
+            shader,
+            Vector2::new(bounds.width(), bounds.height()),
+            Vector2::new(bounds.left, bounds.top),
+        ) {
+            Some(builder) => image_filters::runtime_shader(&builder, "content", filter),
+            None => filter,
+        }
+    }
+
+    ///Retrieves the area covered by something at `bounds` drawn with this rule, including its borders, stroked over its edges, and the
+    ///area its effects cover. It's a pixel bigger on every side, for the antialiasing of the edges
+    pub fn visual_bounds(&self, bounds: &candy_shared_types::Rect) -> candy_shared_types::Rect {
+        let borders = self.borders();
+        let border = [
+            borders.top.width,
+            borders.right.width,
+            borders.bottom.width,
+            borders.left.width,
+        ]
+        .into_iter()
+        .fold(0.0, f32::max);
+        self.effects.bounds(bounds).outset(border + 1.0)
+    }
+
+    ///Creates the filter that draws the glow and shadows of this rule below the given `source`, which is the content itself if `None`
+    fn shadows_filter(&self, source: Option<ImageFilter>, crop: CropRect) -> Option<ImageFilter> {
+        let effects = &self.effects;
+        //a `None` filter is the source itself, so only the shadows that could be created are kept
+        let mut filters = Vec::with_capacity(effects.shadows.len() + 2);
+        if let Some(glow) = effects.glow {
+            filters.extend(
+                image_filters::drop_shadow_only(
+                    Point::new(0.0, 0.0),
+                    (glow.radius, glow.radius),
+                    vec4f32_to_color_value(glow.color),
+                    None,
+                    None,
+                    crop,
+                )
+                .map(Some),
+            );
+        }
+        for shadow in &effects.shadows {
+            filters.extend(
+                image_filters::drop_shadow_only(
+                    Point::new(shadow.offset.x, shadow.offset.y),
+                    (shadow.blur.x, shadow.blur.y),
+                    vec4f32_to_color_value(shadow.color),
+                    None,
+                    None,
+                    crop,
+                )
+                .map(Some),
+            );
         }
-        let out = image_filters::merge(effects, CropRect::from(&rect));
-        self.inner.set_image_filter(out);
+        //drawn above every shadow
+        filters.push(source);
+        image_filters::merge(filters, crop)
     }
 
     pub fn apply_style(&mut self, style: &dyn Style) {
-        self.apply_effect(&*style.effect(), Rect::new(0.0, 0.0, 2000.0, 2000.0));
+        self.apply_effect(&*style.effect());
 
-        self.set_color(&style.background_color());
+        self.set_fill(style.background_fill());
         self.border_color = style.border_color();
         self.border_radius = style.border_radius();
         self.border_width = style.border_width();
+        self.border_style = style.border_style();
+        self.set_corner_radii(style.corner_radii());
+        self.set_borders(style.borders());
+        self.set_blend_mode(style.blend_mode());
+        self.set_color_filter(style.color_filter());
     }
 }
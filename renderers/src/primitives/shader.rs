@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+
+use candy_shared_types::{RuntimeShader, Uniform};
+use nalgebra::Vector2;
+use skia_safe::{RuntimeEffect, runtime_effect::RuntimeShaderBuilder};
+
+use super::LruCache;
+
+///The bytes the compiled shaders are kept on, counting each one as its source and [COMPILED_SHADER_BYTES]. The least recently used
+///ones are compiled again when they go over it
+const SHADER_CACHE_BUDGET: usize = 1024 * 1024;
+
+///About how many bytes a compiled shader takes besides its source
+const COMPILED_SHADER_BYTES: usize = 4 * 1024;
+
+thread_local! {
+    ///The shaders compiled recently, by their source. The ones that couldn't be compiled keep the error, so they aren't compiled again
+    static COMPILED: RefCell<LruCache<String, Result<RuntimeEffect, String>>> =
+        RefCell::new(LruCache::new(SHADER_CACHE_BUDGET));
+}
+
+///Retrieves the compiled effect of the given SkSL `source`, compiling it only if it wasn't requested recently
+fn runtime_effect(source: &str) -> Result<RuntimeEffect, String> {
+    COMPILED.with_borrow_mut(|compiled| {
+        if let Some(effect) = compiled.get(source) {
+            return effect.clone();
+        }
+        let effect = RuntimeEffect::make_for_shader(source, None);
+        let bytes = source.len() + COMPILED_SHADER_BYTES;
+        compiled.insert(source.to_string(), effect, bytes).clone()
+    })
+}
+
+///Compiles the given `shader`, so its errors can be known before it's used. Shaders that can't be compiled are not drawn
+pub fn compile_shader(shader: &RuntimeShader) -> Result<(), String> {
+    runtime_effect(shader.source()).map(|_| ())
+}
+
+///Creates the builder of the given `shader`, with its uniforms and the `resolution` and `origin` ones set. Uniforms the shader doesn't
+///declare are skipped, so the same ones can be set on many shaders. `None` if it couldn't be compiled, or if an uniform has got the
+///wrong type
+pub(crate) fn shader_builder(
+    shader: &RuntimeShader,
+    resolution: Vector2<f32>,
+    origin: Vector2<f32>,
+) -> Option<RuntimeShaderBuilder> {
+    let effect = runtime_effect(shader.source()).ok()?;
+    let mut builder = RuntimeShaderBuilder::new(effect.clone());
+    let builtin = [
+        ("resolution", Uniform::Float2(resolution)),
+        ("origin", Uniform::Float2(origin)),
+    ];
+    let uniforms = builtin.iter().map(|(name, value)| (*name, value)).chain(
+        shader
+            .uniforms()
+            .iter()
+            .map(|(name, value)| (name.as_str(), value)),
+    );
+    for (name, value) in uniforms {
+        if effect.find_uniform(name).is_none() {
+            continue;
+        }
+        match value {
+            Uniform::Float(value) => builder.set_uniform_float(name, &[*value]),
+            Uniform::Float2(value) => builder.set_uniform_float(name, value.as_slice()),
+            Uniform::Float3(value) => builder.set_uniform_float(name, value.as_slice()),
+            Uniform::Float4(value) => builder.set_uniform_float(name, value.as_slice()),
+            Uniform::Int(value) => builder.set_uniform_int(name, &[*value]),
+        }
+        .ok()?;
+    }
+    Some(builder)
+}
@@ -1,14 +1,15 @@
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, DirtyArea, Rect, Style};
 use nalgebra::Vector2;
 
 use crate::primitives::DrawRule;
 
 ///A handler that contains information about how a square should be drawn.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct CandySquare {
     position: Vector2<f32>,
     size: Vector2<f32>,
-    pub rule: DrawRule,
+    rule: DrawRule,
+    dirty: DirtyArea,
 }
 
 impl CandySquare {
@@ -18,21 +19,38 @@ impl CandySquare {
             position,
             size,
             rule,
+            dirty: DirtyArea::default(),
         }
     }
 
     ///Gets the position of this square
     ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
     pub fn position_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
         &mut self.position
     }
 
     ///Gets the actual size of this square
-    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty    ///
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
     pub fn size_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
         &mut self.size
     }
 
+    #[inline]
+    ///Gets how this square is drawn
+    pub fn rule(&self) -> &DrawRule {
+        &self.rule
+    }
+
+    #[inline]
+    ///Gets how this square is drawn
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn rule_mut(&mut self) -> &mut DrawRule {
+        self.mark_dirty();
+        &mut self.rule
+    }
+
     ///Gets the position of this square
     pub fn position(&self) -> &Vector2<f32> {
         &self.position
@@ -44,6 +62,9 @@ impl CandySquare {
     }
 
     pub fn resize(&mut self, rect: Rect) {
+        if rect != self.bounds() {
+            self.mark_dirty();
+        }
         self.size.x = rect.width;
         self.size.y = rect.height;
         self.position.x = rect.x;
@@ -62,6 +83,7 @@ impl CandySquare {
     ///Applies the given style to this square
     #[inline]
     pub fn apply_style(&mut self, style: &dyn Style) {
+        self.mark_dirty();
         self.rule.apply_style(style);
     }
 
@@ -70,4 +92,31 @@ impl CandySquare {
         self.rule.apply_style(style);
         self
     }
+
+    ///Retrieves the area covered when this square is drawn, with its borders and effects
+    pub fn visual_bounds(&self) -> Rect {
+        self.rule.visual_bounds(&self.bounds())
+    }
+
+    ///Marks this square as changed, so both the area it was drawn on and the one it covers now are repainted
+    pub fn mark_dirty(&mut self) {
+        if !self.dirty.is_dirty() {
+            self.dirty.mark(self.visual_bounds());
+        }
+    }
+
+    #[inline]
+    ///Checks whether this square changed since its damage was last taken
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    ///Adds the area this square was drawn on and the one it covers now to `damage`, if it changed since the last time, and marks it as
+    ///clean
+    pub fn take_damage(&mut self, damage: &mut Damage) {
+        if self.dirty.is_dirty() {
+            let current = self.visual_bounds();
+            self.dirty.take(current, damage);
+        }
+    }
 }
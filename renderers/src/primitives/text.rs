@@ -1,16 +1,23 @@
-use candy_shared_types::{Rect, Style, vec4f32_to_color};
+use std::cell::{Ref, RefCell};
+
+use candy_shared_types::{Damage, DirtyArea, Rect, Style};
 use nalgebra::{Vector2, Vector4};
 
-use crate::primitives::{CandyFont, DrawRule};
+use crate::primitives::{CandyFont, DrawRule, ParagraphLayout, ParagraphStyle};
 
 #[derive(Debug, Default)]
-///A handler that contains on how to draw an specific text
+///A handler that contains on how to draw an specific text. By default it's a single line whose baseline starts at its position. With
+///a [ParagraphStyle] it's laid out as a paragraph on the box given by its position, which is the top left corner, and its size
 pub struct CandyText {
     font: CandyFont,
     text: String,
     position: Vector2<f32>,
     size: Vector2<f32>,
     pub(crate) rule: DrawRule,
+    dirty: DirtyArea,
+    paragraph: Option<ParagraphStyle>,
+    ///The lines the content is broken into, computed when they're needed and forgotten whenever the text changes
+    layout: RefCell<Option<ParagraphLayout>>,
 }
 
 impl CandyText {
@@ -25,6 +32,148 @@ impl CandyText {
                 rule.set_color(&Vector4::new(1.0, 1.0, 1.0, 1.0));
                 rule
             },
+            dirty: DirtyArea::default(),
+            paragraph: None,
+            layout: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    ///Lays out this text as a paragraph following `style`, or as a single line if it's None
+    pub fn set_paragraph(&mut self, style: Option<ParagraphStyle>) -> &mut Self {
+        self.mark_dirty();
+        self.paragraph = style;
+        self
+    }
+
+    #[inline]
+    pub fn with_paragraph(mut self, style: ParagraphStyle) -> Self {
+        self.paragraph = Some(style);
+        self
+    }
+
+    #[inline]
+    ///Retrieves how this text is laid out as a paragraph, if it is
+    pub fn paragraph(&self) -> Option<&ParagraphStyle> {
+        self.paragraph.as_ref()
+    }
+
+    ///Retrieves how this text is laid out as a paragraph, if it is
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
+    pub fn paragraph_mut(&mut self) -> Option<&mut ParagraphStyle> {
+        self.mark_dirty();
+        self.paragraph.as_mut()
+    }
+
+    ///Retrieves the lines of this text laid out on its box, if it's a paragraph. The width of the box is where lines are wrapped, or
+    ///none is if it's 0
+    pub fn layout(&self) -> Option<Ref<'_, ParagraphLayout>> {
+        let style = self.paragraph.as_ref()?;
+        if self.layout.borrow().is_none() {
+            *self.layout.borrow_mut() = Some(ParagraphLayout::new(
+                &self.text,
+                &self.font,
+                Some(&self.rule.inner),
+                style,
+                self.size,
+            ));
+        }
+        Ref::filter_map(self.layout.borrow(), Option::as_ref).ok()
+    }
+
+    ///Retrieves the size this text would take if it could be `max_width` wide at most, which is the width of its widest line and the
+    ///height of all of them. Without a paragraph style it's a single line as wide as its content
+    pub fn measure(&self, max_width: Option<f32>) -> Vector2<f32> {
+        let Some(style) = &self.paragraph else {
+            let (spacing, _) = self.font.metrics();
+            return Vector2::new(self.text_width(), spacing);
+        };
+        ParagraphLayout::new(
+            &self.text,
+            &self.font,
+            Some(&self.rule.inner),
+            style,
+            Vector2::new(max_width.unwrap_or(0.0), 0.0),
+        )
+        .size()
+    }
+
+    #[inline]
+    ///Retrieves the height this text would take if it was laid out `width` wide
+    pub fn height_for_width(&self, width: f32) -> f32 {
+        self.measure(Some(width)).y
+    }
+
+    #[inline]
+    ///Retrieves the amount of lines shown. A text that isn't a paragraph has got a single one
+    pub fn line_count(&self) -> usize {
+        self.layout().map_or(1, |layout| layout.line_count())
+    }
+
+    ///Retrieves every piece of this text that is drawn at once, with where its baseline starts on the GUI
+    pub fn runs(&self) -> Vec<(&str, Vector2<f32>)> {
+        let Some(layout) = self.layout() else {
+            return vec![(self.text.as_str(), self.position)];
+        };
+        let mut runs = Vec::new();
+        for line in layout.lines() {
+            for run in &line.runs {
+                runs.push((&self.text[run.range.clone()], self.position + run.origin));
+            }
+            if let (Some(ellipsis), Some(style)) = (line.ellipsis, &self.paragraph) {
+                runs.push((style.ellipsis.as_str(), self.position + ellipsis));
+            }
+        }
+        runs
+    }
+
+    ///Retrieves where a caret placed before the byte `index` of the content is drawn on the GUI. On a paragraph it's the top left
+    ///corner of the caret, otherwise it's on the baseline
+    pub fn caret_position(&self, index: usize) -> Vector2<f32> {
+        let index = index.min(self.text.len());
+        match self.layout() {
+            Some(layout) => {
+                self.position
+                    + layout.caret_position(&self.text, &self.font, Some(&self.rule.inner), index)
+            }
+            None => Vector2::new(
+                self.position.x
+                    + self
+                        .font
+                        .measure_str(&self.text[..index], Some(&self.rule.inner))
+                        .0,
+                self.position.y,
+            ),
+        }
+    }
+
+    ///Retrieves the area the text is clipped to when drawn. A single line can go up to a font size above and to the left of its
+    ///position, as it's where its baseline starts. A paragraph is clipped to its box, or to its lines on the sides it's got no size
+    pub fn clip_bounds(&self) -> Rect {
+        match self.layout() {
+            Some(layout) => Rect::new(
+                self.position.x,
+                self.position.y,
+                if self.size.x > 0.0 {
+                    self.size.x
+                } else {
+                    layout.width()
+                },
+                if self.size.y > 0.0 {
+                    self.size.y
+                } else {
+                    layout.height()
+                },
+            ),
+            None => {
+                let font_size = self.font.size();
+                Rect::new(
+                    self.position.x - font_size,
+                    self.position.y - font_size,
+                    self.size.x + font_size,
+                    self.size.y + font_size,
+                )
+            }
         }
     }
 
@@ -35,8 +184,10 @@ impl CandyText {
     }
 
     ///Gets the content of this text
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
     #[inline]
     pub fn content_mut(&mut self) -> &mut String {
+        self.mark_dirty();
         &mut self.text
     }
 
@@ -47,7 +198,9 @@ impl CandyText {
     }
 
     ///Gets the inner position of this text
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
     pub fn size_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
         &mut self.size
     }
 
@@ -58,7 +211,9 @@ impl CandyText {
     }
 
     ///Gets the inner position of this text
+    ///Obs: As this gets mutable, this code assumes the data will be changed, so, this is marked as dirty
     pub fn position_mut(&mut self) -> &mut Vector2<f32> {
+        self.mark_dirty();
         &mut self.position
     }
 
@@ -70,6 +225,9 @@ impl CandyText {
 
     #[inline]
     pub fn resize(&mut self, rect: Rect) {
+        if rect != self.bounds() {
+            self.mark_dirty();
+        }
         self.position.x = rect.x;
         self.position.y = rect.y;
         self.size.x = rect.width;
@@ -112,18 +270,64 @@ impl CandyText {
     ///Applies the given style to this square
     #[inline]
     pub fn apply_style(&mut self, style: &dyn Style) {
+        self.mark_dirty();
         self.rule.apply_style(style);
-        self.rule
-            .inner
-            .set_color4f(vec4f32_to_color(&style.color()), None);
+        self.rule.set_fill(style.color_fill());
     }
 
     #[inline]
     pub fn with_style(mut self, style: &dyn Style) -> Self {
         self.rule.apply_style(style);
-        self.rule
-            .inner
-            .set_color4f(vec4f32_to_color(&style.color()), None);
+        self.rule.set_fill(style.color_fill());
         self
     }
+
+    ///Retrieves the area covered by the glyphs of this text when it's drawn, with its effects
+    pub fn visual_bounds(&self) -> Rect {
+        if let Some(layout) = self.layout() {
+            let lines = layout.lines();
+            let left = lines.iter().map(|line| line.x).fold(0.0, f32::min);
+            let right = lines
+                .iter()
+                .map(|line| line.x + line.width)
+                .fold(0.0, f32::max);
+            let top = lines.first().map_or(0.0, |line| line.top);
+            return self.rule.visual_bounds(&Rect::new(
+                self.position.x + left,
+                self.position.y + top,
+                right - left,
+                layout.line_height() * lines.len() as f32,
+            ));
+        }
+        let glyphs = self.text_bounds();
+        self.rule.visual_bounds(&Rect::new(
+            glyphs.x + self.position.x,
+            glyphs.y + self.position.y,
+            glyphs.width,
+            glyphs.height,
+        ))
+    }
+
+    ///Marks this text as changed, so both the area it was drawn on and the one it covers now are repainted
+    pub fn mark_dirty(&mut self) {
+        if !self.dirty.is_dirty() {
+            self.dirty.mark(self.visual_bounds());
+        }
+        self.layout.take();
+    }
+
+    #[inline]
+    ///Checks whether this text changed since its damage was last taken
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_dirty()
+    }
+
+    ///Adds the area this text was drawn on and the one it covers now to `damage`, if it changed since the last time, and marks it as
+    ///clean
+    pub fn take_damage(&mut self, damage: &mut Damage) {
+        if self.dirty.is_dirty() {
+            let current = self.visual_bounds();
+            self.dirty.take(current, damage);
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{ThreeDimensionalRenderer, ThreeDimensionalRendererConstructor};
 
+#[derive(Debug, Default)]
 pub struct Candy3DefaultRenderer {}
 
 impl ThreeDimensionalRenderer for Candy3DefaultRenderer {}
@@ -0,0 +1,225 @@
+use crate::Rect;
+
+///Most rects a damage keeps apart. When there would be more, they're merged into their bounds, as clipping and presenting many small
+///rects costs more than repainting a bit more
+const MAX_DAMAGE_RECTS: usize = 8;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+///The regions of the window that changed since it was last drawn, so only them are repainted and presented. Rects that overlap are
+///merged, and each one is rounded out to whole pixels
+pub struct Damage {
+    rects: Vec<Rect>,
+    ///Whether everything changed, such as when the window is resized
+    full: bool,
+}
+
+impl Damage {
+    #[inline]
+    ///Creates a damage where nothing changed yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Creates a damage where everything changed
+    pub fn full() -> Self {
+        Self {
+            rects: Vec::new(),
+            full: true,
+        }
+    }
+
+    ///Adds `rect` to the regions that changed. Empty rects are ignored
+    pub fn add(&mut self, rect: Rect) -> &mut Self {
+        if self.full || rect.is_empty() || !rect.right().is_finite() || !rect.bottom().is_finite() {
+            return self;
+        }
+        let mut rect = rect.round_out();
+        //merging two rects may make the result overlap others, so it's repeated until nothing overlaps
+        while let Some(index) = self.rects.iter().position(|other| other.intersects(&rect)) {
+            rect = rect.union(&self.rects.swap_remove(index));
+        }
+        self.rects.push(rect);
+        if self.rects.len() > MAX_DAMAGE_RECTS {
+            let bounds = self.bounds();
+            self.rects.clear();
+            self.rects.extend(bounds);
+        }
+        self
+    }
+
+    #[inline]
+    ///Marks everything as changed
+    pub fn invalidate_all(&mut self) -> &mut Self {
+        self.full = true;
+        self.rects.clear();
+        self
+    }
+
+    ///Adds every region that changed on `other` to this damage
+    pub fn merge(&mut self, other: &Damage) -> &mut Self {
+        if other.full {
+            return self.invalidate_all();
+        }
+        for rect in &other.rects {
+            self.add(rect.clone());
+        }
+        self
+    }
+
+    #[inline]
+    ///Checks whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.rects.is_empty()
+    }
+
+    #[inline]
+    ///Checks whether everything changed
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    #[inline]
+    ///Retrieves the rects that changed. It's empty when everything changed, so check [Self::is_full] first
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    ///Retrieves the smallest rect that contains every region that changed. `None` if nothing did or if everything did
+    pub fn bounds(&self) -> Option<Rect> {
+        self.rects
+            .iter()
+            .cloned()
+            .reduce(|out, rect| out.union(&rect))
+    }
+
+    ///Retrieves the rects that changed inside of a surface of `width` x `height` pixels. If everything changed, it's the whole surface
+    pub fn rects_within(&self, width: f32, height: f32) -> Vec<Rect> {
+        let surface = Rect::new(0.0, 0.0, width, height);
+        if self.full {
+            return (!surface.is_empty())
+                .then_some(surface)
+                .into_iter()
+                .collect();
+        }
+        self.rects
+            .iter()
+            .filter_map(|rect| rect.intersection(&surface))
+            .collect()
+    }
+
+    #[inline]
+    ///Marks nothing as changed
+    pub fn clear(&mut self) {
+        self.full = false;
+        self.rects.clear();
+    }
+
+    #[inline]
+    ///Retrieves everything that changed, leaving this damage as if nothing did
+    pub fn take(&mut self) -> Self {
+        std::mem::take(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///Tracks the area something covered when it was last drawn, from the first change made to it after, so both where it was and where
+///it is now are damaged. Something new is dirty, so it's drawn for the first time
+pub struct DirtyArea {
+    dirty: bool,
+    ///The area covered when it was last drawn. Empty if it was never drawn
+    drawn: Rect,
+}
+
+impl Default for DirtyArea {
+    fn default() -> Self {
+        Self {
+            dirty: true,
+            drawn: Rect::default(),
+        }
+    }
+}
+
+impl DirtyArea {
+    #[inline]
+    ///Checks whether something changed since its damage was last taken
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    #[inline]
+    ///Marks as changed what was covering `drawn` when it was last drawn. If it was already changed, the area kept is the one from before
+    ///the first change
+    pub fn mark(&mut self, drawn: Rect) {
+        if !self.dirty {
+            self.dirty = true;
+            self.drawn = drawn;
+        }
+    }
+
+    ///Adds where it was drawn and the `current` area it covers to `damage`, if it changed, and marks it as clean
+    pub fn take(&mut self, current: Rect, damage: &mut Damage) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        damage.add(std::mem::take(&mut self.drawn));
+        damage.add(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_rects_are_merged() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0.0, 0.0, 10.0, 10.0));
+        damage.add(Rect::new(5.0, 5.0, 10.0, 10.0));
+        assert_eq!(damage.rects(), &[Rect::new(0.0, 0.0, 15.0, 15.0)]);
+    }
+
+    #[test]
+    fn merging_repeats_until_nothing_overlaps() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0.0, 0.0, 10.0, 10.0));
+        damage.add(Rect::new(20.0, 0.0, 10.0, 4.0));
+        assert_eq!(damage.rects().len(), 2);
+        //overlaps only the first one, but their union overlaps the second one too
+        damage.add(Rect::new(5.0, 5.0, 20.0, 5.0));
+        assert_eq!(damage.rects(), &[Rect::new(0.0, 0.0, 30.0, 10.0)]);
+    }
+
+    #[test]
+    fn rects_are_rounded_out_and_empty_ones_ignored() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0.0, 0.0, 0.0, 10.0));
+        damage.add(Rect::new(0.0, 0.0, f32::INFINITY, 10.0));
+        assert!(damage.is_empty());
+        damage.add(Rect::new(0.5, 1.25, 2.0, 2.0));
+        assert_eq!(damage.rects(), &[Rect::new(0.0, 1.0, 3.0, 3.0)]);
+    }
+
+    #[test]
+    fn too_many_rects_become_their_bounds() {
+        let mut damage = Damage::new();
+        for index in 0..=MAX_DAMAGE_RECTS {
+            damage.add(Rect::new(index as f32 * 20.0, 0.0, 10.0, 10.0));
+        }
+        let right = MAX_DAMAGE_RECTS as f32 * 20.0 + 10.0;
+        assert_eq!(damage.rects(), &[Rect::new(0.0, 0.0, right, 10.0)]);
+    }
+
+    #[test]
+    fn full_damage_ignores_rects() {
+        let mut damage = Damage::full();
+        damage.add(Rect::new(0.0, 0.0, 10.0, 10.0));
+        assert!(damage.is_full());
+        assert!(damage.rects().is_empty());
+        assert_eq!(
+            damage.rects_within(4.0, 2.0),
+            vec![Rect::new(0.0, 0.0, 4.0, 2.0)]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, Ordering},
+};
+
+use crate::Rect;
+
+///Most changes an image remembers, so the copies renderers keep of it can be updated only where it changed. Renderers whose copy is
+///older than that read the whole image again
+const MAX_IMAGE_CHANGES: usize = 8;
+
+///Source of the ids of the images, so each one created gets a different one
+static NEXT_IMAGE_ID: AtomicU32 = AtomicU32::new(1);
+
+#[derive(Clone)]
+///The pixels of an image, as premultiplied RGBA, row by row. It doesn't belong to any renderer: each one converts it to its own image
+///the first time it's drawn and caches it by [Self::unique_id], updating only the regions that changed since then.
+///Clones share the same pixels until one of them is changed
+pub struct RendererImage {
+    id: u32,
+    width: u32,
+    height: u32,
+    pixels: Arc<Vec<u8>>,
+    ///How many times the pixels were changed in place
+    generation: u64,
+    ///The last regions that were changed, each one with the generation it led to
+    changes: Vec<(u64, Rect)>,
+}
+
+impl std::fmt::Debug for RendererImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RendererImage")
+            .field("id", &self.id)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for RendererImage {
+    ///Images are the same when they've got the same id and were changed the same amount of times, without comparing their pixels
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.generation == other.generation
+    }
+}
+
+impl RendererImage {
+    ///Creates an image with the given size from its premultiplied RGBA `pixels`, row by row. `None` if it's empty or there aren't
+    ///`width * height` pixels
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        if width == 0 || height == 0 || pixels.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            id: NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels: Arc::new(pixels),
+            generation: 0,
+            changes: Vec::new(),
+        })
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    ///Retrieves the id of this image, shared by its clones while they have got the same pixels. Renderers cache what they create from
+    ///an image by it
+    pub fn unique_id(&self) -> u32 {
+        self.id
+    }
+
+    #[inline]
+    ///Retrieves the premultiplied RGBA pixels of this image, row by row
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[inline]
+    ///Retrieves how many times the pixels of this image were changed in place
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    ///Retrieves the region that changed since the given `generation`, in pixels. It's empty if nothing changed, and `None` if it's too
+    ///old to be known, when the whole image must be read again
+    pub fn changes_since(&self, generation: u64) -> Option<Rect> {
+        if generation == self.generation {
+            return Some(Rect::default());
+        }
+        let oldest = self.changes.first()?.0;
+        if generation > self.generation || generation + 1 < oldest {
+            return None;
+        }
+        self.changes
+            .iter()
+            .filter(|(changed, _)| *changed > generation)
+            .map(|(_, rect)| rect.clone())
+            .reduce(|acc, rect| acc.union(&rect))
+    }
+
+    ///Copies into `target`, with the same size and layout of the pixels of this image, the region that changed since the given
+    ///`generation`, or everything if it's too old to be known. Returns the region copied, in pixels, and `None` if `target` has got
+    ///another size
+    pub fn copy_changes_since(&self, generation: u64, target: &mut [u8]) -> Option<Rect> {
+        if target.len() != self.pixels.len() {
+            return None;
+        }
+        let Some(region) = self.changes_since(generation) else {
+            target.copy_from_slice(&self.pixels);
+            return Some(Rect::new(0.0, 0.0, self.width as f32, self.height as f32));
+        };
+        let stride = self.width as usize * 4;
+        let (x, y) = (region.x as usize, region.y as usize);
+        let (width, height) = (region.width as usize, region.height as usize);
+        for row in y..y + height {
+            let start = row * stride + x * 4;
+            let end = start + width * 4;
+            target[start..end].copy_from_slice(&self.pixels[start..end]);
+        }
+        Some(region)
+    }
+
+    ///Copies the region at (`x`, `y`) with the given `width` and `height` from `pixels`, premultiplied RGBA with the same size of this
+    ///image. The pixels are changed in place unless a clone shares them, in which case this image gets a copy of them with a new id.
+    ///Returns whether it could be done
+    pub fn update(&mut self, pixels: &[u8], x: u32, y: u32, width: u32, height: u32) -> bool {
+        if pixels.len() != self.pixels.len() {
+            return false;
+        }
+        let (x, y) = (x.min(self.width), y.min(self.height));
+        let (width, height) = (width.min(self.width - x), height.min(self.height - y));
+        if width == 0 || height == 0 {
+            return true;
+        }
+        if Arc::get_mut(&mut self.pixels).is_none() {
+            self.id = NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed);
+            self.changes.clear();
+        }
+        let stride = self.width as usize * 4;
+        let target = Arc::make_mut(&mut self.pixels);
+        for row in y as usize..(y + height) as usize {
+            let start = row * stride + x as usize * 4;
+            let end = start + width as usize * 4;
+            target[start..end].copy_from_slice(&pixels[start..end]);
+        }
+        self.generation += 1;
+        if self.changes.len() == MAX_IMAGE_CHANGES {
+            self.changes.remove(0);
+        }
+        self.changes.push((
+            self.generation,
+            Rect::new(x as f32, y as f32, width as f32, height as f32),
+        ));
+        true
+    }
+}
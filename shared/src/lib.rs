@@ -1,6 +1,10 @@
 //This crate contains only the abstractions over `renderer` and the most higher level features used on the library
+mod damage;
+mod image;
 mod rect;
 mod ui;
+pub use damage::*;
+pub use image::*;
 use nalgebra::Vector4;
 pub use rect::*;
 use skia_safe::Color4f;
@@ -20,8 +24,3 @@ pub fn vec4f32_to_color_value(color: Vector4<f32>) -> Color4f {
 pub fn vec4f32_to_rect(color: &Vector4<f32>) -> &Rect {
     unsafe { std::mem::transmute::<&Vector4<f32>, &Rect>(color) }
 }
-
-#[cfg(feature = "skia")]
-pub type RendererImage = skia_safe::Image;
-#[cfg(feature = "vello")]
-pub type RendererImage = ();
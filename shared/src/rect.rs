@@ -1,4 +1,4 @@
-use nalgebra::Vector2;
+use nalgebra::{Matrix3, Point2, Vector2};
 
 #[repr(C)]
 #[derive(Default, Debug, Clone, PartialEq)]
@@ -44,4 +44,78 @@ impl Rect {
     pub fn center(&self) -> Vector2<f32> {
         Vector2::new(self.x + self.width * 0.5, self.y + self.height * 0.5)
     }
+
+    #[inline]
+    ///Checks whether this rect covers no area at all
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    ///Retrieves the smallest rect that contains both this one and `other`
+    pub fn union(&self, other: &Rect) -> Rect {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    ///Retrieves the area covered by both this rect and `other`. `None` if they don't overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let out = Rect::new(
+            x,
+            y,
+            self.right().min(other.right()) - x,
+            self.bottom().min(other.bottom()) - y,
+        );
+        (!out.is_empty()).then_some(out)
+    }
+
+    #[inline]
+    ///Checks whether this rect and `other` overlap
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    ///Grows this rect by `amount` on every side
+    pub fn outset(&self, amount: f32) -> Rect {
+        Rect::new(
+            self.x - amount,
+            self.y - amount,
+            self.width + amount * 2.0,
+            self.height + amount * 2.0,
+        )
+    }
+
+    ///Retrieves the smallest rect of whole pixels that contains this one
+    pub fn round_out(&self) -> Rect {
+        let (x, y) = (self.x.floor(), self.y.floor());
+        Rect::new(x, y, self.right().ceil() - x, self.bottom().ceil() - y)
+    }
+
+    ///Retrieves the bounds of this rect after being transformed by the 2D homogeneous `matrix`
+    pub fn transformed(&self, matrix: &Matrix3<f32>) -> Rect {
+        let corners = [
+            Point2::new(self.x, self.y),
+            Point2::new(self.right(), self.y),
+            Point2::new(self.x, self.bottom()),
+            Point2::new(self.right(), self.bottom()),
+        ]
+        .map(|corner| matrix.transform_point(&corner));
+        let (mut left, mut top) = (f32::INFINITY, f32::INFINITY);
+        let (mut right, mut bottom) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for corner in corners {
+            left = left.min(corner.x);
+            top = top.min(corner.y);
+            right = right.max(corner.x);
+            bottom = bottom.max(corner.y);
+        }
+        Rect::new(left, top, right - left, bottom - top)
+    }
 }
@@ -0,0 +1,57 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the colors of something drawn are combined with the ones already below it
+pub enum BlendMode {
+    ///Draws above what's below, the usual way
+    #[default]
+    SrcOver,
+    ///Draws only what's below, so nothing changes
+    Dst,
+    ///Replaces what's below
+    Src,
+    ///Clears what's below
+    Clear,
+    ///Adds both colors
+    Plus,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    ///Retrieves the name of this blend mode as used by CSS and SVG, if it's got one
+    pub fn css_name(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::SrcOver => "normal",
+            Self::Plus => "plus-lighter",
+            Self::Multiply => "multiply",
+            Self::Screen => "screen",
+            Self::Overlay => "overlay",
+            Self::Darken => "darken",
+            Self::Lighten => "lighten",
+            Self::ColorDodge => "color-dodge",
+            Self::ColorBurn => "color-burn",
+            Self::HardLight => "hard-light",
+            Self::SoftLight => "soft-light",
+            Self::Difference => "difference",
+            Self::Exclusion => "exclusion",
+            Self::Hue => "hue",
+            Self::Saturation => "saturation",
+            Self::Color => "color",
+            Self::Luminosity => "luminosity",
+            Self::Dst | Self::Src | Self::Clear => return None,
+        })
+    }
+}
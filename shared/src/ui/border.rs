@@ -0,0 +1,222 @@
+use nalgebra::{Vector2, Vector4};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///The radius of each corner of a rect. Each of them is a Vec2(X,Y), so corners can be elliptical
+pub struct CornerRadii {
+    pub top_left: Vector2<f32>,
+    pub top_right: Vector2<f32>,
+    pub bottom_right: Vector2<f32>,
+    pub bottom_left: Vector2<f32>,
+}
+
+impl CornerRadii {
+    #[inline]
+    ///Creates radii where every corner has the given `radius`
+    pub fn uniform(radius: Vector2<f32>) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    #[inline]
+    ///Creates radii where only the top corners are rounded, such as the ones of a tab
+    pub fn top(radius: Vector2<f32>) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    ///Creates radii where only the bottom corners are rounded
+    pub fn bottom(radius: Vector2<f32>) -> Self {
+        Self {
+            bottom_right: radius,
+            bottom_left: radius,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    ///Creates radii where only the left corners are rounded, such as the ones of the first button of a segmented control
+    pub fn left(radius: Vector2<f32>) -> Self {
+        Self {
+            top_left: radius,
+            bottom_left: radius,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    ///Creates radii where only the right corners are rounded
+    pub fn right(radius: Vector2<f32>) -> Self {
+        Self {
+            top_right: radius,
+            bottom_right: radius,
+            ..Default::default()
+        }
+    }
+
+    ///Retrieves whether every corner has the same radius
+    pub fn is_uniform(&self) -> bool {
+        self.top_left == self.top_right
+            && self.top_left == self.bottom_right
+            && self.top_left == self.bottom_left
+    }
+
+    ///Retrieves the radii in the order top left, top right, bottom right and bottom left
+    pub fn as_array(&self) -> [Vector2<f32>; 4] {
+        [
+            self.top_left,
+            self.top_right,
+            self.bottom_right,
+            self.bottom_left,
+        ]
+    }
+
+    ///Retrieves these radii grown by `offset`, such as the ones of a rect outset by it. Corners that aren't rounded stay sharp
+    pub fn outset(&self, offset: f32) -> Self {
+        let grow = |radius: Vector2<f32>| {
+            if radius.x == 0.0 || radius.y == 0.0 {
+                radius
+            } else {
+                radius.add_scalar(offset).map(|value| value.max(0.0))
+            }
+        };
+        Self {
+            top_left: grow(self.top_left),
+            top_right: grow(self.top_right),
+            bottom_right: grow(self.bottom_right),
+            bottom_left: grow(self.bottom_left),
+        }
+    }
+
+    ///Retrieves these radii scaled down so they fit in a rect of the given `size`, the same way CSS does: if the radii of two corners
+    ///of a side are bigger than it, every radius is scaled by the same factor until they fit. Negative radii become 0
+    pub fn fitted(&self, size: &Vector2<f32>) -> Self {
+        let clamp = |radius: Vector2<f32>| radius.map(|value| value.max(0.0));
+        let radii = Self {
+            top_left: clamp(self.top_left),
+            top_right: clamp(self.top_right),
+            bottom_right: clamp(self.bottom_right),
+            bottom_left: clamp(self.bottom_left),
+        };
+        let ratio = |side: f32, sum: f32| if sum > side { side / sum } else { 1.0 };
+        let scale = ratio(size.x, radii.top_left.x + radii.top_right.x)
+            .min(ratio(size.x, radii.bottom_left.x + radii.bottom_right.x))
+            .min(ratio(size.y, radii.top_left.y + radii.bottom_left.y))
+            .min(ratio(size.y, radii.top_right.y + radii.bottom_right.y))
+            .max(0.0);
+        if scale >= 1.0 {
+            return radii;
+        }
+        Self {
+            top_left: radii.top_left * scale,
+            top_right: radii.top_right * scale,
+            bottom_right: radii.bottom_right * scale,
+            bottom_left: radii.bottom_left * scale,
+        }
+    }
+}
+
+impl From<Vector2<f32>> for CornerRadii {
+    fn from(radius: Vector2<f32>) -> Self {
+        Self::uniform(radius)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///How the line of a border is drawn
+pub enum BorderStyle {
+    ///Nothing is drawn
+    None,
+    ///A single continuous line
+    #[default]
+    Solid,
+    ///A line made of dashes 3 times longer than the width of the border
+    Dashed,
+    ///A line made of round dots as big as the width of the border
+    Dotted,
+    ///Two continuous lines, each one with a third of the width of the border and separated by the other third
+    Double,
+}
+
+impl BorderStyle {
+    ///Retrieves the lengths of the dashes and gaps, alternately, of a border of this style with the given `width`. Empty if it's continuous
+    pub fn dash_pattern(&self, width: f32) -> Vec<f32> {
+        match self {
+            Self::Dashed => vec![width * 3.0, width * 2.0],
+            //dots are drawn with round caps, so a dash with no length becomes a dot as big as the width
+            Self::Dotted => vec![0.0, width * 2.0],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///The border of a single side of a rect
+pub struct BorderSide {
+    pub width: f32,
+    pub color: Vector4<f32>,
+    pub style: BorderStyle,
+}
+
+impl BorderSide {
+    pub fn new(width: f32, color: Vector4<f32>, style: BorderStyle) -> Self {
+        Self {
+            width,
+            color,
+            style,
+        }
+    }
+
+    ///Retrieves whether something is drawn for this side
+    pub fn is_visible(&self) -> bool {
+        self.width > 0.0 && self.color.w != 0.0 && self.style != BorderStyle::None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///The borders of every side of a rect. Like on a single border, each one is centered on its side of the rect
+pub struct Borders {
+    pub top: BorderSide,
+    pub right: BorderSide,
+    pub bottom: BorderSide,
+    pub left: BorderSide,
+}
+
+impl Borders {
+    #[inline]
+    ///Creates borders where every side is the given `side`
+    pub fn uniform(side: BorderSide) -> Self {
+        Self {
+            top: side,
+            right: side,
+            bottom: side,
+            left: side,
+        }
+    }
+
+    ///Retrieves whether every side is the same
+    pub fn is_uniform(&self) -> bool {
+        self.top == self.right && self.top == self.bottom && self.top == self.left
+    }
+
+    ///Retrieves whether any side is drawn
+    pub fn is_visible(&self) -> bool {
+        self.as_array().iter().any(BorderSide::is_visible)
+    }
+
+    ///Retrieves the sides in the order top, right, bottom and left
+    pub fn as_array(&self) -> [BorderSide; 4] {
+        [self.top, self.right, self.bottom, self.left]
+    }
+}
@@ -0,0 +1,136 @@
+use nalgebra::Vector4;
+
+///The matrix that keeps every color as it is
+const IDENTITY: [f32; 20] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, //
+    0.0, 1.0, 0.0, 0.0, 0.0, //
+    0.0, 0.0, 1.0, 0.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0, 0.0, //
+];
+
+///How much each channel contributes to the luminance of a color
+const LUMINANCE: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Changes the colors of everything drawn with it, after it's been filled. Every filter can be described as a color matrix, so they can
+///be chained and used by any renderer
+pub enum ColorFilter {
+    ///A 4x5 matrix, in row major order, that maps a RGBA color (not premultiplied) to the new one. The fifth column is added to each
+    ///channel, from 0 to 1
+    Matrix([f32; 20]),
+    ///Multiplies every channel by the ones of the given color
+    Tint(Vector4<f32>),
+    ///Removes the saturation of colors, by `amount` from 0 (nothing changes) to 1 (completely gray)
+    Grayscale(f32),
+    ///Adds `brightness` to every color channel, from -1 to 1, and scales their distance to the middle gray by `contrast`, where 1
+    ///keeps them as they are
+    BrightnessContrast { brightness: f32, contrast: f32 },
+    ///Applies every filter, in order
+    Chain(Vec<ColorFilter>),
+}
+
+impl ColorFilter {
+    #[inline]
+    ///Creates a filter that makes colors completely gray
+    pub fn grayscale() -> Self {
+        Self::Grayscale(1.0)
+    }
+
+    #[inline]
+    ///Creates a filter that only changes the brightness of colors
+    pub fn brightness(brightness: f32) -> Self {
+        Self::BrightnessContrast {
+            brightness,
+            contrast: 1.0,
+        }
+    }
+
+    #[inline]
+    ///Creates a filter that only changes the contrast of colors
+    pub fn contrast(contrast: f32) -> Self {
+        Self::BrightnessContrast {
+            brightness: 0.0,
+            contrast,
+        }
+    }
+
+    ///Creates a filter that applies `self` and then `next`
+    pub fn then(self, next: ColorFilter) -> Self {
+        match self {
+            Self::Chain(mut filters) => {
+                filters.push(next);
+                Self::Chain(filters)
+            }
+            filter => Self::Chain(vec![filter, next]),
+        }
+    }
+
+    ///Retrieves the color matrix this filter is equivalent to, in the same format of [ColorFilter::Matrix]
+    pub fn matrix(&self) -> [f32; 20] {
+        match self {
+            Self::Matrix(matrix) => *matrix,
+            Self::Tint(color) => [
+                color.x, 0.0, 0.0, 0.0, 0.0, //
+                0.0, color.y, 0.0, 0.0, 0.0, //
+                0.0, 0.0, color.z, 0.0, 0.0, //
+                0.0, 0.0, 0.0, color.w, 0.0, //
+            ],
+            Self::Grayscale(amount) => {
+                let amount = amount.clamp(0.0, 1.0);
+                let mut out = IDENTITY;
+                for row in 0..3 {
+                    for (col, weight) in LUMINANCE.iter().enumerate() {
+                        let keep = if row == col { 1.0 } else { 0.0 };
+                        out[row * 5 + col] = keep + (weight - keep) * amount;
+                    }
+                }
+                out
+            }
+            Self::BrightnessContrast {
+                brightness,
+                contrast,
+            } => {
+                let offset = brightness + 0.5 * (1.0 - contrast);
+                [
+                    *contrast, 0.0, 0.0, 0.0, offset, //
+                    0.0, *contrast, 0.0, 0.0, offset, //
+                    0.0, 0.0, *contrast, 0.0, offset, //
+                    0.0, 0.0, 0.0, 1.0, 0.0, //
+                ]
+            }
+            Self::Chain(filters) => filters
+                .iter()
+                .fold(IDENTITY, |acc, filter| concat(&filter.matrix(), &acc)),
+        }
+    }
+
+    ///Applies this filter on the given `color`. Used by whatever can only change the color something is filled with
+    pub fn apply(&self, color: &Vector4<f32>) -> Vector4<f32> {
+        let m = self.matrix();
+        let channel = |row: usize| {
+            (m[row * 5] * color.x
+                + m[row * 5 + 1] * color.y
+                + m[row * 5 + 2] * color.z
+                + m[row * 5 + 3] * color.w
+                + m[row * 5 + 4])
+                .clamp(0.0, 1.0)
+        };
+        Vector4::new(channel(0), channel(1), channel(2), channel(3))
+    }
+}
+
+///Multiplies the color matrices `a` and `b`, which results in the matrix that applies `b` and then `a`
+fn concat(a: &[f32; 20], b: &[f32; 20]) -> [f32; 20] {
+    let mut out = [0.0; 20];
+    for row in 0..4 {
+        for col in 0..5 {
+            let mut value = (0..4).map(|k| a[row * 5 + k] * b[k * 5 + col]).sum::<f32>();
+            if col == 4 {
+                value += a[row * 5 + 4];
+            }
+            out[row * 5 + col] = value;
+        }
+    }
+    out
+}
@@ -1,6 +1,9 @@
 use nalgebra::{Vector2, Vector4};
 
+use crate::{Rect, RuntimeShader};
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 ///The specification of how the shadow effect should be applies
 pub struct ShadowEffect {
     ///The color of the shadow
@@ -11,12 +14,222 @@ pub struct ShadowEffect {
     pub blur: Vector2<f32>,
 }
 
+impl ShadowEffect {
+    ///Retrieves the area covered by this shadow when cast by something at `bounds`, including how far its blur goes
+    pub fn bounds(&self, bounds: &Rect) -> Rect {
+        let (bx, by) = (self.blur.x.abs() * 3.0, self.blur.y.abs() * 3.0);
+        Rect::new(
+            bounds.x + self.offset.x - bx,
+            bounds.y + self.offset.y - by,
+            bounds.width + bx * 2.0,
+            bounds.height + by * 2.0,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A light that spreads evenly around the outside of something
+pub struct GlowEffect {
+    ///The color of the glow
+    pub color: Vector4<f32>,
+    ///How far the glow spreads. It's the blur power, so it fades until about 3 times this distance
+    pub radius: f32,
+}
+
 ///An effect to be applied to a component when rendering
 pub trait Effect {
     ///Retrieves whether this effect has or not a ShadowEffect
     fn shadow(&self) -> Option<ShadowEffect> {
         None
     }
+
+    ///Retrieves every shadow cast outside of the component, drawn in order below it. By default it's the `shadow`, if any
+    fn shadows(&self) -> Vec<ShadowEffect> {
+        self.shadow().into_iter().collect()
+    }
+
+    ///Retrieves the shadows cast inside of the component, above its background and below its border, such as the one of a pressed button
+    fn inner_shadows(&self) -> Vec<ShadowEffect> {
+        Vec::new()
+    }
+
+    ///Retrieves the glow around the component, if any
+    fn glow(&self) -> Option<GlowEffect> {
+        None
+    }
+
+    ///Retrieves the blur power, on X and Y, the component itself is drawn with
+    fn blur(&self) -> Option<Vector2<f32>> {
+        None
+    }
+
+    ///Retrieves the blur power, on X and Y, of what is behind the component, such as a frosted glass. Only visible if the background
+    ///of the component isn't opaque
+    fn backdrop_blur(&self) -> Option<Vector2<f32>> {
+        None
+    }
+
+    ///Retrieves the shader the component, after every other effect, goes through, such as a ripple or noise. See [RuntimeShader]
+    ///for how it receives what was drawn
+    fn shader(&self) -> Option<RuntimeShader> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///Every effect of a component, read once from an [Effect]. It's also an [Effect] itself, so it can be used to build one with many effects
+pub struct Effects {
+    pub shadows: Vec<ShadowEffect>,
+    pub inner_shadows: Vec<ShadowEffect>,
+    pub glow: Option<GlowEffect>,
+    pub blur: Option<Vector2<f32>>,
+    pub backdrop_blur: Option<Vector2<f32>>,
+    pub shader: Option<RuntimeShader>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Reads every effect of the given `effect`
+    pub fn from_effect(effect: &dyn Effect) -> Self {
+        Self {
+            shadows: effect.shadows(),
+            inner_shadows: effect.inner_shadows(),
+            glow: effect.glow(),
+            blur: effect.blur(),
+            backdrop_blur: effect.backdrop_blur(),
+            shader: effect.shader(),
+        }
+    }
+
+    ///Retrieves whether there's no effect at all
+    pub fn is_empty(&self) -> bool {
+        self.shadows.is_empty()
+            && self.inner_shadows.is_empty()
+            && self.glow.is_none()
+            && self.blur.is_none()
+            && self.backdrop_blur.is_none()
+            && self.shader.is_none()
+    }
+
+    ///Retrieves whether any of the effects draws outside of the component or changes how it's drawn, so it must be drawn on a layer.
+    ///Inner shadows and the backdrop blur are drawn within the component, so they don't need it
+    pub fn needs_layer(&self) -> bool {
+        !self.shadows.is_empty()
+            || self.glow.is_some()
+            || self.blur.is_some()
+            || self.shader.is_some()
+    }
+
+    ///Retrieves the area covered by something at `bounds` drawn with these effects
+    pub fn bounds(&self, bounds: &Rect) -> Rect {
+        let (mut left, mut top, mut right, mut bottom) =
+            (bounds.x, bounds.y, bounds.right(), bounds.bottom());
+        let mut grow = |area: Rect| {
+            left = left.min(area.x);
+            top = top.min(area.y);
+            right = right.max(area.right());
+            bottom = bottom.max(area.bottom());
+        };
+        for shadow in &self.shadows {
+            grow(shadow.bounds(bounds));
+        }
+        if let Some(glow) = self.glow {
+            let spread = glow.radius.abs() * 3.0;
+            grow(Rect::new(
+                bounds.x - spread,
+                bounds.y - spread,
+                bounds.width + spread * 2.0,
+                bounds.height + spread * 2.0,
+            ));
+        }
+        if let Some(blur) = self.blur {
+            let (bx, by) = (blur.x.abs() * 3.0, blur.y.abs() * 3.0);
+            grow(Rect::new(
+                bounds.x - bx,
+                bounds.y - by,
+                bounds.width + bx * 2.0,
+                bounds.height + by * 2.0,
+            ));
+        }
+        Rect::new(left, top, right - left, bottom - top)
+    }
+
+    #[inline]
+    ///Adds the given `shadow` above the ones added before
+    pub fn with_shadow(mut self, shadow: ShadowEffect) -> Self {
+        self.shadows.push(shadow);
+        self
+    }
+
+    #[inline]
+    ///Adds the given inner `shadow` above the ones added before
+    pub fn with_inner_shadow(mut self, shadow: ShadowEffect) -> Self {
+        self.inner_shadows.push(shadow);
+        self
+    }
+
+    #[inline]
+    ///Sets the glow around the component to be the given `glow`
+    pub fn with_glow(mut self, glow: GlowEffect) -> Self {
+        self.glow = Some(glow);
+        self
+    }
+
+    #[inline]
+    ///Sets the component to be drawn blurred by the given `blur`
+    pub fn with_blur(mut self, blur: Vector2<f32>) -> Self {
+        self.blur = Some(blur);
+        self
+    }
+
+    #[inline]
+    ///Sets what is behind the component to be blurred by the given `blur`
+    pub fn with_backdrop_blur(mut self, blur: Vector2<f32>) -> Self {
+        self.backdrop_blur = Some(blur);
+        self
+    }
+
+    #[inline]
+    ///Sets the component to go through the given `shader` after every other effect
+    pub fn with_shader(mut self, shader: RuntimeShader) -> Self {
+        self.shader = Some(shader);
+        self
+    }
+}
+
+impl Effect for Effects {
+    fn shadow(&self) -> Option<ShadowEffect> {
+        self.shadows.first().copied()
+    }
+
+    fn shadows(&self) -> Vec<ShadowEffect> {
+        self.shadows.clone()
+    }
+
+    fn inner_shadows(&self) -> Vec<ShadowEffect> {
+        self.inner_shadows.clone()
+    }
+
+    fn glow(&self) -> Option<GlowEffect> {
+        self.glow
+    }
+
+    fn blur(&self) -> Option<Vector2<f32>> {
+        self.blur
+    }
+
+    fn backdrop_blur(&self) -> Option<Vector2<f32>> {
+        self.backdrop_blur
+    }
+
+    fn shader(&self) -> Option<RuntimeShader> {
+        self.shader.clone()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -25,6 +238,7 @@ pub struct Shadow {
     color: Vector4<f32>,
     offset: Vector2<f32>,
     blur: Vector2<f32>,
+    inset: bool,
 }
 
 ///A Effect that doesn't apply any effect actually.
@@ -61,14 +275,33 @@ impl Shadow {
         self.offset = offset;
         self
     }
-}
 
-impl Effect for Shadow {
-    fn shadow(&self) -> Option<super::ShadowEffect> {
-        Some(ShadowEffect {
+    #[inline]
+    ///Sets whether this shadow is cast inside of the component instead of outside
+    pub fn with_inset(mut self, inset: bool) -> Self {
+        self.inset = inset;
+        self
+    }
+
+    fn effect(&self) -> ShadowEffect {
+        ShadowEffect {
             color: self.color,
             blur: self.blur,
             offset: self.offset,
-        })
+        }
+    }
+}
+
+impl Effect for Shadow {
+    fn shadow(&self) -> Option<super::ShadowEffect> {
+        (!self.inset).then(|| self.effect())
+    }
+
+    fn inner_shadows(&self) -> Vec<ShadowEffect> {
+        if self.inset {
+            vec![self.effect()]
+        } else {
+            Vec::new()
+        }
     }
 }
@@ -0,0 +1,166 @@
+use nalgebra::{Vector2, Vector4};
+
+use crate::{RendererImage, RuntimeShader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+///A color of a gradient and where it's located on it
+pub struct GradientStop {
+    ///Where this color is on the gradient, from 0 (its start) to 1 (its end)
+    pub offset: f32,
+    pub color: Vector4<f32>,
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: Vector4<f32>) -> Self {
+        Self { offset, color }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+///What is drawn outside of the area a gradient or a pattern covers
+pub enum TileMode {
+    ///The colors at the edges are extended
+    #[default]
+    Clamp,
+    ///The gradient or pattern is repeated
+    Repeat,
+    ///The gradient or pattern is repeated, mirroring it every time
+    Mirror,
+    ///Nothing is drawn
+    Decal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+///How the inside of an element is painted.
+///The points of the gradients are relative to the bounds of the element, where (0,0) is its top left corner and (1,1) its bottom right one,
+///so they follow the element when it's resized
+pub enum Fill {
+    ///A single color
+    Solid(Vector4<f32>),
+    ///Colors that go along the line from `start` to `end`
+    LinearGradient {
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        stops: Vec<GradientStop>,
+        tile_mode: TileMode,
+    },
+    ///Colors that go from `center` to the ellipse with the given `radius`. A radius of 0.5 touches the sides of the element
+    RadialGradient {
+        center: Vector2<f32>,
+        radius: f32,
+        stops: Vec<GradientStop>,
+        tile_mode: TileMode,
+    },
+    ///Colors that go around `center`, from `start_angle` to `end_angle`, in degrees clockwise starting from the right
+    SweepGradient {
+        center: Vector2<f32>,
+        start_angle: f32,
+        end_angle: f32,
+        stops: Vec<GradientStop>,
+        tile_mode: TileMode,
+    },
+    ///An image tiled over the element. Unlike gradients, `offset` is in pixels, from the top left corner of the element, and `scale`
+    ///is the scale the image is drawn with
+    Pattern {
+        image: RendererImage,
+        offset: Vector2<f32>,
+        scale: Vector2<f32>,
+        tile_mode: TileMode,
+    },
+    ///A SkSL shader run for every pixel of the element, with coordinates relative to its top left corner
+    Shader(RuntimeShader),
+}
+
+impl Default for Fill {
+    fn default() -> Self {
+        Self::Solid(Vector4::new(0.0, 0.0, 0.0, 1.0))
+    }
+}
+
+impl Fill {
+    ///Creates a linear gradient from `start` to `end` whose colors are evenly distributed
+    pub fn linear(start: Vector2<f32>, end: Vector2<f32>, colors: &[Vector4<f32>]) -> Self {
+        Self::LinearGradient {
+            start,
+            end,
+            stops: Self::evenly(colors),
+            tile_mode: TileMode::default(),
+        }
+    }
+
+    ///Creates a radial gradient around `center` whose colors are evenly distributed
+    pub fn radial(center: Vector2<f32>, radius: f32, colors: &[Vector4<f32>]) -> Self {
+        Self::RadialGradient {
+            center,
+            radius,
+            stops: Self::evenly(colors),
+            tile_mode: TileMode::default(),
+        }
+    }
+
+    ///Creates a gradient that goes a whole turn around `center` whose colors are evenly distributed
+    pub fn sweep(center: Vector2<f32>, colors: &[Vector4<f32>]) -> Self {
+        Self::SweepGradient {
+            center,
+            start_angle: 0.0,
+            end_angle: 360.0,
+            stops: Self::evenly(colors),
+            tile_mode: TileMode::default(),
+        }
+    }
+
+    ///Creates a pattern that repeats the given `image` with its normal size
+    pub fn pattern(image: RendererImage) -> Self {
+        Self::Pattern {
+            image,
+            offset: Vector2::zeros(),
+            scale: Vector2::new(1.0, 1.0),
+            tile_mode: TileMode::Repeat,
+        }
+    }
+
+    fn evenly(colors: &[Vector4<f32>]) -> Vec<GradientStop> {
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        colors
+            .iter()
+            .enumerate()
+            .map(|(idx, color)| GradientStop::new(idx as f32 / last, *color))
+            .collect()
+    }
+
+    ///Retrieves whether this fill is a single color
+    pub fn is_solid(&self) -> bool {
+        matches!(self, Self::Solid(_))
+    }
+
+    ///Retrieves the stops of this fill, if it's a gradient
+    pub fn stops(&self) -> Option<&[GradientStop]> {
+        match self {
+            Self::LinearGradient { stops, .. }
+            | Self::RadialGradient { stops, .. }
+            | Self::SweepGradient { stops, .. } => Some(stops),
+            _ => None,
+        }
+    }
+
+    ///Retrieves a single color that represents this fill. Used by whatever can't draw gradients, patterns or shaders.
+    ///For gradients this is the color of their first stop, for patterns it's white and for shaders it's their fallback
+    pub fn representative_color(&self) -> Vector4<f32> {
+        match self {
+            Self::Solid(color) => *color,
+            Self::Pattern { .. } => Vector4::new(1.0, 1.0, 1.0, 1.0),
+            Self::Shader(shader) => shader.fallback(),
+            _ => self
+                .stops()
+                .and_then(|stops| stops.first())
+                .map(|stop| stop.color)
+                .unwrap_or(Vector4::new(0.0, 0.0, 0.0, 0.0)),
+        }
+    }
+}
+
+impl From<Vector4<f32>> for Fill {
+    fn from(color: Vector4<f32>) -> Self {
+        Self::Solid(color)
+    }
+}
@@ -1,4 +1,14 @@
+mod blend;
+mod border;
+mod color_filter;
 mod effect;
+mod fill;
+mod shader;
 mod style;
+pub use blend::*;
+pub use border::*;
+pub use color_filter::*;
 pub use effect::*;
+pub use fill::*;
+pub use shader::*;
 pub use style::*;
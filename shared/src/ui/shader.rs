@@ -0,0 +1,122 @@
+use nalgebra::{Vector2, Vector3, Vector4};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///The value of an uniform of a [RuntimeShader]. Its type must be the same of the uniform declared on the shader
+pub enum Uniform {
+    Float(f32),
+    Float2(Vector2<f32>),
+    Float3(Vector3<f32>),
+    Float4(Vector4<f32>),
+    Int(i32),
+}
+
+impl From<f32> for Uniform {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<Vector2<f32>> for Uniform {
+    fn from(value: Vector2<f32>) -> Self {
+        Self::Float2(value)
+    }
+}
+
+impl From<Vector3<f32>> for Uniform {
+    fn from(value: Vector3<f32>) -> Self {
+        Self::Float3(value)
+    }
+}
+
+impl From<Vector4<f32>> for Uniform {
+    fn from(value: Vector4<f32>) -> Self {
+        Self::Float4(value)
+    }
+}
+
+impl From<i32> for Uniform {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///A shader written in SkSL, with the values of its uniforms. The painters backed by skia compile it once and cache it by its source, so
+///changing only the uniforms, such as a time for animations, is cheap.
+///Besides its own uniforms, the shader may declare `uniform float2 resolution`, which is set to the size of the element, and
+///`uniform float2 origin`, its top left corner on the coordinates the shader receives. When used as a fill, those coordinates are
+///relative to the element, so `origin` is (0,0). When used as a filter, they're the ones of the window, and the element drawn is given
+///to the shader as `uniform shader content`.
+///Whatever can't run SkSL draws the `fallback` color instead
+pub struct RuntimeShader {
+    source: String,
+    uniforms: Vec<(String, Uniform)>,
+    fallback: Vector4<f32>,
+}
+
+impl RuntimeShader {
+    ///Creates a shader with the given SkSL `source`, without any uniform and a transparent fallback
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            uniforms: Vec::new(),
+            fallback: Vector4::zeros(),
+        }
+    }
+
+    #[inline]
+    ///Retrieves the SkSL source of this shader
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[inline]
+    ///Retrieves the uniforms set on this shader, by their names
+    pub fn uniforms(&self) -> &[(String, Uniform)] {
+        &self.uniforms
+    }
+
+    ///Retrieves the value of the uniform with the given `name`, if it was set
+    pub fn uniform(&self, name: &str) -> Option<Uniform> {
+        self.uniforms
+            .iter()
+            .find(|(uniform, _)| uniform.as_str() == name)
+            .map(|(_, value)| *value)
+    }
+
+    ///Sets the uniform with the given `name` to be `value`, replacing the one set before
+    pub fn set_uniform(&mut self, name: &str, value: impl Into<Uniform>) -> &mut Self {
+        let value = value.into();
+        match self
+            .uniforms
+            .iter_mut()
+            .find(|(uniform, _)| uniform.as_str() == name)
+        {
+            Some((_, current)) => *current = value,
+            None => self.uniforms.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    #[inline]
+    ///Sets the uniform with the given `name` to be `value`, replacing the one set before
+    pub fn with_uniform(mut self, name: &str, value: impl Into<Uniform>) -> Self {
+        self.set_uniform(name, value);
+        self
+    }
+
+    #[inline]
+    ///Retrieves the color drawn instead of this shader by whatever can't run it
+    pub fn fallback(&self) -> Vector4<f32> {
+        self.fallback
+    }
+
+    #[inline]
+    ///Sets the color drawn instead of this shader by whatever can't run it
+    pub fn with_fallback(mut self, color: Vector4<f32>) -> Self {
+        self.fallback = color;
+        self
+    }
+}
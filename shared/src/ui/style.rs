@@ -1,6 +1,8 @@
 use nalgebra::{Vector2, Vector4};
 
-use crate::{Effect, NoEffect};
+use crate::{
+    BlendMode, BorderSide, BorderStyle, Borders, ColorFilter, CornerRadii, Effect, Fill, NoEffect,
+};
 
 pub trait Style: std::fmt::Debug {
     ///Retrieves the color of this Style
@@ -13,6 +15,33 @@ pub trait Style: std::fmt::Debug {
         Vector4::new(1.0, 1.0, 1.0, 1.0)
     }
 
+    ///Retrieves how the background of elements with this style is painted. By default it's the `background_color`, but it can be a gradient
+    ///or a pattern
+    fn background_fill(&self) -> Fill {
+        Fill::Solid(self.background_color())
+    }
+
+    ///Retrieves how texts with this style are painted. By default it's the `color`
+    fn color_fill(&self) -> Fill {
+        Fill::Solid(self.color())
+    }
+
+    ///Retrieves the opacity of elements with this style, from 0 (invisible) to 1. Unlike changing the alpha of the colors, containers
+    ///are faded with their children as a single unit
+    fn opacity(&self) -> f32 {
+        1.0
+    }
+
+    ///Retrieves how elements with this style are blended with what's below them
+    fn blend_mode(&self) -> BlendMode {
+        BlendMode::SrcOver
+    }
+
+    ///Retrieves the filter the colors of elements with this style go through, such as a grayscale one for disabled images
+    fn color_filter(&self) -> Option<ColorFilter> {
+        None
+    }
+
     ///Retrieves the effects of this Style
     fn effect(&self) -> Box<dyn Effect> {
         Box::new(NoEffect)
@@ -32,6 +61,25 @@ pub trait Style: std::fmt::Debug {
     fn border_width(&self) -> f32 {
         0.0
     }
+
+    ///How the border of the element is drawn. The element must be a square under the hood to this be applied
+    fn border_style(&self) -> BorderStyle {
+        BorderStyle::Solid
+    }
+
+    ///The radius of each corner of the element. By default every corner has the `border_radius`
+    fn corner_radii(&self) -> CornerRadii {
+        CornerRadii::uniform(self.border_radius())
+    }
+
+    ///The border of each side of the element. By default every side has the `border_width`, `border_color` and `border_style`
+    fn borders(&self) -> Borders {
+        Borders::uniform(BorderSide::new(
+            self.border_width(),
+            self.border_color(),
+            self.border_style(),
+        ))
+    }
 }
 
 #[derive(Debug)]
@@ -39,7 +87,9 @@ pub trait Style: std::fmt::Debug {
 ///Border Radius: 8px 8px
 ///Effect: None
 ///Color: Black
+///Opacity: 1
 ///BorderWidth: 0
 ///BorderColor: White
+///BorderStyle: Solid
 pub struct DefaultStyle;
 impl Style for DefaultStyle {}
@@ -1,7 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
 use candy_renderers::{BiDimensionalPainter, primitives::CandySquare};
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector2;
 use winit::event::MouseButton;
 
@@ -38,6 +38,15 @@ impl<'a, Msg> Component for Button<'a, Msg> {
         self.text.apply_offset(offset);
         *self.rect.position_mut() += offset;
     }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.rect.take_damage(damage);
+        self.text.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
 }
 
 impl<'a, Msg> Button<'a, Msg> {
@@ -110,3 +119,56 @@ impl<'a, Msg> std::fmt::Debug for Button<'a, Msg> {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use candy_renderers::{DrawCommand, RecordingPainter, primitives::CandyFont};
+    use nalgebra::Vector4;
+    use skia_safe::FontMgr;
+
+    use super::*;
+
+    fn font() -> CandyFont {
+        let typeface = FontMgr::new()
+            .new_from_data(include_bytes!("../../inter.ttf"), None)
+            .unwrap();
+        CandyFont::new(typeface, 20.0)
+    }
+
+    #[derive(Debug)]
+    struct Colored;
+
+    impl Style for Colored {
+        fn color(&self) -> Vector4<f32> {
+            Vector4::new(0.0, 0.0, 1.0, 1.0)
+        }
+        fn background_color(&self) -> Vector4<f32> {
+            Vector4::new(1.0, 0.0, 0.0, 1.0)
+        }
+    }
+
+    #[test]
+    fn draws_its_square_and_then_its_centered_text() {
+        let mut button =
+            Button::new(Text::new_content("Click", font()), |_, _| ()).with_style(&Colored);
+        let rect = Rect::new(10.0, 20.0, 100.0, 40.0);
+        button.resize(rect.clone());
+        let mut painter = RecordingPainter::new();
+        button.render(&mut painter);
+
+        let commands = painter.display_list().commands();
+        assert_eq!(commands.len(), 2);
+        let DrawCommand::Square(ref square) = commands[0] else {
+            panic!("expected the square of the button, found {:?}", commands[0]);
+        };
+        assert_eq!(square.position, Vector2::new(10.0, 20.0));
+        assert_eq!(square.size, Vector2::new(100.0, 40.0));
+        assert_eq!(square.color, Vector4::new(1.0, 0.0, 0.0, 1.0));
+        let DrawCommand::Text(ref text) = commands[1] else {
+            panic!("expected the text of the button, found {:?}", commands[1]);
+        };
+        assert_eq!(text.drawn_content(), "Click");
+        assert_eq!(text.color, Vector4::new(0.0, 0.0, 1.0, 1.0));
+        assert_eq!(text.position, center(&button.text.text_bounds(), &rect));
+    }
+}
@@ -1,7 +1,14 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    cell::RefCell,
+    ops::{Deref, DerefMut},
+};
 
-use candy_renderers::{BiDimensionalPainter, primitives::CandySquare};
-use candy_shared_types::{Rect, Style};
+use candy_renderers::{
+    BiDimensionalPainter,
+    primitives::{CandyPicture, CandySquare},
+};
+use candy_shared_types::{BlendMode, Damage, Rect, Style};
+use nalgebra::{Matrix3, Vector2, Vector4};
 
 use crate::ui::{
     component::Component,
@@ -17,24 +24,53 @@ pub struct Container<C: Component> {
     pub(crate) layout: Layout,
     children: Vec<C>,
     ignore_overflow: bool,
+    ///Whether the children are clipped by the bounds of this container
+    clip_children: bool,
+    ///Transform applied to this container and its children, around its center
+    transform: Matrix3<f32>,
+    ///Opacity this container and its children are composited with, as a single unit
+    opacity: f32,
+    ///Whether how the container and its children are drawn changed as a whole, such as its opacity or which children it's got, so all
+    ///of it is repainted
+    content_damaged: bool,
+    ///Whether the transform changed, so the whole window is repainted, as there's no telling where the last one drew it
+    transform_damaged: bool,
+    ///Whether the children are drawn by replaying a picture recorded from them
+    cached: bool,
+    ///The picture the children were recorded into, if they were and didn't change since
+    picture: RefCell<Option<RetainedChildren>>,
+    ///Damage the children had before being moved as a whole, not reported yet
+    pending: Damage,
+    ///Indices of the children a new page is started before when printed, sorted
+    page_breaks: Vec<usize>,
+}
+
+#[derive(Debug)]
+///The children of a container recorded into a picture
+struct RetainedChildren {
+    picture: CandyPicture,
+    ///How much the children were moved since they were recorded
+    offset: Vector2<f32>,
 }
 
 impl<C: Component> Component for Container<C> {
+    #[inline]
     fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
-        if self.square.rule.get_color().w != 0.0 && self.square.rule.border_color.w != 0.0 {
-            renderer.square(&self.square);
-        }
-        for child in &self.children {
-            child.render(renderer);
-        }
+        self.draw(renderer, None);
     }
     fn resize(&mut self, rect: Rect) {
-        self.square.position_mut().x = rect.x;
-        self.square.position_mut().y = rect.y;
-        self.square.size_mut().x = rect.width;
-        self.square.size_mut().y = rect.height;
+        //only marked as dirty when the rect actually changed, so laying out again doesn't repaint everything
+        if rect != self.square.bounds() {
+            self.invalidate_picture();
+            self.square.resize(rect.clone());
+        }
 
-        let calc = self.layout.calculate(rect, self.ignore_overflow);
+        let children = &self.children;
+        let calc = self
+            .layout
+            .calculate_measured(rect, self.ignore_overflow, &|idx, available| {
+                children.get(idx).and_then(|child| child.measure(available))
+            });
 
         for (idx, rec) in calc.into_iter().enumerate() {
             self.children[idx].resize(rec);
@@ -42,6 +78,7 @@ impl<C: Component> Component for Container<C> {
     }
     fn apply_style(&mut self, style: &dyn Style) {
         self.square.apply_style(style);
+        self.set_opacity(style.opacity());
     }
     fn position(&self) -> nalgebra::Vector2<f32> {
         *self.square.position()
@@ -52,22 +89,307 @@ impl<C: Component> Component for Container<C> {
 
     fn apply_offset(&mut self, offset: nalgebra::Vector2<f32>) {
         *self.position_mut() += offset;
-        for child in &mut self.children {
-            child.apply_offset(offset);
+        self.offset_children(offset);
+    }
+
+    fn take_damage(&mut self, damage: &mut Damage) {
+        let mut inner = std::mem::take(&mut self.pending);
+        if self.take_children_damage(&mut inner) {
+            self.invalidate_picture();
+        }
+        self.square.take_damage(&mut inner);
+        if std::mem::take(&mut self.content_damaged) {
+            //children that aren't clipped may be drawn anywhere
+            if self.clip_children {
+                inner.add(self.square.visual_bounds());
+            } else {
+                inner.invalidate_all();
+            }
+        }
+        if std::mem::take(&mut self.transform_damaged) {
+            inner.invalidate_all();
+        }
+        if inner.is_full() || self.transform == Matrix3::identity() {
+            damage.merge(&inner);
+            return;
+        }
+        let transform = self.drawn_transform();
+        for rect in inner.rects() {
+            damage.add(rect.transformed(&transform));
         }
     }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        self.children.iter().all(|child| child.tracks_damage())
+    }
 }
 
 impl<C: Component> Container<C> {
     ///Creates a new container with the provided `layout`. If `ignore_overflow` is true, the children will overflow the bounds of this component,
     ///which can cause some bugs, since elements will be calculated out of bounds and may appear above or below other elements.
+    ///Its square is transparent, so nothing is drawn behind the children until a style is applied
     pub fn new(layout: Layout, ignore_overflow: bool) -> Self {
+        let mut square = CandySquare::default();
+        square.rule_mut().set_color(&Vector4::zeros());
         Self {
             ignore_overflow,
             layout,
-            square: CandySquare::default(),
+            square,
             children: Vec::new(),
+            clip_children: false,
+            transform: Matrix3::identity(),
+            opacity: 1.0,
+            content_damaged: false,
+            transform_damaged: false,
+            cached: false,
+            picture: RefCell::new(None),
+            pending: Damage::new(),
+            page_breaks: Vec::new(),
+        }
+    }
+
+    ///Sets whether a new page is started before the child at `index` when this container is printed with `print_container`, even if
+    ///it would fit on the current one. The index moves with the child when others are added or removed before it
+    pub fn set_page_break(&mut self, index: usize, page_break: bool) -> &mut Self {
+        match (self.page_breaks.binary_search(&index), page_break) {
+            (Err(at), true) => self.page_breaks.insert(at, index),
+            (Ok(at), false) => {
+                self.page_breaks.remove(at);
+            }
+            _ => {}
+        }
+        self
+    }
+
+    #[inline]
+    ///Starts a new page before the next child added when this container is printed
+    pub fn add_page_break(&mut self) -> &mut Self {
+        self.set_page_break(self.children.len(), true)
+    }
+
+    #[inline]
+    ///Checks whether a new page is started before the child at `index` when this container is printed
+    pub fn has_page_break(&self, index: usize) -> bool {
+        self.page_breaks.binary_search(&index).is_ok()
+    }
+
+    #[inline]
+    ///Sets whether the children are recorded into a picture the first time they're drawn and replayed on the next frames, instead of
+    ///drawing each of them again, which saves a lot of draw calls on big trees that barely change. The picture is recorded again after
+    ///the children change, which is found when they're resized, added, removed, borrowed with `children_mut` or when their damage is
+    ///taken. Changes made any other way, such as through interior mutability, need [Self::invalidate_picture].
+    ///It's off by default. Children are only recorded if every one of them [tracks its damage](Component::tracks_damage), and only
+    ///painters that can record pictures, the ones backed by skia, use it. Otherwise the children are drawn as usual
+    pub fn set_cached(&mut self, cached: bool) -> &mut Self {
+        self.cached = cached;
+        if !cached {
+            self.invalidate_picture();
+        }
+        self
+    }
+
+    #[inline]
+    ///Retrieves whether the children are recorded into a picture that is replayed while they don't change
+    pub fn is_cached(&self) -> bool {
+        self.cached
+    }
+
+    #[inline]
+    ///Retrieves the picture the children were recorded into, if they were and didn't change since
+    pub fn picture(&self) -> Option<CandyPicture> {
+        self.picture
+            .borrow()
+            .as_ref()
+            .map(|retained| retained.picture.clone())
+    }
+
+    #[inline]
+    ///Drops the picture the children were recorded into, so they're recorded again the next time they're drawn
+    pub fn invalidate_picture(&mut self) {
+        self.picture.get_mut().take();
+    }
+
+    ///Moves every child by `offset`, such as when scrolling. The picture they were recorded into is kept and drawn moved, unless they
+    ///changed otherwise, and the damage of the move is the area of this container as a whole, instead of each child
+    pub fn offset_children(&mut self, offset: Vector2<f32>) {
+        //what changed before moving is reported as is, as it isn't on the picture
+        let mut changed = std::mem::take(&mut self.pending);
+        if self.take_children_damage(&mut changed) {
+            self.invalidate_picture();
+        }
+        self.pending = changed;
+        for child in &mut self.children {
+            child.apply_offset(offset);
+        }
+        //the damage of each child moving is covered by the damage of the content
+        self.take_children_damage(&mut Damage::new());
+        self.content_damaged = true;
+        if let Some(retained) = self.picture.get_mut() {
+            retained.offset += offset;
+        }
+    }
+
+    ///Adds the damage of every child to `damage`. Returns whether any child changed
+    fn take_children_damage(&mut self, damage: &mut Damage) -> bool {
+        let mut changed = Damage::new();
+        for child in &mut self.children {
+            child.take_damage(&mut changed);
+        }
+        damage.merge(&changed);
+        !changed.is_empty()
+    }
+
+    ///Draws this container with its children, or `only` the ones at the indices it accepts, which are drawn on their own
+    fn draw(&self, renderer: &mut dyn BiDimensionalPainter, only: Option<&dyn Fn(usize) -> bool>) {
+        if self.opacity <= 0.0 {
+            return;
+        }
+        let transformed = self.transform != Matrix3::identity();
+        let faded = self.opacity < 1.0;
+        let saved = self.clip_children || transformed || faded;
+        if faded {
+            //Children overflowing this container would be cut by the bounds of the layer, so they're only given when the children are clipped anyway
+            let bounds = (self.clip_children && !transformed).then(|| self.square.bounds());
+            renderer.save_layer(self.opacity, BlendMode::SrcOver, bounds.as_ref());
+        } else if saved {
+            renderer.save();
+        }
+        if transformed {
+            renderer.concat(&self.drawn_transform());
+        }
+        if self.square.rule().is_visible() {
+            renderer.square(&self.square);
+        }
+        if self.clip_children {
+            renderer
+                .clip_rounded_rect_radii(&self.square.bounds(), &self.square.rule().corner_radii());
+        }
+        match only {
+            Some(keep) => {
+                for (idx, child) in self.children.iter().enumerate() {
+                    if keep(idx) {
+                        child.render(renderer);
+                    }
+                }
+            }
+            None => self.render_children(renderer),
         }
+        if saved {
+            renderer.restore();
+        }
+    }
+
+    #[inline]
+    ///Draws this container with only the children at the indices `keep` accepts, such as the ones on a printed page
+    pub(crate) fn render_where(
+        &self,
+        renderer: &mut dyn BiDimensionalPainter,
+        keep: &dyn Fn(usize) -> bool,
+    ) {
+        self.draw(renderer, Some(keep));
+    }
+
+    ///Draws every child. If cached, the picture they were recorded into is replayed, recording it if there's none
+    fn render_children(&self, renderer: &mut dyn BiDimensionalPainter) {
+        if self.cached && self.tracks_damage() {
+            if let Some(ref retained) = *self.picture.borrow() {
+                renderer.save();
+                renderer.translate(&retained.offset);
+                let drawn = renderer.picture(&retained.picture);
+                renderer.restore();
+                if drawn {
+                    return;
+                }
+            }
+            if renderer.begin_picture() {
+                for child in &self.children {
+                    child.render(renderer);
+                }
+                //only `None` if recording failed, in which case nothing was drawn yet
+                let Some(picture) = renderer.end_picture() else {
+                    return self.render_children_directly(renderer);
+                };
+                renderer.picture(&picture);
+                *self.picture.borrow_mut() = Some(RetainedChildren {
+                    picture,
+                    offset: Vector2::zeros(),
+                });
+                return;
+            }
+        }
+        self.render_children_directly(renderer);
+    }
+
+    ///Draws every child on its own
+    fn render_children_directly(&self, renderer: &mut dyn BiDimensionalPainter) {
+        for child in &self.children {
+            child.render(renderer);
+        }
+    }
+
+    #[inline]
+    ///Sets whether the children are clipped by the (rounded) bounds of this container, so nothing overflowing it is drawn
+    pub fn set_clip(&mut self, clip: bool) -> &mut Self {
+        self.content_damaged |= self.clip_children != clip;
+        self.clip_children = clip;
+        self
+    }
+
+    #[inline]
+    ///Retrieves whether the children are clipped by the bounds of this container
+    pub fn clips(&self) -> bool {
+        self.clip_children
+    }
+
+    #[inline]
+    ///Sets the `transform` this container and its children are drawn with. It's applied around the center of this container, so a rotation
+    ///spins it in place.
+    ///Obs: This only affects how things are drawn, the bounds used by the layout and hit testing stay the same
+    pub fn set_transform(&mut self, transform: Matrix3<f32>) -> &mut Self {
+        self.transform_damaged |= self.transform != transform;
+        self.transform = transform;
+        self
+    }
+
+    #[inline]
+    ///Retrieves the transform this container and its children are drawn with
+    pub fn transform(&self) -> &Matrix3<f32> {
+        &self.transform
+    }
+
+    ///Retrieves the transform that is actually drawn with, which is the transform of this container applied around its center
+    fn drawn_transform(&self) -> Matrix3<f32> {
+        let center = self.square.position() + self.square.size() * 0.5;
+        Matrix3::new_translation(&center) * self.transform * Matrix3::new_translation(&-center)
+    }
+
+    #[inline]
+    ///Sets the `opacity` this container is drawn with, from 0 to 1. Its children are faded with it as a single unit, so overlapping ones
+    ///don't show through each other. This is also set by the `opacity` of the styles applied to it
+    pub fn set_opacity(&mut self, opacity: f32) -> &mut Self {
+        let opacity = opacity.clamp(0.0, 1.0);
+        self.content_damaged |= self.opacity != opacity;
+        self.opacity = opacity;
+        self
+    }
+
+    #[inline]
+    ///Retrieves the opacity this container and its children are drawn with
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    #[inline]
+    ///Rotates this container by `degrees` clockwise around its center, replacing its current transform
+    pub fn set_rotation(&mut self, degrees: f32) -> &mut Self {
+        self.set_transform(Matrix3::new_rotation(degrees.to_radians()))
+    }
+
+    #[inline]
+    ///Scales this container by `scale` from its center, replacing its current transform
+    pub fn set_scale(&mut self, scale: Vector2<f32>) -> &mut Self {
+        self.set_transform(Matrix3::new_nonuniform_scaling(&scale))
     }
 
     ///Adds the given `child` and `def` at the provided `index`. If `index` > `len(children)`, then the provided `child` is inserted as
@@ -78,12 +400,22 @@ impl<C: Component> Container<C> {
         } else {
             self.children.insert(index, child);
             self.layout.boxes.insert(index, def);
+            for page_break in &mut self.page_breaks {
+                if *page_break >= index {
+                    *page_break += 1;
+                }
+            }
+            self.content_damaged = true;
+            self.invalidate_picture();
             self
         }
     }
 
     ///Clears all the children this Container has and returns them with their respective layout
     pub fn clear_children(&mut self) -> Vec<(C, DefinitionRect)> {
+        self.content_damaged = true;
+        self.invalidate_picture();
+        self.page_breaks.clear();
         let children = std::mem::take(&mut self.children);
         let layouts = std::mem::take(&mut self.layout.boxes);
         debug_assert!(children.len() == layouts.len());
@@ -96,6 +428,8 @@ impl<C: Component> Container<C> {
     #[inline]
     ///Appends the given `child` on this container without a definition. Note that if the amount of deffinition don't match, this will lead to bugs
     pub unsafe fn add_child_unsafe(&mut self, child: C) -> &mut Self {
+        self.content_damaged = true;
+        self.invalidate_picture();
         self.children.push(child);
         self
     }
@@ -103,14 +437,25 @@ impl<C: Component> Container<C> {
     #[inline]
     ///Adds the given `child` as the new last one with the given `def` rect for resizing.
     pub fn add_child(&mut self, child: C, def: DefinitionRect) -> &mut Self {
+        self.content_damaged = true;
+        self.invalidate_picture();
         self.children.push(child);
         self.layout.with_definition(def);
         self
     }
 
     #[inline]
-    ///Removes the child at the provided `index`
+    ///Removes the child at the provided `index`, with its layout
     pub fn remove_children_at_index(&mut self, index: usize) -> C {
+        self.content_damaged = true;
+        self.invalidate_picture();
+        self.page_breaks.retain(|page_break| *page_break != index);
+        for page_break in &mut self.page_breaks {
+            if *page_break > index {
+                *page_break -= 1;
+            }
+        }
+        self.layout.boxes.remove(index);
         self.children.remove(index)
     }
 
@@ -125,13 +470,36 @@ impl<C: Component> Container<C> {
             .map(|idx| self.remove_children_at_index(idx))
     }
 
+    ///Calculates the bounds each child has got based on the current bounds of this Container, in the same order as the children
+    pub fn children_bounds(&self) -> Vec<Rect> {
+        self.layout.calculate_measured(
+            self.square.bounds(),
+            self.ignore_overflow,
+            &|idx, available| {
+                self.children
+                    .get(idx)
+                    .and_then(|child| child.measure(available))
+            },
+        )
+    }
+
+    ///Calculates the height the children take when laid out on `rect`, with the auto sizes measured from them
+    pub(crate) fn content_height(&mut self, rect: Rect) -> f32 {
+        let children = &self.children;
+        self.layout
+            .calculate_height_measured(rect, self.ignore_overflow, &|idx, available| {
+                children.get(idx).and_then(|child| child.measure(available))
+            })
+    }
+
     ///Retrieves all the children of this Container
     pub fn children(&self) -> &Vec<C> {
         &self.children
     }
 
-    ///Retrieves all the children of this Container
+    ///Retrieves all the children of this Container. As they may be changed, the picture they were recorded into is dropped
     pub fn children_mut(&mut self) -> &mut Vec<C> {
+        self.invalidate_picture();
         &mut self.children
     }
 }
@@ -148,3 +516,77 @@ impl<C: Component> DerefMut for Container<C> {
         &mut self.square
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{components::SolidBox, ui::styling::layout::Size};
+
+    fn def(height: f32) -> DefinitionRect {
+        DefinitionRect {
+            x: Size::Length(0.0),
+            y: Size::Length(0.0),
+            width: Size::Length(10.0),
+            height: Size::Length(height),
+        }
+    }
+
+    fn child() -> SolidBox {
+        SolidBox::new(&Vector4::new(1.0, 0.0, 0.0, 1.0))
+    }
+
+    fn heights(container: &Container<SolidBox>) -> Vec<f32> {
+        container
+            .children_bounds()
+            .iter()
+            .map(|rect| rect.height)
+            .collect()
+    }
+
+    fn breaks(container: &Container<SolidBox>) -> Vec<usize> {
+        (0..=container.children().len())
+            .filter(|index| container.has_page_break(*index))
+            .collect()
+    }
+
+    fn container() -> Container<SolidBox> {
+        let mut container = Container::new(Layout::vertical(), true);
+        container
+            .add_child(child(), def(10.0))
+            .add_page_break()
+            .add_child(child(), def(20.0))
+            .set_page_break(2, true)
+            .add_child(child(), def(30.0));
+        container
+    }
+
+    #[test]
+    fn page_breaks_move_with_the_children_inserted_before_them() {
+        let mut container = container();
+        assert_eq!(breaks(&container), vec![1, 2]);
+        container.add_child_at(child(), def(40.0), 1);
+        assert_eq!(breaks(&container), vec![2, 3]);
+        assert_eq!(heights(&container), vec![10.0, 40.0, 20.0, 30.0]);
+        //inserting after every break leaves them where they are
+        container.add_child_at(child(), def(50.0), 10);
+        assert_eq!(breaks(&container), vec![2, 3]);
+    }
+
+    #[test]
+    fn page_breaks_move_with_the_children_removed_before_them() {
+        let mut container = container();
+        container.remove_children_at_index(0);
+        assert_eq!(breaks(&container), vec![0, 1]);
+        assert_eq!(heights(&container), vec![20.0, 30.0]);
+    }
+
+    #[test]
+    fn page_break_of_a_removed_child_is_removed() {
+        let mut container = container();
+        container.remove_children_at_index(1);
+        assert_eq!(breaks(&container), vec![1]);
+        assert_eq!(heights(&container), vec![10.0, 30.0]);
+        container.set_page_break(1, false);
+        assert!(breaks(&container).is_empty());
+    }
+}
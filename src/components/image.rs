@@ -1,22 +1,78 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
 
-use candy_renderers::{BiDimensionalPainter, primitives::CandyImage};
-use candy_shared_types::{Rect, Style};
+use candy_renderers::{
+    BiDimensionalPainter,
+    primitives::{AnimatedImage, CandyImage, CandySquare, DrawRule, TwodCandyImg},
+};
+use candy_shared_types::{Damage, Rect, RendererImage, Style};
+use nalgebra::Vector4;
 
-use crate::ui::component::Component;
+use crate::ui::{clock, component::Component, loader::PendingImage};
 
 #[derive(Debug)]
-///A component that simply represents an image
+///The state of an animated image being played
+struct Playback {
+    animation: AnimatedImage,
+    ///The frame being drawn
+    frame: usize,
+    ///When the animation would have started to be at the time it is now. `None` if it's paused
+    started: Option<Instant>,
+    ///The time of the animation it was paused at
+    paused_at: Duration,
+    ///When the clock was last asked to wake the window for the next frame
+    wake: Option<Instant>,
+}
+
+impl Playback {
+    #[inline]
+    ///Retrieves the time of the animation, since its first frame
+    fn time(&self) -> Duration {
+        self.started
+            .map_or(self.paused_at, |started| started.elapsed())
+    }
+}
+
+#[inline]
+///Retrieves when an animation would have started to be at `time` now
+fn started_at(time: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_sub(time).unwrap_or(now)
+}
+
+#[derive(Debug)]
+///A component that simply represents an image. It may be loaded in background, drawing a placeholder until it arrives, and it may
+///have many frames, such as an animated gif, which are played with the frame clock
 pub struct Image {
     image: CandyImage,
+    ///Drawn instead of the image while it's loading, always on the bounds of the image
+    placeholder: CandySquare,
+    pending: Option<PendingImage>,
+    error: Option<std::io::Error>,
+    playback: Option<Playback>,
 }
 
 impl Component for Image {
     fn resize(&mut self, rect: Rect) {
         self.image.resize(rect);
+        self.placeholder.resize(rect);
     }
     fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
-        renderer.render_image(&self.image);
+        if self.pending.is_none() && self.error.is_none() {
+            renderer.render_image(&self.image);
+            return;
+        }
+        let bounds = self.image.bounds();
+        if self.placeholder.bounds() == bounds {
+            renderer.square(&self.placeholder);
+            return;
+        }
+        //the image was moved through its position, which doesn't reach the placeholder, so it's drawn where the image is
+        let mut placeholder = self.placeholder.clone();
+        placeholder.resize(bounds);
+        renderer.square(&placeholder);
     }
     fn apply_style(&mut self, style: &dyn Style) {
         self.image.apply_style(style);
@@ -27,12 +83,233 @@ impl Component for Image {
     fn position_mut(&mut self) -> &mut nalgebra::Vector2<f32> {
         self.image.position_mut()
     }
+    fn apply_offset(&mut self, offset: nalgebra::Vector2<f32>) {
+        *self.image.position_mut() += offset;
+        *self.placeholder.position_mut() += offset;
+    }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.placeholder.resize(self.image.bounds());
+        self.image.take_damage(damage);
+        self.placeholder.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
 }
 
 impl Image {
     ///Creates a new Image component from the provided `image` from candy
     pub fn new(image: CandyImage) -> Self {
-        Self { image }
+        let mut placeholder = CandySquare::new(*image.position(), *image.size());
+        placeholder.rule_mut().set_color(&Vector4::zeros());
+        Self {
+            image,
+            placeholder,
+            pending: None,
+            error: None,
+            playback: None,
+        }
+    }
+
+    ///Creates an Image component on the given `square` that plays the frames of `animation`, starting now. The root component must call
+    ///[Self::check_updates] on its own `check_updates`, as the window is asked to check for updates whenever the frame changes
+    pub fn animated(animation: AnimatedImage, square: CandySquare) -> Self {
+        let first = animation.frame(0).map(|frame| frame.image.clone());
+        let first = first.unwrap_or_else(|| RendererImage::from_rgba(1, 1, &[0; 4]).unwrap());
+        let mut out = Self::new(CandyImage::new(first, square));
+        out.playback = Some(Playback {
+            animation,
+            frame: 0,
+            started: None,
+            paused_at: Duration::ZERO,
+            wake: None,
+        });
+        out.play();
+        out
+    }
+
+    ///Creates an Image component on the given `square` whose image at `path` is decoded in background. Until it arrives, the placeholder
+    ///is drawn instead. The root component must call [Self::check_updates] on its own `check_updates` to receive it
+    pub fn load(path: impl Into<std::path::PathBuf>, square: CandySquare) -> Self {
+        Self::loading(PendingImage::load(path), square)
+    }
+
+    ///Creates an Image component on the given `square` whose encoded `bytes` are decoded in background. Until it arrives, the placeholder
+    ///is drawn instead. The root component must call [Self::check_updates] on its own `check_updates` to receive it
+    pub fn load_bytes(bytes: Vec<u8>, square: CandySquare) -> Self {
+        Self::loading(PendingImage::load_bytes(bytes), square)
+    }
+
+    fn loading(pending: PendingImage, square: CandySquare) -> Self {
+        //the image is blank until the real one arrives, so the square and how it's drawn can be set as usual meanwhile
+        let blank = RendererImage::from_rgba(1, 1, &[0; 4]).unwrap();
+        let mut out = Self::new(CandyImage::new(blank, square));
+        out.pending = Some(pending);
+        out
+    }
+
+    ///Checks if the image being loaded arrived, replacing the blank one with it, and if an animated image must go to another frame.
+    ///Returns whether a redraw should be made.
+    ///If it couldn't be loaded, the placeholder keeps being drawn and the error can be retrieved with [Self::load_error]
+    pub fn check_updates(&mut self) -> bool {
+        let loaded = self.receive_image();
+        let advanced = self.sync_frame();
+        loaded || advanced
+    }
+
+    ///Replaces the blank image with the one being loaded if it arrived. Returns whether it did
+    fn receive_image(&mut self) -> bool {
+        let Some(result) = self.pending.as_ref().and_then(PendingImage::poll) else {
+            return false;
+        };
+        self.pending = None;
+        match result {
+            Ok(image) => {
+                self.image.set_image(image);
+                true
+            }
+            Err(error) => {
+                self.error = Some(error);
+                false
+            }
+        }
+    }
+
+    #[inline]
+    ///Checks if the image is still being loaded
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    #[inline]
+    ///Retrieves why the image couldn't be loaded, if it couldn't
+    pub fn load_error(&self) -> Option<&std::io::Error> {
+        self.error.as_ref()
+    }
+
+    #[inline]
+    ///Retrieves how the square drawn instead of the image while it's loading looks like
+    pub fn placeholder(&self) -> &DrawRule {
+        self.placeholder.rule()
+    }
+
+    #[inline]
+    ///Applies `style` on the square drawn instead of the image while it's loading, such as a skeleton color or a spinner shader
+    pub fn set_placeholder_style(&mut self, style: &dyn Style) -> &mut Self {
+        self.placeholder.apply_style(style);
+        self
+    }
+
+    ///Cancels loading the image. The placeholder keeps being drawn
+    pub fn cancel_loading(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.cancel();
+            self.error = Some(std::io::Error::new(
+                std::io::ErrorKind::Interrupted,
+                "the image loading was cancelled",
+            ));
+        }
+    }
+    ///Draws the frame of the animation at its current time, asking the clock to wake the window when the next one is due.
+    ///Returns whether the frame changed
+    fn sync_frame(&mut self) -> bool {
+        let Some(ref mut playback) = self.playback else {
+            return false;
+        };
+        let (frame, remaining) = playback.animation.frame_at(playback.time());
+        if let (Some(_), Some(remaining)) = (playback.started, remaining) {
+            let now = Instant::now();
+            //a single wake up is requested at a time, the next one is requested when it arrives
+            if playback.wake.is_none_or(|wake| wake <= now) {
+                let wake = now + remaining;
+                clock::wake_at(wake);
+                playback.wake = Some(wake);
+            }
+        }
+        if frame == playback.frame {
+            return false;
+        }
+        playback.frame = frame;
+        if let Some(frame) = playback.animation.frame(frame) {
+            self.image.set_image(frame.image.clone());
+        }
+        true
+    }
+
+    #[inline]
+    ///Retrieves the animation played by this image, if it has got one
+    pub fn animation(&self) -> Option<&AnimatedImage> {
+        self.playback.as_ref().map(|playback| &playback.animation)
+    }
+
+    ///Plays the animation from where it was paused. Nothing happens if it's already playing or if there's no animation
+    pub fn play(&mut self) {
+        let stopped = self
+            .playback
+            .as_mut()
+            .filter(|playback| playback.started.is_none());
+        if let Some(playback) = stopped {
+            playback.started = Some(started_at(playback.paused_at));
+            playback.wake = None;
+        }
+        self.sync_frame();
+    }
+
+    ///Pauses the animation on the frame it is
+    pub fn pause(&mut self) {
+        if let Some(ref mut playback) = self.playback {
+            playback.paused_at = playback.time();
+            playback.started = None;
+        }
+    }
+
+    #[inline]
+    ///Checks if the animation is being played. It is even after its last repetition is over, until it's paused
+    pub fn is_playing(&self) -> bool {
+        self.playback
+            .as_ref()
+            .is_some_and(|playback| playback.started.is_some())
+    }
+
+    ///Moves the animation to `time` since its first frame, keeping it playing or paused. Returns whether a redraw should be made
+    pub fn seek(&mut self, time: Duration) -> bool {
+        let Some(ref mut playback) = self.playback else {
+            return false;
+        };
+        match playback.started {
+            Some(_) => playback.started = Some(started_at(time)),
+            None => playback.paused_at = time,
+        }
+        playback.wake = None;
+        self.sync_frame()
+    }
+
+    ///Moves the animation to the start of the frame at `index`, keeping it playing or paused. Returns whether a redraw should be made
+    pub fn seek_frame(&mut self, index: usize) -> bool {
+        let Some(time) = self
+            .playback
+            .as_ref()
+            .map(|playback| playback.animation.frame_start(index))
+        else {
+            return false;
+        };
+        self.seek(time)
+    }
+
+    #[inline]
+    ///Retrieves the index of the frame of the animation being drawn
+    pub fn current_frame(&self) -> usize {
+        self.playback.as_ref().map_or(0, |playback| playback.frame)
+    }
+
+    #[inline]
+    ///Retrieves the time of the animation, since its first frame
+    pub fn animation_time(&self) -> Duration {
+        self.playback
+            .as_ref()
+            .map_or(Duration::ZERO, Playback::time)
     }
 }
 
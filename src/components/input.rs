@@ -1,7 +1,7 @@
 use std::ops::{Deref, DerefMut, Range};
 
 use candy_renderers::{BiDimensionalPainter, primitives::CandySquare};
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector2;
 
 use crate::{
@@ -167,6 +167,12 @@ impl RawInput {
 
     ///Updates the cursor position on the GUI. In fact, if it did change the position, sets the cursor square to be there
     pub fn update_cursor(&mut self) {
+        //a paragraph is laid out on lines, so the cursor is placed on the one it's at
+        if let Some(line_height) = self.content.layout().map(|layout| layout.line_height()) {
+            *self.cursor_square.position_mut() = self.content.caret_position(self.cursor);
+            self.cursor_square.size_mut().y = line_height;
+            return;
+        }
         fn char_to_byte_index(s: &str, char_idx: usize) -> (usize, usize) {
             s.char_indices()
                 .nth(char_idx)
@@ -236,6 +242,12 @@ impl RawInput {
 impl Component for Input {
     fn resize(&mut self, rect: Rect) {
         self.rect.resize(rect.clone());
+        if self.content.paragraph().is_some() {
+            self.content.resize(rect);
+            self.cursor_square.size_mut().x = 1.0;
+            self.update_cursor();
+            return;
+        }
 
         let content_bounds = self.content.text_bounds();
         //y center
@@ -249,7 +261,9 @@ impl Component for Input {
     }
     fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
         renderer.square(&self.rect);
-        {
+        if self.content.paragraph().is_some() {
+            renderer.text(&self.content);
+        } else {
             let visible = self.visible_chars();
             let mut char_indices: Vec<usize> =
                 self.content().char_indices().map(|(i, _)| i).collect();
@@ -272,4 +286,94 @@ impl Component for Input {
     fn position_mut(&mut self) -> &mut Vector2<f32> {
         self.content.position_mut()
     }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.rect.take_damage(damage);
+        self.content.take_damage(damage);
+        self.cursor_square.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candy_renderers::{DrawCommand, RecordingPainter, primitives::CandyFont};
+    use skia_safe::FontMgr;
+
+    use super::*;
+
+    fn font() -> CandyFont {
+        let typeface = FontMgr::new()
+            .new_from_data(include_bytes!("../../inter.ttf"), None)
+            .unwrap();
+        CandyFont::new(typeface, 20.0)
+    }
+
+    fn record(input: &Input) -> Vec<DrawCommand> {
+        let mut painter = RecordingPainter::new();
+        input.render(&mut painter);
+        painter.take_display_list().commands().to_vec()
+    }
+
+    #[test]
+    fn draws_its_square_the_text_and_the_cursor() {
+        let mut input = Input::new(Text::new_content("hello", font()));
+        input.resize(Rect::new(0.0, 0.0, 200.0, 30.0));
+        let commands = record(&input);
+        assert_eq!(commands.len(), 3);
+
+        let DrawCommand::Square(ref square) = commands[0] else {
+            panic!("expected the square of the input, found {:?}", commands[0]);
+        };
+        assert_eq!(square.position, Vector2::zeros());
+        assert_eq!(square.size, Vector2::new(200.0, 30.0));
+        let DrawCommand::Text(ref text) = commands[1] else {
+            panic!("expected the text of the input, found {:?}", commands[1]);
+        };
+        assert_eq!(text.content, "hello");
+        assert_eq!(text.range, Some(0..5));
+        let DrawCommand::Square(ref cursor) = commands[2] else {
+            panic!("expected the cursor of the input, found {:?}", commands[2]);
+        };
+        assert_eq!(cursor.position.x, input.content.font().width_for("hello"));
+        assert_eq!(cursor.size.x, 1.0);
+    }
+
+    #[test]
+    fn draws_only_the_chars_around_the_cursor() {
+        let content = "abcdefghijklmnopqrstuvwxyz0123456789";
+        let mut input = Input::new(Text::new_content(content, font()));
+        //10 chars of the font size fit on the width
+        input.resize(Rect::new(0.0, 0.0, 100.0, 30.0));
+        let commands = record(&input);
+        let DrawCommand::Text(ref text) = commands[1] else {
+            panic!("expected the text of the input, found {:?}", commands[1]);
+        };
+        assert_eq!(text.range, Some(26..36));
+
+        input.move_left(20);
+        let commands = record(&input);
+        let DrawCommand::Text(ref text) = commands[1] else {
+            panic!("expected the text of the input, found {:?}", commands[1]);
+        };
+        assert_eq!(text.drawn_content(), "lmnopqrstu");
+        let DrawCommand::Square(ref cursor) = commands[2] else {
+            panic!("expected the cursor of the input, found {:?}", commands[2]);
+        };
+        assert_eq!(cursor.position.x, input.content.font().width_for("lmnop"));
+    }
+
+    #[test]
+    fn password_draws_its_content_hidden() {
+        let mut input = Input::new_password(Text::new_content("secret", font()));
+        input.resize(Rect::new(0.0, 0.0, 200.0, 30.0));
+        let commands = record(&input);
+        let DrawCommand::Text(ref text) = commands[1] else {
+            panic!("expected the text of the input, found {:?}", commands[1]);
+        };
+        assert_eq!(text.drawn_content(), "******");
+    }
 }
@@ -2,6 +2,9 @@ mod button;
 mod container;
 mod image;
 mod input;
+mod path;
+mod perf_hud;
+mod pixel_canvas;
 mod scrollable;
 mod solidbox;
 mod text;
@@ -11,6 +14,9 @@ pub use button::*;
 pub use container::*;
 pub use image::*;
 pub use input::*;
+pub use path::*;
+pub use perf_hud::*;
+pub use pixel_canvas::*;
 pub use scrollable::*;
 pub use solidbox::*;
 pub use text::*;
@@ -0,0 +1,62 @@
+use std::ops::{Deref, DerefMut};
+
+use candy_renderers::{BiDimensionalPainter, primitives::CandyPath};
+use candy_shared_types::{Damage, Rect, Style};
+
+use crate::ui::component::Component;
+
+#[derive(Debug)]
+///A component that draws a vector path. The path is positioned at the rect it's given, but its segments aren't scaled to it
+pub struct Path {
+    path: CandyPath,
+}
+
+impl Component for Path {
+    fn resize(&mut self, rect: Rect) {
+        self.path.resize(rect);
+    }
+    fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
+        renderer.path(&self.path);
+    }
+    fn apply_style(&mut self, style: &dyn Style) {
+        self.path.apply_style(style);
+    }
+    fn position(&self) -> nalgebra::Vector2<f32> {
+        *self.path.position()
+    }
+    fn position_mut(&mut self) -> &mut nalgebra::Vector2<f32> {
+        self.path.position_mut()
+    }
+    fn apply_offset(&mut self, offset: nalgebra::Vector2<f32>) {
+        *self.path.position_mut() += offset;
+    }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.path.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
+}
+
+impl Path {
+    ///Creates a new Path component that draws the given `path`
+    pub fn new(path: CandyPath) -> Self {
+        Self { path }
+    }
+}
+
+impl Deref for Path {
+    type Target = CandyPath;
+    fn deref(&self) -> &Self::Target {
+        &self.path
+    }
+}
+impl DerefMut for Path {
+    ///As the path is taken mutably, it's assumed it will be changed, so it's marked as dirty
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.path.mark_dirty();
+        &mut self.path
+    }
+}
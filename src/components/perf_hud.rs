@@ -0,0 +1,167 @@
+use candy_renderers::{
+    BiDimensionalPainter,
+    primitives::{CandyFont, CandySquare, CandyText},
+};
+use candy_shared_types::{Damage, Rect, Style};
+use nalgebra::{Vector2, Vector4};
+
+use crate::ui::{
+    component::Component,
+    stats::{FrameHistory, FrameStats},
+};
+
+///How many lines of stats are shown
+const LINES: usize = 6;
+
+#[derive(Debug)]
+///An overlay showing what the last frames cost: frame time, FPS, time spent laying out, rendering and flushing, the calls made on
+///the painter by kind and how many animations are running. When set on a window with `CandyWindow::set_perf_hud`, it's drawn over the
+///root component and updated after every frame, so it shows the stats of the frame before the one it's drawn on
+pub struct PerfHud {
+    background: CandySquare,
+    lines: [CandyText; LINES],
+    ///Space between the border of the background and the text
+    padding: f32,
+}
+
+impl PerfHud {
+    ///Creates a new HUD at the top left corner of the window whose text is drawn with `font`
+    pub fn new(font: CandyFont) -> Self {
+        let mut background = CandySquare::new(Vector2::zeros(), Vector2::zeros());
+        background
+            .rule_mut()
+            .set_color(&Vector4::new(0.0, 0.0, 0.0, 0.75));
+        let mut out = Self {
+            background,
+            lines: std::array::from_fn(|_| CandyText::new("", Vector2::zeros(), font.clone())),
+            padding: 6.0,
+        };
+        out.layout();
+        out
+    }
+
+    #[inline]
+    ///Sets the space between the border of the background and the text
+    pub fn set_padding(&mut self, padding: f32) -> &mut Self {
+        self.padding = padding;
+        self.layout();
+        self
+    }
+
+    #[inline]
+    ///Retrieves the background drawn behind the stats
+    pub fn background_mut(&mut self) -> &mut CandySquare {
+        &mut self.background
+    }
+
+    ///Shows the `stats` of the last frame drawn, with the average of the frames kept on `history`
+    pub fn update(&mut self, stats: &FrameStats, history: &FrameHistory) {
+        let ms = |time: std::time::Duration| time.as_secs_f32() * 1000.0;
+        let calls = &stats.calls;
+        let contents = [
+            format!(
+                "{:.0} fps ({:.0} avg)  {:.2} ms (worst {:.2} ms)",
+                stats.fps(),
+                history.average_fps(),
+                ms(stats.frame_time),
+                ms(history.worst_frame_time())
+            ),
+            format!(
+                "layout {:.2} ms  render {:.2} ms  flush {:.2} ms",
+                ms(stats.layout_time),
+                ms(stats.render_time),
+                ms(stats.flush_time)
+            ),
+            format!(
+                "draws {}  calls {}  animations {}",
+                calls.draws(),
+                calls.total(),
+                stats.animations
+            ),
+            format!(
+                "squares {}  texts {}  images {}  paths {}",
+                calls.squares, calls.texts, calls.images, calls.paths
+            ),
+            format!(
+                "sprites {}  circles {}  pictures {}  backgrounds {}",
+                calls.sprites, calls.circles, calls.pictures, calls.backgrounds
+            ),
+            format!(
+                "saves {}  layers {}  clips {}  transforms {}",
+                calls.saves, calls.layers, calls.clips, calls.transforms
+            ),
+        ];
+        for (line, content) in self.lines.iter_mut().zip(contents) {
+            if line.content() != content {
+                *line.content_mut() = content;
+            }
+        }
+        self.layout();
+    }
+
+    ///Places every line below each other and sizes the background to fit them
+    fn layout(&mut self) {
+        let origin = *self.background.position();
+        let font_size = self.lines[0].font().size();
+        let line_height = font_size * 1.3;
+        let width = self
+            .lines
+            .iter()
+            .map(|line| line.text_width())
+            .fold(0.0, f32::max);
+        for (idx, line) in self.lines.iter_mut().enumerate() {
+            line.resize(Rect::new(
+                origin.x + self.padding,
+                origin.y + self.padding + font_size + line_height * idx as f32,
+                width,
+                line_height - font_size,
+            ));
+        }
+        let size = Vector2::new(
+            width + self.padding * 2.0,
+            line_height * LINES as f32 + self.padding * 2.0,
+        );
+        if *self.background.size() != size {
+            *self.background.size_mut() = size;
+        }
+    }
+}
+
+impl Component for PerfHud {
+    ///Moves the HUD to the position of `rect`. Its size only depends on the stats shown
+    fn resize(&mut self, rect: Rect) {
+        *self.background.position_mut() = Vector2::new(rect.x, rect.y);
+        self.layout();
+    }
+    fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
+        renderer.square(&self.background);
+        for line in &self.lines {
+            renderer.text(line);
+        }
+    }
+    fn apply_style(&mut self, style: &dyn Style) {
+        self.background.apply_style(style);
+    }
+    fn position(&self) -> Vector2<f32> {
+        *self.background.position()
+    }
+    ///Retrieves the position of the HUD. The stats follow it when they're updated
+    fn position_mut(&mut self) -> &mut Vector2<f32> {
+        self.background.position_mut()
+    }
+    fn apply_offset(&mut self, offset: Vector2<f32>) {
+        *self.background.position_mut() += offset;
+        self.layout();
+    }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.background.take_damage(damage);
+        for line in &mut self.lines {
+            line.take_damage(damage);
+        }
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
+}
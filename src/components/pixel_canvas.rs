@@ -0,0 +1,205 @@
+use std::ops::{Deref, DerefMut};
+
+use candy_renderers::{
+    BiDimensionalPainter,
+    primitives::{CandyImage, CandySquare, ImageSampling, TwodCandyImg},
+};
+use candy_shared_types::{Damage, Rect, RendererImage, Style};
+
+use crate::ui::component::Component;
+
+#[derive(Debug)]
+///A component that draws a buffer of premultiplied RGBA pixels the application changes whenever it wants, such as the screen of an
+///emulator, a camera preview or a plot computed on the CPU. Changes are kept on the buffer and only sent to the image on
+///[Self::commit], copying just the region that changed when the renderer allows it.
+///It's drawn with nearest sampling by default, so pixel art stays sharp when scaled
+pub struct PixelCanvas {
+    image: CandyImage,
+    pixels: Vec<u8>,
+    width: u32,
+    height: u32,
+    ///The region changed since the last commit, as its left, top, right and bottom edges
+    dirty: Option<(u32, u32, u32, u32)>,
+}
+
+impl Component for PixelCanvas {
+    fn resize(&mut self, rect: Rect) {
+        self.image.resize(rect);
+    }
+    fn render(&self, renderer: &mut dyn BiDimensionalPainter) {
+        renderer.render_image(&self.image);
+    }
+    fn apply_style(&mut self, style: &dyn Style) {
+        self.image.apply_style(style);
+    }
+    fn position(&self) -> nalgebra::Vector2<f32> {
+        *self.image.position()
+    }
+    fn position_mut(&mut self) -> &mut nalgebra::Vector2<f32> {
+        self.image.position_mut()
+    }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.image.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
+}
+
+impl PixelCanvas {
+    ///Creates a transparent canvas with `width` x `height` pixels drawn on the given `square`. Empty sizes are made 1 pixel long, as
+    ///an image can't be empty
+    pub fn new(width: u32, height: u32, square: CandySquare) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        let pixels = vec![0; width as usize * height as usize * 4];
+        Self::from_rgba(width, height, pixels, square).expect("The canvas could not be created")
+    }
+
+    ///Creates a canvas with `width` x `height` of the given premultiplied RGBA `pixels` drawn on the given `square`. `None` if there
+    ///aren't `width * height` pixels
+    pub fn from_rgba(
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+        square: CandySquare,
+    ) -> Option<Self> {
+        let inner = RendererImage::from_rgba(width, height, &pixels)?;
+        let mut image = CandyImage::new(inner, square);
+        image.set_sampling(ImageSampling::Nearest);
+        Some(Self {
+            image,
+            pixels,
+            width,
+            height,
+            dirty: None,
+        })
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    ///Retrieves the pixels of the buffer, premultiplied RGBA row by row. They may have changed since the last commit
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[inline]
+    ///Retrieves the pixels of the buffer to be changed. As they may change anywhere, the whole buffer is sent on the next commit
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        self.mark_dirty(0, 0, self.width, self.height);
+        &mut self.pixels
+    }
+
+    ///Retrieves the pixel at (`x`, `y`), if it's inside of the buffer
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[start..start + 4].try_into().ok()
+    }
+
+    ///Sets the pixel at (`x`, `y`) to `rgba`. Pixels outside of the buffer are ignored
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let start = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[start..start + 4].copy_from_slice(&rgba);
+        self.mark_dirty(x, y, 1, 1);
+    }
+
+    ///Copies `pixels`, premultiplied RGBA rows of `width` pixels, into the region at (`x`, `y`) with the given `width` and `height`.
+    ///Returns `false`, copying nothing, if the region doesn't fit in the buffer or if there aren't `width * height` pixels
+    pub fn write_region(&mut self, x: u32, y: u32, width: u32, height: u32, pixels: &[u8]) -> bool {
+        if x.saturating_add(width) > self.width
+            || y.saturating_add(height) > self.height
+            || pixels.len() != width as usize * height as usize * 4
+        {
+            return false;
+        }
+        let row = width as usize * 4;
+        for line in 0..height as usize {
+            let start = ((y as usize + line) * self.width as usize + x as usize) * 4;
+            self.pixels[start..start + row].copy_from_slice(&pixels[line * row..(line + 1) * row]);
+        }
+        self.mark_dirty(x, y, width, height);
+        true
+    }
+
+    ///Sets every pixel to `rgba`
+    pub fn fill(&mut self, rgba: [u8; 4]) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+        self.mark_dirty(0, 0, self.width, self.height);
+    }
+
+    ///Marks the region at (`x`, `y`) with the given `width` and `height` as changed, so it's sent on the next commit
+    pub fn mark_dirty(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        let right = x.saturating_add(width).min(self.width);
+        let bottom = y.saturating_add(height).min(self.height);
+        if x >= right || y >= bottom {
+            return;
+        }
+        self.dirty = Some(match self.dirty {
+            Some((l, t, r, b)) => (l.min(x), t.min(y), r.max(right), b.max(bottom)),
+            None => (x, y, right, bottom),
+        });
+    }
+
+    #[inline]
+    ///Checks if the buffer changed since the last commit
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    ///Sends the region of the buffer that changed since the last commit to the image drawn. Returns whether a redraw should be made
+    pub fn commit(&mut self) -> bool {
+        let Some((left, top, right, bottom)) = self.dirty.take() else {
+            return false;
+        };
+        self.image.image_handler_mut().update_rgba(
+            &self.pixels,
+            left,
+            top,
+            right - left,
+            bottom - top,
+        )
+    }
+
+    ///Changes the size of the buffer to `width` x `height`, clearing it to transparent. The square it's drawn on stays the same.
+    ///Empty sizes are made 1 pixel long, like on [Self::new]
+    pub fn resize_buffer(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        self.pixels = vec![0; width as usize * height as usize * 4];
+        self.width = width;
+        self.height = height;
+        self.dirty = None;
+        if let Some(image) = RendererImage::from_rgba(width, height, &self.pixels) {
+            self.image.set_image(image);
+        }
+    }
+}
+
+impl Deref for PixelCanvas {
+    type Target = CandyImage;
+    fn deref(&self) -> &Self::Target {
+        &self.image
+    }
+}
+impl DerefMut for PixelCanvas {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.image
+    }
+}
@@ -1,7 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
 use candy_renderers::BiDimensionalPainter;
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::{Vector2, Vector4};
 
 use crate::{
@@ -88,7 +88,9 @@ impl<C: Component> Scrollable<C> {
                     height: Size::Percent(1.0),
                 }),
         };
-        let container = Container::new(config.layout, true);
+        let mut container = Container::new(config.layout, true);
+        //the content overflows on purpose, so only what's inside of the scrollable is drawn
+        container.set_clip(true);
 
         Self {
             layout,
@@ -170,16 +172,14 @@ impl<C: Component> Scrollable<C> {
 
     ///Updates the positions of the inner elements based on the accumulated offset.
     fn update_positions_accum(&mut self) {
-        for child in self.container.children_mut() {
-            child.apply_offset(Vector2::new(0.0, self.accum_offset));
-        }
+        self.container
+            .offset_children(Vector2::new(0.0, self.accum_offset));
     }
 
     ///Updates the positions of all the elements inside this scrollable based on the scroll offset
     fn update_positions(&mut self) {
-        for child in self.container.children_mut() {
-            child.apply_offset(Vector2::new(0.0, self.offset));
-        }
+        self.container
+            .offset_children(Vector2::new(0.0, self.offset));
     }
 }
 
@@ -201,10 +201,7 @@ impl<C: Component> Component for Scrollable<C> {
         let rects = self.layout.calculate(rect, true);
         self.scrollbar.resize(rects[0].clone());
         self.container.resize(rects[1].clone());
-        let calc_height = self
-            .container
-            .layout
-            .calculate_height(rects[1].clone(), true);
+        let calc_height = self.container.content_height(rects[1].clone());
         if calc_height > height {
             self.limit = height - calc_height;
         }
@@ -231,4 +228,90 @@ impl<C: Component> Component for Scrollable<C> {
         *self.container.position_mut() += offset;
         *self.scrollbar.position_mut() += offset;
     }
+
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.container.take_damage(damage);
+        self.scrollbar.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        self.container.tracks_damage() && self.scrollbar.tracks_damage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candy_renderers::{DrawCommand, RecordingPainter};
+
+    use super::*;
+
+    fn scrollable() -> Scrollable<SolidBox> {
+        let mut scrollable = Scrollable::new(ScrollableConfig {
+            scroll_bar_width: 10.0,
+            direction: Direction::Vertical,
+            layout: Layout::vertical(),
+        });
+        for _ in 0..3 {
+            scrollable.add_child(
+                SolidBox::new(&Vector4::new(1.0, 0.0, 0.0, 1.0)),
+                DefinitionRect {
+                    x: Size::Length(0.0),
+                    y: Size::Length(0.0),
+                    width: Size::Percent(1.0),
+                    height: Size::Length(50.0),
+                },
+            );
+        }
+        scrollable.resize(Rect::new(0.0, 0.0, 100.0, 100.0));
+        scrollable
+    }
+
+    ///Retrieves the kind of each command, with the position of the squares
+    fn record(scrollable: &Scrollable<SolidBox>) -> Vec<(&'static str, Vector2<f32>)> {
+        let mut painter = RecordingPainter::new();
+        scrollable.render(&mut painter);
+        painter
+            .display_list()
+            .iter()
+            .map(|command| match command {
+                DrawCommand::Square(square) => ("square", square.position),
+                DrawCommand::Save => ("save", Vector2::zeros()),
+                DrawCommand::ClipCorners { position, .. } => ("clip", *position),
+                DrawCommand::Restore => ("restore", Vector2::zeros()),
+                other => panic!("unexpected command {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clips_the_content_and_draws_the_scrollbar_after_it() {
+        assert_eq!(
+            record(&scrollable()),
+            vec![
+                ("save", Vector2::zeros()),
+                ("clip", Vector2::new(10.0, 0.0)),
+                ("square", Vector2::new(10.0, 0.0)),
+                ("square", Vector2::new(10.0, 50.0)),
+                ("square", Vector2::new(10.0, 100.0)),
+                ("restore", Vector2::zeros()),
+                ("square", Vector2::zeros()),
+            ]
+        );
+    }
+
+    #[test]
+    fn scrolling_moves_the_content_within_its_limit() {
+        let mut scrollable = scrollable();
+        assert!(scrollable.drag_offset(Vector2::new(0.0, -20.0)));
+        //the content is 50 pixels taller than the scrollable, so it can't be scrolled further
+        assert!(!scrollable.drag_offset(Vector2::new(0.0, -40.0)));
+        assert!(!scrollable.drag_offset(Vector2::new(0.0, 30.0)));
+        let squares: Vec<_> = record(&scrollable)
+            .into_iter()
+            .filter(|(kind, _)| *kind == "square")
+            .map(|(_, position)| position.y)
+            .collect();
+        assert_eq!(squares, vec![-20.0, 30.0, 80.0, 0.0]);
+    }
 }
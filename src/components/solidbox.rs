@@ -4,7 +4,7 @@ use candy_renderers::{
     BiDimensionalPainter,
     primitives::{CandySquare, DrawRule},
 };
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector4;
 
 use crate::ui::component::Component;
@@ -34,6 +34,14 @@ impl Component for SolidBox {
     fn apply_offset(&mut self, offset: nalgebra::Vector2<f32>) {
         *self.square.position_mut() += offset;
     }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.square.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
 }
 
 impl SolidBox {
@@ -49,12 +57,13 @@ impl SolidBox {
 impl Deref for SolidBox {
     type Target = DrawRule;
     fn deref(&self) -> &Self::Target {
-        &self.square.rule
+        self.square.rule()
     }
 }
 
 impl DerefMut for SolidBox {
+    ///As the rule is taken mutably, it's assumed it will be changed, so the box is marked as dirty
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.square.rule
+        self.square.rule_mut()
     }
 }
@@ -2,9 +2,9 @@ use std::ops::{Deref, DerefMut};
 
 use candy_renderers::{
     BiDimensionalPainter,
-    primitives::{CandyFont, CandyText},
+    primitives::{CandyFont, CandyText, ParagraphStyle},
 };
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector2;
 
 use crate::ui::component::Component;
@@ -32,7 +32,12 @@ impl Component for Text {
         renderer.text(&self.inner);
     }
     #[inline]
+    ///Moves the text to the position of `rect`. A paragraph is laid out on the whole of it
     fn resize(&mut self, rect: Rect) {
+        if self.inner.paragraph().is_some() {
+            self.inner.resize(rect);
+            return;
+        }
         let pos = self.position_mut();
         pos.x = rect.x;
         pos.y = rect.y;
@@ -51,6 +56,20 @@ impl Component for Text {
     fn apply_offset(&mut self, offset: Vector2<f32>) {
         *self.inner.position_mut() += offset;
     }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.inner.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
+    ///Retrieves the size of the text with its lines wrapped to the `available` width, if it's a paragraph
+    fn measure(&self, available: Vector2<f32>) -> Option<Vector2<f32>> {
+        self.inner
+            .paragraph()
+            .map(|_| self.inner.measure(Some(available.x)))
+    }
 }
 
 impl Text {
@@ -66,6 +85,11 @@ impl Text {
             inner: CandyText::new(content, Vector2::zeros(), font),
         }
     }
+    ///Lays out this text as a paragraph following `style`, so it's wrapped and aligned on the rect it's resized to
+    pub fn with_paragraph(mut self, style: ParagraphStyle) -> Self {
+        self.inner.set_paragraph(Some(style));
+        self
+    }
     ///Retrieves the content text used by candy for this Text
     pub fn inner(&self) -> &CandyText {
         &self.inner
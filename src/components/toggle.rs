@@ -1,5 +1,5 @@
 use candy_renderers::BiDimensionalPainter;
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector2;
 
 use crate::{
@@ -81,4 +81,12 @@ impl Component for Toggle {
     fn position_mut(&mut self) -> &mut Vector2<f32> {
         self.square.position_mut()
     }
+    fn take_damage(&mut self, damage: &mut Damage) {
+        self.square.take_damage(damage);
+    }
+
+    #[inline]
+    fn tracks_damage(&self) -> bool {
+        true
+    }
 }
@@ -1,5 +1,6 @@
 pub mod components;
 pub mod helpers;
+pub mod render;
 pub mod renderer;
 pub mod text;
 pub mod ui;
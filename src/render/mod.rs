@@ -0,0 +1,4 @@
+mod pdf;
+mod snapshot;
+pub use pdf::*;
+pub use snapshot::*;
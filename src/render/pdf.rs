@@ -0,0 +1,84 @@
+use candy_renderers::{BiDimensionalRenderer, PdfPageConfig, PdfRenderer};
+use candy_shared_types::Rect;
+use nalgebra::Vector2;
+
+use crate::{components::Container, ui::component::Component};
+
+///Renders `root` on every page of `pdf`, each page starting at the respective offset on `breaks`
+fn render_pages(root: &dyn Component, mut pdf: PdfRenderer, breaks: &[f32]) -> PdfRenderer {
+    for (idx, start) in breaks.iter().enumerate() {
+        if idx != 0 {
+            pdf.begin_page_at(Vector2::new(0.0, *start));
+        }
+        root.render(pdf.painter());
+    }
+    pdf
+}
+
+///Renders the `root` component into a PDF document. The component is laid out with the width of the content area of the pages and the
+///given `height`, and is split into as many pages as needed to show all of it
+pub fn print(root: &mut dyn Component, height: f32, config: PdfPageConfig) -> PdfRenderer {
+    let content = config.content_size();
+    root.resize(Rect::new(0.0, 0.0, content.x, height));
+
+    let mut breaks = vec![0.0];
+    if content.y > 0.0 {
+        let mut start = content.y;
+        while start < height {
+            breaks.push(start);
+            start += content.y;
+        }
+    }
+    render_pages(root, PdfRenderer::new(config), &breaks)
+}
+
+///Renders the given `container` into a PDF document. The container is laid out with the size of the content area of the pages and its
+///children are expected to overflow it, so a `Container` created with `ignore_overflow` or the one inside a `Scrollable` is what should be used.
+///Pages are broken before the first child that would not fit on the current page, so children are not cut in half unless they are taller
+///than a page, and before every child marked with `Container::set_page_break`. Each page only draws the children that are on it
+pub fn print_container<C: Component>(
+    container: &mut Container<C>,
+    config: PdfPageConfig,
+) -> PdfRenderer {
+    let content = config.content_size();
+    container.resize(Rect::new(0.0, 0.0, content.x, content.y));
+
+    let bounds = container.children_bounds();
+    let mut order: Vec<usize> = (0..bounds.len()).collect();
+    order.sort_by(|a, b| bounds[*a].y.total_cmp(&bounds[*b].y));
+
+    let mut breaks = vec![0.0];
+    let mut page_start = 0.0;
+    if content.y > 0.0 {
+        for idx in order {
+            let child = &bounds[idx];
+            let forced = container.has_page_break(idx);
+            if !forced && child.bottom() <= page_start + content.y {
+                continue;
+            }
+            if child.y > page_start {
+                page_start = child.y;
+                breaks.push(page_start);
+            }
+            //children taller than a page can't avoid being cut
+            while child.bottom() > page_start + content.y {
+                page_start += content.y;
+                breaks.push(page_start);
+            }
+        }
+    }
+
+    let mut pdf = PdfRenderer::new(config);
+    for (page, start) in breaks.iter().enumerate() {
+        if page != 0 {
+            pdf.begin_page_at(Vector2::new(0.0, *start));
+        }
+        //a page ends where the next one starts, so children moved to it aren't drawn cut at the bottom of this one
+        let end = breaks
+            .get(page + 1)
+            .map_or(start + content.y, |next| next.min(start + content.y));
+        let on_page = |idx: usize| bounds[idx].y < end && bounds[idx].bottom() > *start;
+        container.render_where(pdf.painter(), &on_page);
+    }
+    pdf
+}
@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use candy_renderers::{BiDimensionalRenderer, Candy2DRasterRenderer};
+use candy_shared_types::Rect;
+use nalgebra::Vector2;
+use skia_safe::{AlphaType, ColorType, EncodedImageFormat, ISize, ImageInfo, Pixmap};
+
+use crate::ui::component::Component;
+
+#[derive(Debug, Clone, PartialEq)]
+///A buffer of non premultiplied RGBA8888 pixels, stored row by row from the top left corner
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    ///Creates a new image with the given `width` and `height` whose content is `pixels`. Returns None if the amount of bytes
+    ///doesn't match `width * height * 4`
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    #[inline]
+    ///The width in pixels of this image
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    ///The height in pixels of this image
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    ///Retrieves the raw bytes of this image
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[inline]
+    ///Consumes this image and returns its raw bytes
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    ///Retrieves the RGBA color of the pixel at (`x`, `y`). Returns None if it's out of bounds
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        Some([
+            self.pixels[idx],
+            self.pixels[idx + 1],
+            self.pixels[idx + 2],
+            self.pixels[idx + 3],
+        ])
+    }
+
+    ///Encodes this image as a PNG file. Returns None if skia could not encode it
+    pub fn encode_png(&self) -> Option<Vec<u8>> {
+        let info = ImageInfo::new(
+            ISize::new(self.width as i32, self.height as i32),
+            ColorType::RGBA8888,
+            AlphaType::Unpremul,
+            None,
+        );
+        let row_bytes = info.min_row_bytes();
+        //Pixmap needs a mutable buffer even though encoding only reads it
+        let mut pixels = self.pixels.clone();
+        let pixmap = Pixmap::new(&info, &mut pixels, row_bytes)?;
+        pixmap.encode(EncodedImageFormat::PNG, None)
+    }
+
+    ///Encodes this image as PNG and writes it on the given `path`
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let Some(data) = self.encode_png() else {
+            return Err(std::io::Error::other("Could not encode the image as PNG"));
+        };
+        std::fs::write(path, data)
+    }
+}
+
+///Lays out the `root` component at (0, 0) with the given `size` and renders it on an offscreen CPU surface, returning its pixels.
+///The `scale` is the amount of pixels used for each unit of `size`, so a `scale` of 2.0 would produce an image with twice the `size`,
+///useful for high dpi thumbnails.
+pub fn snapshot(root: &mut dyn Component, size: Vector2<u32>, scale: f32) -> RgbaImage {
+    let width = (size.x as f32 * scale).round() as u32;
+    let height = (size.y as f32 * scale).round() as u32;
+    let mut renderer = Candy2DRasterRenderer::new(width, height);
+
+    root.resize(Rect::new(0.0, 0.0, size.x as f32, size.y as f32));
+    renderer.surface_mut().canvas().scale((scale, scale));
+    root.render(renderer.painter());
+    renderer.flush();
+
+    let surface_size = renderer.size();
+    RgbaImage {
+        width: surface_size.x,
+        height: surface_size.y,
+        pixels: renderer.read_pixels(),
+    }
+}
@@ -1,7 +1,10 @@
 use std::{
     collections::BTreeMap,
     ops::Deref,
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
     thread,
     time::{Duration, Instant},
 };
@@ -17,6 +20,15 @@ use crate::{
     window::{ComponentEvents, SCHEDULER},
 };
 
+///How many animations are running, or waiting for their delay, on every animation manager
+static RUNNING_ANIMATIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+///Retrieves how many animations are running, or waiting for their delay, on every [AnimationManager]
+pub fn running_animations() -> usize {
+    RUNNING_ANIMATIONS.load(Ordering::Relaxed)
+}
+
 ///A Component reference that is unsafely, send and sync to be used across threads for scheduling
 pub struct ComponentRef(*mut dyn Component);
 unsafe impl Send for ComponentRef {}
@@ -96,6 +108,7 @@ impl AnimationScheduler for AnimationManager {
                 for (ref dur, idx) in indices.drain(..).rev() {
                     if let Some(vec) = self.animations.get_mut(dur) {
                         vec.swap_remove(idx);
+                        RUNNING_ANIMATIONS.fetch_sub(1, Ordering::Relaxed);
                         if vec.is_empty() {
                             self.animations.remove(dur);
                         }
@@ -116,6 +129,7 @@ impl AnimationScheduler for AnimationManager {
             start_time: Instant::now() + config.delay,
             target: ComponentRef(target),
         };
+        RUNNING_ANIMATIONS.fetch_add(1, Ordering::Relaxed);
         if let Some(vec) = self.animations.get_mut(&animation.step_time()) {
             vec.push(anim);
         } else {
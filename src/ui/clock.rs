@@ -0,0 +1,44 @@
+use std::{collections::BTreeSet, thread, time::Instant};
+
+use flume::{RecvTimeoutError, Sender};
+use lazy_static::lazy_static;
+
+use crate::window::{ComponentEvents, SCHEDULER};
+
+lazy_static! {
+    ///The sender of the wake ups for the clock thread, which is started the first time one is requested
+    static ref CLOCK: Sender<Instant> = {
+        let (tx, rx) = flume::unbounded::<Instant>();
+        thread::spawn(move || {
+            let window = SCHEDULER.retrieve_sender();
+            let mut deadlines = BTreeSet::new();
+            loop {
+                let received = match deadlines.first() {
+                    Some(deadline) => rx.recv_deadline(*deadline),
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(deadline) => {
+                        deadlines.insert(deadline);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+                let now = Instant::now();
+                let pending = deadlines.len();
+                deadlines.retain(|deadline| *deadline > now);
+                if deadlines.len() != pending {
+                    let _ = window.send(ComponentEvents::CheckUpdates);
+                }
+            }
+        });
+        tx
+    };
+}
+
+#[inline]
+///Asks the window to check for updates at `deadline`, so things that change with time, such as the frames of an animated image, can be
+///updated by the root component on [crate::ui::component::RootComponent::check_updates]. Wake ups that are due together are sent once
+pub fn wake_at(deadline: Instant) {
+    let _ = CLOCK.send(deadline);
+}
@@ -1,5 +1,5 @@
 use candy_renderers::BiDimensionalPainter;
-use candy_shared_types::{Rect, Style};
+use candy_shared_types::{Damage, Rect, Style};
 use nalgebra::Vector2;
 use winit::{
     event::{MouseButton, MouseScrollDelta, TouchPhase},
@@ -7,6 +7,8 @@ use winit::{
     window::Window,
 };
 
+use crate::ui::stats::FrameStats;
+
 pub trait Component {
     ///Method called when some parent tries to resize this component. The `rect` parameter is the bounds calculated
     fn resize(&mut self, rect: Rect);
@@ -26,6 +28,27 @@ pub trait Component {
     fn apply_offset(&mut self, offset: Vector2<f32>) {
         *self.position_mut() += offset;
     }
+
+    ///Adds the regions of the window this component changed since it was last drawn to `damage`, marking it as clean, so only them are
+    ///repainted. Components with children add theirs as well. By default the whole window is damaged, so components that don't track
+    ///their changes are always drawn right
+    fn take_damage(&mut self, damage: &mut Damage) {
+        damage.invalidate_all();
+    }
+
+    #[inline]
+    ///Whether this component reports what changed on `take_damage`, instead of the whole window. Containers only record the children
+    ///into pictures if every one of them does, as the others would make the picture be recorded again on every frame
+    fn tracks_damage(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    ///Retrieves the size this component would like to have if it was given `available` space, such as a text that wraps its lines
+    ///to fit the width. None if it takes whatever it's given
+    fn measure(&self, _: Vector2<f32>) -> Option<Vector2<f32>> {
+        None
+    }
 }
 
 ///The root component that will be used to render all the screen. Note that mouse position is tracked by it as well
@@ -68,4 +91,8 @@ pub trait RootComponent: Component {
     fn check_updates(&mut self) -> bool {
         false
     }
+
+    #[inline]
+    ///Emitted after every frame is drawn with what it cost, such as to log slow frames. The stats of the last frames are kept by the window
+    fn on_frame(&mut self, _: &FrameStats) {}
 }
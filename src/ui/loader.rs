@@ -0,0 +1,110 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use candy_renderers::primitives::TwodCandyImg;
+use candy_shared_types::RendererImage;
+use flume::{Receiver, Sender};
+use lazy_static::lazy_static;
+
+use crate::window::{ComponentEvents, SCHEDULER};
+
+///Where an image that is loaded in background comes from
+enum ImageOrigin {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+///An image waiting to be decoded by the loader thread
+struct LoadJob {
+    origin: ImageOrigin,
+    cancelled: Arc<AtomicBool>,
+    result: Sender<std::io::Result<RendererImage>>,
+}
+
+lazy_static! {
+    ///The sender of the jobs for the loader thread, which is started the first time an image is loaded
+    static ref LOADER: Sender<LoadJob> = {
+        let (tx, rx) = flume::unbounded::<LoadJob>();
+        thread::spawn(move || {
+            let window = SCHEDULER.retrieve_sender();
+            while let Ok(job) = rx.recv() {
+                if job.cancelled.load(Ordering::Acquire) {
+                    continue;
+                }
+                let image = match job.origin {
+                    ImageOrigin::Path(path) => RendererImage::from_source(path),
+                    ImageOrigin::Bytes(bytes) => RendererImage::from_bytes(&bytes),
+                };
+                //the one waiting for it may have been dropped while it was decoded
+                if job.cancelled.load(Ordering::Acquire) || job.result.send(image).is_err() {
+                    continue;
+                }
+                let _ = window.send(ComponentEvents::CheckUpdates);
+            }
+        });
+        tx
+    };
+}
+
+#[derive(Debug)]
+///An image being decoded on the loader thread. When it's done, the window is asked to check for updates, so the root component can
+///[poll](PendingImage::poll) it on [crate::ui::component::RootComponent::check_updates]. Dropping it cancels the load
+pub struct PendingImage {
+    receiver: Receiver<std::io::Result<RendererImage>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl PendingImage {
+    ///Starts decoding the image at `path` on the loader thread
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        Self::start(ImageOrigin::Path(path.into()))
+    }
+
+    ///Starts decoding the encoded `bytes` on the loader thread
+    pub fn load_bytes(bytes: Vec<u8>) -> Self {
+        Self::start(ImageOrigin::Bytes(bytes))
+    }
+
+    fn start(origin: ImageOrigin) -> Self {
+        let (result, receiver) = flume::bounded(1);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _ = LOADER.send(LoadJob {
+            origin,
+            cancelled: cancelled.clone(),
+            result,
+        });
+        Self {
+            receiver,
+            cancelled,
+        }
+    }
+
+    #[inline]
+    ///Retrieves the image if it was already decoded, or the error it had. Once it's retrieved, it's `None` again
+    pub fn poll(&self) -> Option<std::io::Result<RendererImage>> {
+        self.receiver.try_recv().ok()
+    }
+
+    #[inline]
+    ///Cancels the load. If the image wasn't decoded yet, it won't be
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for PendingImage {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
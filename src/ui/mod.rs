@@ -1,3 +1,6 @@
 pub mod animation;
+pub mod clock;
 pub mod component;
+pub mod loader;
+pub mod stats;
 pub mod styling;
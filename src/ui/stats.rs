@@ -0,0 +1,125 @@
+use std::{collections::VecDeque, time::Duration};
+
+use candy_renderers::PainterCalls;
+
+///How many frames a [FrameHistory] keeps by default
+pub const DEFAULT_FRAME_HISTORY: usize = 120;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+///What a single frame cost to be drawn
+pub struct FrameStats {
+    ///Time since the previous frame started. Zero on the first one
+    pub frame_time: Duration,
+    ///Time spent laying out the components since the previous frame, such as when the window was resized
+    pub layout_time: Duration,
+    ///Time spent by the components reporting what changed and issuing their draw calls
+    pub render_time: Duration,
+    ///Time spent by the renderer finishing the frame and presenting it
+    pub flush_time: Duration,
+    ///The calls made on the painter while drawing the frame
+    pub calls: PainterCalls,
+    ///How many animations were running when the frame was drawn
+    pub animations: usize,
+}
+
+impl FrameStats {
+    #[inline]
+    ///Retrieves how many frames would be drawn per second if every one took as long as this one. Zero on the first frame
+    pub fn fps(&self) -> f32 {
+        let seconds = self.frame_time.as_secs_f32();
+        if seconds > 0.0 { 1.0 / seconds } else { 0.0 }
+    }
+
+    #[inline]
+    ///Retrieves the time spent on the frame itself, laying out, rendering and flushing it. When it gets close to the frame time, the
+    ///frame rate is limited by the app rather than by how often it's asked to redraw
+    pub fn busy_time(&self) -> Duration {
+        self.layout_time + self.render_time + self.flush_time
+    }
+}
+
+#[derive(Debug, Clone)]
+///The stats of the last frames drawn, from the oldest to the newest
+pub struct FrameHistory {
+    frames: VecDeque<FrameStats>,
+    capacity: usize,
+}
+
+impl Default for FrameHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_FRAME_HISTORY)
+    }
+}
+
+impl FrameHistory {
+    ///Creates an empty history that keeps the last `capacity` frames, at least one
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    ///Appends the `stats` of the frame just drawn, forgetting the oldest one if it's full
+    pub fn push(&mut self, stats: FrameStats) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(stats);
+    }
+
+    #[inline]
+    ///Retrieves the stats of the last frame drawn
+    pub fn last(&self) -> Option<&FrameStats> {
+        self.frames.back()
+    }
+
+    #[inline]
+    ///Iterates over the frames kept, from the oldest to the newest
+    pub fn iter(&self) -> std::collections::vec_deque::Iter<'_, FrameStats> {
+        self.frames.iter()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    #[inline]
+    ///Forgets every frame kept
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    ///Retrieves the frames drawn per second on average over the frames kept. Zero if there aren't enough of them
+    pub fn average_fps(&self) -> f32 {
+        //the first frame kept only marks when the span starts
+        let span: Duration = self
+            .frames
+            .iter()
+            .skip(1)
+            .map(|frame| frame.frame_time)
+            .sum();
+        let seconds = span.as_secs_f32();
+        if seconds > 0.0 {
+            (self.frames.len() - 1) as f32 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    ///Retrieves the longest time between two frames kept, which is what a user notices as a stutter
+    pub fn worst_frame_time(&self) -> Duration {
+        self.frames
+            .iter()
+            .map(|frame| frame.frame_time)
+            .max()
+            .unwrap_or_default()
+    }
+}
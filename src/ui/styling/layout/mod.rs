@@ -17,9 +17,14 @@ pub enum Direction {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum Size {
     Length(f32),
     Percent(f32),
+    ///Sized by the component itself, from what it [measures](crate::ui::component::Component::measure) on the space it's given, such
+    ///as a paragraph taking the height of its wrapped lines. Components that don't measure themselves take the whole space. Only
+    ///widths and heights can be auto, anywhere else it's zero
+    Auto,
 }
 
 impl Default for Size {
@@ -136,19 +141,23 @@ impl Layout {
         out.x = match def.x {
             Size::Length(defx) => defx + metrics.offset_x,
             Size::Percent(defx) => defx * rect.width + metrics.offset_x,
+            Size::Auto => metrics.offset_x,
         };
         out.width = match def.width {
             Size::Length(defw) => defw,
             Size::Percent(defw) => defw * rect.width,
+            Size::Auto => rect.width,
         };
 
         out.y = match def.y {
             Size::Length(defy) => defy + metrics.offset_y,
             Size::Percent(defy) => defy * rect.height + metrics.offset_y,
+            Size::Auto => metrics.offset_y,
         };
         out.height = match def.height {
             Size::Length(defh) => defh,
             Size::Percent(defh) => defh * rect.height,
+            Size::Auto => rect.height,
         };
 
         metrics.largest_x = metrics.largest_x.max(out.width);
@@ -175,19 +184,23 @@ impl Layout {
         out.x = match def.x {
             Size::Length(defx) => defx + metrics.offset_x,
             Size::Percent(defx) => defx * rect.width + metrics.offset_x,
+            Size::Auto => metrics.offset_x,
         };
         out.width = match def.width {
             Size::Length(defw) => defw,
             Size::Percent(defw) => defw * rect.width,
+            Size::Auto => rect.width,
         };
 
         out.y = match def.y {
             Size::Length(defy) => defy + metrics.offset_y,
             Size::Percent(defy) => defy * rect.height + metrics.offset_y,
+            Size::Auto => metrics.offset_y,
         };
         out.height = match def.height {
             Size::Length(defh) => defh,
             Size::Percent(defh) => defh * rect.height,
+            Size::Auto => rect.height,
         };
 
         metrics.largest_y = metrics.largest_y.max(out.height);
@@ -225,24 +238,55 @@ impl Layout {
         out
     }
 
+    ///Resolves the [auto](Size::Auto) width and height of `def` into lengths, from what `measure` retrieves for the space of the parent
+    ///`rect`. The height is measured on the width it ends up with, so wrapped content gets taller. `None` if there's nothing to resolve
+    fn resolve_auto(
+        def: &DefinitionRect,
+        rect: &Rect,
+        measure: impl Fn(Vector2<f32>) -> Option<Vector2<f32>>,
+    ) -> Option<DefinitionRect> {
+        if def.width != Size::Auto && def.height != Size::Auto {
+            return None;
+        }
+        let mut out = def.clone();
+        if out.width == Size::Auto {
+            out.width = measure(Vector2::new(rect.width, rect.height))
+                .map_or(Size::Auto, |size| Size::Length(size.x));
+        }
+        if out.height == Size::Auto {
+            let width = match out.width {
+                Size::Length(width) => width,
+                Size::Percent(width) => width * rect.width,
+                Size::Auto => rect.width,
+            };
+            out.height = measure(Vector2::new(width, rect.height))
+                .map_or(Size::Auto, |size| Size::Length(size.y));
+        }
+        Some(out)
+    }
+
     ///Calculates the padding in pixels of this Layout based on the provided `rect` if some is Percent
     pub fn calculate_padding(&self, rect: &Rect) -> Vector4<f32> {
         let x = match self.padding.x {
             Size::Length(x) => x,
             Size::Percent(x) => rect.width * x,
+            Size::Auto => 0.0,
         };
         let y = match self.padding.y {
             Size::Length(y) => y,
             Size::Percent(y) => rect.height * y,
+            Size::Auto => 0.0,
         };
 
         let r = match self.padding.z {
             Size::Length(z) => z,
             Size::Percent(z) => rect.width * z,
+            Size::Auto => 0.0,
         };
         let b = match self.padding.w {
             Size::Length(w) => w,
             Size::Percent(w) => rect.height * w,
+            Size::Auto => 0.0,
         };
         Vector4::new(x, y, r, b)
     }
@@ -252,18 +296,32 @@ impl Layout {
         let x = match self.gap.x {
             Size::Length(gx) => gx,
             Size::Percent(gx) => gx * rect.width,
+            Size::Auto => 0.0,
         };
         let y = match self.gap.y {
             Size::Length(gy) => gy,
             Size::Percent(gy) => gy * rect.height,
+            Size::Auto => 0.0,
         };
         Vector2::new(x, y)
     }
 
+    #[inline]
     ///Calculates this layout based on its values and the boxes defined. Note that it will generate N Rects, where N is the amount of boxes added before calculating it.
     ///The boxes are in order of pushing, so the Nth Rect on the out vector is correspondent to the Nth push.
     ///On `ignore_overflow` true, the content will be overflowed and will follow `rect` yet, but may contain out of bounds boxes
-    pub fn calculate(&self, mut rect: Rect, ignore_overflow: bool) -> Vec<Rect> {
+    pub fn calculate(&self, rect: Rect, ignore_overflow: bool) -> Vec<Rect> {
+        self.calculate_measured(rect, ignore_overflow, &|_, _| None)
+    }
+
+    ///Calculates this layout like [Self::calculate], but [auto](Size::Auto) sizes are taken from `measure`, which is given the index
+    ///of the box and the space available to it
+    pub fn calculate_measured(
+        &self,
+        mut rect: Rect,
+        ignore_overflow: bool,
+        measure: &dyn Fn(usize, Vector2<f32>) -> Option<Vector2<f32>>,
+    ) -> Vec<Rect> {
         let mut out = Vec::with_capacity(self.boxes.len());
 
         {
@@ -277,7 +335,9 @@ impl Layout {
         let gap = self.calculate_gap(&rect);
         let mut metrics = CalculationMetrics::new(rect.x, rect.y);
 
-        for def in &self.boxes {
+        for (idx, def) in self.boxes.iter().enumerate() {
+            let resolved = Self::resolve_auto(def, &rect, |available| measure(idx, available));
+            let def = resolved.as_ref().unwrap_or(def);
             out.push(Self::calc_definition(
                 &mut metrics,
                 &self.corner,
@@ -291,9 +351,21 @@ impl Layout {
         out
     }
 
+    #[inline]
     ///Calculates the height of the layout based on the provided `rect`. Note that it will return the sum of the height of all the N boxes including anything that would generate
     ///gaps between them.
-    pub fn calculate_height(&mut self, mut rect: Rect, ignore_overflow: bool) -> f32 {
+    pub fn calculate_height(&mut self, rect: Rect, ignore_overflow: bool) -> f32 {
+        self.calculate_height_measured(rect, ignore_overflow, &|_, _| None)
+    }
+
+    ///Calculates the height of the layout like [Self::calculate_height], but [auto](Size::Auto) sizes are taken from `measure`, as on
+    ///[Self::calculate_measured]
+    pub fn calculate_height_measured(
+        &mut self,
+        mut rect: Rect,
+        ignore_overflow: bool,
+        measure: &dyn Fn(usize, Vector2<f32>) -> Option<Vector2<f32>>,
+    ) -> f32 {
         if !ignore_overflow {
             rect.height
         } else {
@@ -307,7 +379,9 @@ impl Layout {
             }
             let gap = self.calculate_gap(&rect);
             let mut metrics = CalculationMetrics::new(rect.x, rect.y);
-            for def in &self.boxes {
+            for (idx, def) in self.boxes.iter().enumerate() {
+                let resolved = Self::resolve_auto(def, &rect, |available| measure(idx, available));
+                let def = resolved.as_ref().unwrap_or(def);
                 out += Self::calc_definition(
                     &mut metrics,
                     &self.corner,
@@ -323,3 +397,73 @@ impl Layout {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(width: Size, height: Size) -> DefinitionRect {
+        DefinitionRect {
+            x: Size::Length(0.0),
+            y: Size::Length(0.0),
+            width,
+            height,
+        }
+    }
+
+    fn layout() -> Layout {
+        let mut layout = Layout::vertical();
+        layout
+            .with_gap(Vector2::new(Size::Length(0.0), Size::Length(5.0)))
+            .with_definition(def(Size::Auto, Size::Auto))
+            .with_definition(def(Size::Length(20.0), Size::Auto))
+            .with_definition(def(Size::Auto, Size::Length(10.0)));
+        layout
+    }
+
+    ///Measures the first box as a wide line, the second one as wrapped text as tall as it's wide and the last one as nothing
+    fn measure(idx: usize, available: Vector2<f32>) -> Option<Vector2<f32>> {
+        match idx {
+            0 => Some(Vector2::new(available.x.min(60.0), 30.0)),
+            1 => Some(Vector2::new(999.0, available.x)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn auto_sizes_take_the_whole_space_without_measuring() {
+        let rects = layout().calculate(Rect::new(0.0, 0.0, 100.0, 1000.0), true);
+        assert_eq!(rects[0], Rect::new(0.0, 0.0, 100.0, 1000.0));
+        assert_eq!(rects[1], Rect::new(0.0, 1005.0, 20.0, 1000.0));
+    }
+
+    #[test]
+    fn auto_sizes_are_measured() {
+        let rects = layout().calculate_measured(Rect::new(0.0, 0.0, 100.0, 200.0), false, &measure);
+        assert_eq!(
+            rects,
+            vec![
+                Rect::new(0.0, 0.0, 60.0, 30.0),
+                //its height is measured on the width it's given
+                Rect::new(0.0, 35.0, 20.0, 20.0),
+                Rect::new(0.0, 60.0, 100.0, 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn auto_sizes_are_measured_inside_of_the_padding() {
+        let mut layout = layout();
+        layout.with_padding(Vector4::repeat(Size::Length(10.0)));
+        let rects = layout.calculate_measured(Rect::new(0.0, 0.0, 100.0, 200.0), false, &measure);
+        assert_eq!(rects[0], Rect::new(10.0, 10.0, 60.0, 30.0));
+        assert_eq!(rects[2], Rect::new(10.0, 70.0, 80.0, 10.0));
+    }
+
+    #[test]
+    fn height_adds_the_measured_heights() {
+        let height =
+            layout().calculate_height_measured(Rect::new(0.0, 0.0, 100.0, 200.0), true, &measure);
+        assert_eq!(height, 60.0);
+    }
+}
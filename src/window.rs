@@ -1,12 +1,23 @@
-use candy_renderers::{BiDimensionalRenderer, CandyRenderer};
-use candy_shared_types::Rect;
+use std::time::{Duration, Instant};
+
+use candy_renderers::{
+    BiDimensionalPainter, BiDimensionalRenderer, CandyRenderer, CountingPainter,
+};
+use candy_shared_types::{Damage, Rect};
 use flume::unbounded;
 use lazy_static::lazy_static;
 
 use nalgebra::Vector2;
 use winit::{event_loop::EventLoop, window::WindowAttributes};
 
-use crate::ui::component::RootComponent;
+use crate::{
+    components::PerfHud,
+    ui::{
+        animation::manager::running_animations,
+        component::{Component, RootComponent},
+        stats::{FrameHistory, FrameStats},
+    },
+};
 
 use flume::{Receiver, Sender};
 
@@ -49,6 +60,16 @@ where
 {
     handler: Option<(Root, Renderer)>,
     attribs: WindowAttributes,
+    ///The regions changed since the last frame that weren't taken from the root component, such as the whole window when it's resized
+    damage: Damage,
+    ///The stats of the last frames drawn
+    stats: FrameHistory,
+    ///When the last frame started
+    last_frame: Option<Instant>,
+    ///Time spent laying out the root component since the last frame
+    layout_time: Duration,
+    ///The overlay showing the stats, if enabled
+    hud: Option<PerfHud>,
 }
 impl<Root: RootComponent, R> CandyWindow<Root, R>
 where
@@ -58,9 +79,41 @@ where
         Self {
             handler: None,
             attribs,
+            damage: Damage::full(),
+            stats: FrameHistory::default(),
+            last_frame: None,
+            layout_time: Duration::ZERO,
+            hud: None,
         }
     }
 
+    #[inline]
+    ///Enables the given performance `hud`, drawn over the root component
+    pub fn with_perf_hud(mut self, hud: PerfHud) -> Self {
+        self.set_perf_hud(Some(hud));
+        self
+    }
+
+    ///Sets the performance `hud` drawn over the root component. `None` disables it
+    pub fn set_perf_hud(&mut self, hud: Option<PerfHud>) -> &mut Self {
+        //the area of the hud being removed is only known by the hud itself
+        self.damage.invalidate_all();
+        self.hud = hud;
+        self
+    }
+
+    #[inline]
+    ///Retrieves the performance hud drawn over the root component, if enabled
+    pub fn perf_hud_mut(&mut self) -> Option<&mut PerfHud> {
+        self.hud.as_mut()
+    }
+
+    #[inline]
+    ///Retrieves the stats of the last frames drawn. They're collected even if the performance hud isn't enabled
+    pub fn frame_stats(&self) -> &FrameHistory {
+        &self.stats
+    }
+
     pub fn run(&mut self) {
         let lp = EventLoop::with_user_event().build().unwrap();
         #[cfg(feature = "opengl")]
@@ -109,14 +162,40 @@ where
     }
 }
 
+///Draws the `root` component and the `hud` over it, if any
+fn render_frame<Root: RootComponent>(
+    root: &Root,
+    hud: Option<&PerfHud>,
+    painter: &mut dyn BiDimensionalPainter,
+) {
+    root.render(painter);
+    if let Some(hud) = hud {
+        hud.render(painter);
+    }
+}
+
 impl<Root, R> winit::application::ApplicationHandler<ComponentEvents> for CandyWindow<Root, R>
 where
     Root: RootComponent,
     R: CandyRenderer,
 {
-    fn resumed(&mut self, _: &winit::event_loop::ActiveEventLoop) {
+    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        //without opengl the window is created once the event loop is running, and the renderer presents to it on its own
         #[cfg(not(feature = "opengl"))]
-        println!("gayzinho");
+        if self.handler.is_none() {
+            let window = event_loop
+                .create_window(self.attribs.clone())
+                .expect("Window could not be created.");
+            let renderer = R::from_window(&window).expect(
+                "The renderer can't present to a window without opengl, enable tiny-skia or vello",
+            );
+            self.handler = Some((
+                Root::new(window, <Root as RootComponent>::Args::default()),
+                renderer,
+            ));
+        }
+        #[cfg(feature = "opengl")]
+        let _ = event_loop;
     }
 
     fn user_event(&mut self, _: &winit::event_loop::ActiveEventLoop, event: ComponentEvents) {
@@ -147,11 +226,56 @@ where
             let (handler, renderer) = (&mut handler.0, &mut handler.1);
             match event {
                 winit::event::WindowEvent::RedrawRequested => {
-                    handler.render(renderer.twod_renderer().painter());
+                    let start = Instant::now();
+                    let frame_time = self
+                        .last_frame
+                        .replace(start)
+                        .map(|last| start - last)
+                        .unwrap_or_default();
+                    handler.take_damage(&mut self.damage);
+                    if let Some(ref mut hud) = self.hud {
+                        hud.take_damage(&mut self.damage);
+                    }
+                    let mut damage = self.damage.take();
+                    //nothing changed, so the redraw was asked by the system, such as when the window is uncovered
+                    if damage.is_empty() {
+                        damage.invalidate_all();
+                    }
+                    let twod = renderer.twod_renderer();
+                    let repaint = twod.begin_frame(&damage);
+                    let mut painter = CountingPainter::new(twod.painter());
+                    if repaint.is_full() {
+                        render_frame(handler, self.hud.as_ref(), &mut painter);
+                    } else {
+                        //the tree is drawn once, clipped to every rect that must be repainted, as everything outside of the clip is
+                        //skipped
+                        painter.save();
+                        painter.clip_rects(repaint.rects());
+                        render_frame(handler, self.hud.as_ref(), &mut painter);
+                        painter.restore();
+                    }
+                    let calls = *painter.calls();
+                    let rendered = Instant::now();
                     renderer.flush();
+                    let stats = FrameStats {
+                        frame_time,
+                        layout_time: std::mem::take(&mut self.layout_time),
+                        render_time: rendered - start,
+                        flush_time: rendered.elapsed(),
+                        calls,
+                        animations: running_animations(),
+                    };
+                    self.stats.push(stats);
+                    if let Some(ref mut hud) = self.hud {
+                        hud.update(&stats, &self.stats);
+                    }
+                    handler.on_frame(&stats);
                 }
                 winit::event::WindowEvent::Resized(size) => {
+                    self.damage.invalidate_all();
+                    let start = Instant::now();
                     handler.resize(Rect::new(0.0, 0.0, size.width as f32, size.height as f32));
+                    self.layout_time += start.elapsed();
                     renderer.resize(handler.window(), size.width, size.height);
                 }
                 winit::event::WindowEvent::CloseRequested => {